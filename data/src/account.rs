@@ -2,10 +2,73 @@
 
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum AccountType {
+    Checking,
+    Savings,
+    Cash,
+    CreditCard,
+    Loan,
+    Investment,
+}
+
+impl AccountType {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            AccountType::Checking => 0,
+            AccountType::Savings => 1,
+            AccountType::Cash => 2,
+            AccountType::CreditCard => 3,
+            AccountType::Loan => 4,
+            AccountType::Investment => 5,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<AccountType> {
+        match value {
+            0 => Some(AccountType::Checking),
+            1 => Some(AccountType::Savings),
+            2 => Some(AccountType::Cash),
+            3 => Some(AccountType::CreditCard),
+            4 => Some(AccountType::Loan),
+            5 => Some(AccountType::Investment),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AccountType::Checking => "Checking",
+            AccountType::Savings => "Savings",
+            AccountType::Cash => "Cash",
+            AccountType::CreditCard => "Credit Card",
+            AccountType::Loan => "Loan",
+            AccountType::Investment => "Investment",
+        }
+    }
+
+    // Money for spending is budgeted, loans and investments are usually only tracked
+    pub fn default_on_budget(&self) -> bool {
+        !matches!(self, AccountType::Loan | AccountType::Investment)
+    }
+
+    // Money owed rather than owned, counted against net worth
+    pub fn is_liability(&self) -> bool {
+        matches!(self, AccountType::CreditCard | AccountType::Loan)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Account {
     pub id: i64,
     pub display_name: String,
+    pub account_type: AccountType,
+
+    // Tracking accounts, off budget, count toward no category and not toward ready to assign
+    pub on_budget: bool,
+
+    // Hidden from the account list. Only accounts with nothing in them can be closed.
+    pub closed: bool,
 }
 
 impl Account {
@@ -13,6 +76,17 @@ impl Account {
         Account {
             id: 0,
             display_name: name.to_string(),
+            account_type: AccountType::Checking,
+            on_budget: true,
+            closed: false,
+        }
+    }
+
+    pub fn new_typed(name: &str, account_type: AccountType) -> Account {
+        Account {
+            account_type,
+            on_budget: account_type.default_on_budget(),
+            ..Account::new(name)
         }
     }
 }
@@ -21,7 +95,33 @@ impl Account {
 pub struct AccountDisplay {
     pub account_id: i64,
     pub display_name: String,
+    pub account_type: AccountType,
+    pub on_budget: bool,
+
+    // Every transaction, the working balance
     pub balance: i64,
+
+    // Only cleared and reconciled transactions, what the bank should show
+    pub cleared_balance: i64,
+}
+
+// Outcome of checking an account against a bank statement
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ReconcileSummary {
+    pub account_id: i64,
+
+    // unix timestamp, transactions on this day count
+    pub statement_date: i64,
+    pub statement_balance: i64,
+
+    // Of the cleared and reconciled transactions up to the statement date
+    pub cleared_balance: i64,
+
+    // statement_balance - cleared_balance. Nothing is locked unless this is 0.
+    pub difference: i64,
+
+    // Transactions locked by this reconcile
+    pub reconciled_count: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
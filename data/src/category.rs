@@ -2,11 +2,25 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::category_target::CategoryTarget;
+
+// category_id of transactions without a category. Their money is Ready to Assign.
+pub const UNCATEGORIZED_ID: i64 = 0;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct Category {
     pub display_name: String,
     pub balance: i64,
     pub id: i64,
+
+    // The credit card account this category saves up payments for, 0 for ordinary categories
+    pub payment_account_id: i64,
+
+    // See crate::category_group, UNGROUPED_ID when not in a group
+    pub group_id: i64,
+
+    // Order within the group, lowest first
+    pub sort_order: i64,
 }
 
 impl Category {
@@ -15,6 +29,9 @@ impl Category {
             display_name: name.to_string(),
             balance: 0,
             id: 0,
+            payment_account_id: 0,
+            group_id: 0,
+            sort_order: 0,
         }
     }
 
@@ -42,6 +59,28 @@ impl CategoryList {
 pub struct CategoryDisplay {
     pub category_id: i64,
     pub display_name: String,
+
+    // See Category::payment_account_id
+    pub payment_account_id: i64,
+
+    // See Category::group_id and Category::sort_order
+    pub group_id: i64,
+    pub sort_order: i64,
+
     pub transaction_average: f64,
     pub transaction_total: i64,
+
+    // Assigned this month
+    pub assigned: i64,
+
+    // Net money moved in from other categories this month
+    pub transferred: i64,
+
+    // Everything assigned, transferred and spent up to the end of this month
+    pub available: i64,
+
+    pub target: Option<CategoryTarget>,
+
+    // Still to assign this month to meet the target, 0 without one
+    pub target_needed: i64,
 }
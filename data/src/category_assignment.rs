@@ -0,0 +1,24 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+// Money given to a category for one month. There is at most one per category and month.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CategoryAssignment {
+    pub category_id: i64,
+
+    // see crate::month_key
+    pub month: i64,
+
+    pub amount: i64,
+}
+
+impl CategoryAssignment {
+    pub fn new(category_id: i64, month: i64, amount: i64) -> CategoryAssignment {
+        CategoryAssignment {
+            category_id,
+            month,
+            amount,
+        }
+    }
+}
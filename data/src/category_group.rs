@@ -0,0 +1,160 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::category::CategoryDisplay;
+
+// group_id of categories not in any group, they are listed before the groups
+pub const UNGROUPED_ID: i64 = 0;
+
+// A heading categories are listed under, like "Bills" or "Savings Goals"
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CategoryGroup {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub display_name: String,
+
+    // Groups are listed from lowest to highest
+    pub sort_order: i64,
+
+    // Only the subtotals are shown
+    pub collapsed: bool,
+}
+
+impl CategoryGroup {
+    pub fn new(name: &str) -> CategoryGroup {
+        CategoryGroup {
+            id: 0,
+            display_name: name.to_string(),
+            sort_order: 0,
+            collapsed: false,
+        }
+    }
+}
+
+// A group with its categories for one month, and their totals
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryGroupDisplay {
+    // id UNGROUPED_ID for the categories not in a group
+    pub group: CategoryGroup,
+
+    pub categories: Vec<CategoryDisplay>,
+
+    pub assigned: i64,
+    pub transaction_total: i64,
+    pub available: i64,
+}
+
+impl CategoryGroupDisplay {
+    fn new(group: CategoryGroup) -> CategoryGroupDisplay {
+        CategoryGroupDisplay {
+            group,
+            categories: vec![],
+            assigned: 0,
+            transaction_total: 0,
+            available: 0,
+        }
+    }
+
+    fn push(&mut self, category: CategoryDisplay) {
+        self.assigned += category.assigned;
+        self.transaction_total += category.transaction_total;
+        self.available += category.available;
+        self.categories.push(category);
+    }
+}
+
+// Sort categories under their groups, keeping the order of both lists. The ungrouped categories
+// come first, and only if there are any. Categories of a group that isn't in groups are ungrouped.
+pub fn group_categories(
+    groups: Vec<CategoryGroup>,
+    categories: Vec<CategoryDisplay>,
+) -> Vec<CategoryGroupDisplay> {
+    let mut ungrouped = CategoryGroupDisplay::new(CategoryGroup::new(""));
+    let mut ret: Vec<CategoryGroupDisplay> =
+        groups.into_iter().map(CategoryGroupDisplay::new).collect();
+
+    for c in categories {
+        match ret.iter_mut().find(|g| g.group.id == c.group_id) {
+            Some(g) => g.push(c),
+            None => ungrouped.push(c),
+        }
+    }
+
+    if !ungrouped.categories.is_empty() {
+        ret.insert(0, ungrouped);
+    }
+    return ret;
+}
+
+#[cfg(test)]
+fn test_category(
+    category_id: i64,
+    group_id: i64,
+    assigned: i64,
+    available: i64,
+) -> CategoryDisplay {
+    return CategoryDisplay {
+        category_id,
+        display_name: format!("category {category_id}"),
+        payment_account_id: 0,
+        group_id,
+        sort_order: 0,
+        transaction_average: 0.0,
+        transaction_total: available - assigned,
+        assigned,
+        transferred: 0,
+        available,
+        target: None,
+        target_needed: 0,
+    };
+}
+
+#[test]
+fn category_group_subtotals() {
+    let mut bills = CategoryGroup::new("Bills");
+    bills.id = 2;
+    let mut everyday = CategoryGroup::new("Everyday");
+    everyday.id = 1;
+
+    let grouped = group_categories(
+        vec![bills, everyday],
+        vec![
+            test_category(1, 1, 100, 50),
+            test_category(2, 2, 300, 300),
+            test_category(3, 1, 200, -20),
+            test_category(4, 0, 10, 10),
+            test_category(5, 9, 5, 5),
+        ],
+    );
+
+    let names: Vec<&str> = grouped
+        .iter()
+        .map(|g| g.group.display_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["", "Bills", "Everyday"]);
+
+    // Unknown groups count as ungrouped
+    let ids: Vec<i64> = grouped[0]
+        .categories
+        .iter()
+        .map(|c| c.category_id)
+        .collect();
+    assert_eq!(ids, vec![4, 5]);
+
+    let ids: Vec<i64> = grouped[2]
+        .categories
+        .iter()
+        .map(|c| c.category_id)
+        .collect();
+    assert_eq!(ids, vec![1, 3]);
+    assert_eq!(grouped[2].assigned, 300);
+    assert_eq!(grouped[2].available, 30);
+    assert_eq!(grouped[2].transaction_total, -270);
+
+    // Empty groups are kept, no ungrouped heading without categories
+    let grouped = group_categories(vec![CategoryGroup::new("Savings Goals")], vec![]);
+    assert_eq!(grouped.len(), 1);
+    assert!(grouped[0].categories.is_empty());
+}
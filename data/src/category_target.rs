@@ -0,0 +1,163 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::RytError;
+
+// What a category is saving up for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum TargetKind {
+    // Assign amount every month
+    MonthlyAmount,
+
+    // Have amount available by the end of target_month, assigning the same each month until then
+    BalanceByDate,
+
+    // Top available back up to amount every month
+    RefillUpTo,
+}
+
+impl TargetKind {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            TargetKind::MonthlyAmount => 0,
+            TargetKind::BalanceByDate => 1,
+            TargetKind::RefillUpTo => 2,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<TargetKind> {
+        match value {
+            0 => Some(TargetKind::MonthlyAmount),
+            1 => Some(TargetKind::BalanceByDate),
+            2 => Some(TargetKind::RefillUpTo),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TargetKind::MonthlyAmount => "Monthly Amount",
+            TargetKind::BalanceByDate => "Balance by Date",
+            TargetKind::RefillUpTo => "Refill Up To",
+        }
+    }
+}
+
+// A category has at most one target
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CategoryTarget {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub category_id: i64,
+    pub kind: TargetKind,
+
+    // Positive, in cents
+    pub amount: i64,
+
+    // Budget month key (see crate::month_key) of a TargetKind::BalanceByDate, None otherwise
+    pub target_month: Option<i64>,
+}
+
+// Months from one month key to another, negative when to is earlier
+fn months_between(from: i64, to: i64) -> i64 {
+    return (to / 100 - from / 100) * 12 + (to % 100 - from % 100);
+}
+
+impl CategoryTarget {
+    pub fn new(category_id: i64, kind: TargetKind, amount: i64) -> CategoryTarget {
+        CategoryTarget {
+            id: 0,
+            category_id,
+            kind,
+            amount,
+            target_month: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), RytError> {
+        if self.amount <= 0 {
+            return Err(RytError::TargetAmountNotPositive);
+        }
+        if self.kind == TargetKind::BalanceByDate && self.target_month.is_none() {
+            return Err(RytError::TargetMissingMonth);
+        }
+        Ok(())
+    }
+
+    // How much has to be assigned in month in total to stay on target. assigned and available
+    // are the category's for that month, see CategoryDisplay.
+    pub fn goal(&self, month: i64, assigned: i64, available: i64) -> i64 {
+        let ret = match self.kind {
+            TargetKind::MonthlyAmount => self.amount,
+            TargetKind::RefillUpTo => assigned + self.amount - available,
+            TargetKind::BalanceByDate => {
+                // What was there before this month's assignment, spread over the months left.
+                // Past the date the whole rest is due.
+                let before = available - assigned;
+                let months_left = match self.target_month {
+                    Some(v) => (months_between(month, v) + 1).max(1),
+                    None => 1,
+                };
+                let remaining = (self.amount - before).max(0);
+                (remaining + months_left - 1) / months_left
+            }
+        };
+        return ret.max(0);
+    }
+
+    // Still to assign in month, 0 once the target is met
+    pub fn needed(&self, month: i64, assigned: i64, available: i64) -> i64 {
+        return (self.goal(month, assigned, available) - assigned).max(0);
+    }
+
+    // 0 to 100, how much of the month's goal is assigned
+    pub fn progress_percent(&self, month: i64, assigned: i64, available: i64) -> i64 {
+        let goal = self.goal(month, assigned, available);
+        if goal == 0 {
+            return 100;
+        }
+        return (assigned * 100 / goal).clamp(0, 100);
+    }
+}
+
+#[test]
+fn target_needed() {
+    let monthly = CategoryTarget::new(1, TargetKind::MonthlyAmount, 10_000);
+    assert_eq!(monthly.needed(202401, 0, 5_000), 10_000);
+    assert_eq!(monthly.needed(202401, 4_000, 9_000), 6_000);
+    assert_eq!(monthly.needed(202401, 12_000, 12_000), 0);
+    assert_eq!(monthly.progress_percent(202401, 4_000, 9_000), 40);
+
+    // Only what's missing from available
+    let refill = CategoryTarget::new(1, TargetKind::RefillUpTo, 50_000);
+    assert_eq!(refill.needed(202401, 0, 30_000), 20_000);
+    assert_eq!(refill.needed(202401, 5_000, 35_000), 15_000);
+    assert_eq!(refill.needed(202401, 0, 60_000), 0);
+    assert_eq!(refill.progress_percent(202401, 0, 60_000), 100);
+
+    // 1200 by the end of June, 200 already saved, so 1000 over 5 months from February
+    let mut by_date = CategoryTarget::new(1, TargetKind::BalanceByDate, 120_000);
+    by_date.target_month = Some(202406);
+    assert_eq!(by_date.needed(202402, 0, 20_000), 20_000);
+    assert_eq!(by_date.needed(202402, 5_000, 25_000), 15_000);
+
+    // Across a year, and everything left once the month has passed
+    by_date.target_month = Some(202501);
+    assert_eq!(by_date.needed(202412, 0, 0), 60_000);
+    assert_eq!(by_date.needed(202503, 0, 100_000), 20_000);
+    assert_eq!(by_date.needed(202503, 0, 130_000), 0);
+}
+
+#[test]
+fn target_validate() {
+    let mut target = CategoryTarget::new(1, TargetKind::BalanceByDate, 0);
+    assert_eq!(target.validate(), Err(RytError::TargetAmountNotPositive));
+
+    target.amount = 100;
+    assert_eq!(target.validate(), Err(RytError::TargetMissingMonth));
+
+    target.target_month = Some(202412);
+    assert_eq!(target.validate(), Ok(()));
+}
@@ -2,32 +2,32 @@
 
 use serde::{Deserialize, Serialize};
 
+// Money moved from one category's available balance to another's
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct CategoryTransfer {
+    // rowid, 0 until inserted
+    pub id: i64,
+
     pub source: i64,
     pub dest: i64,
     pub amount: i64,
-}
 
-impl CategoryTransfer {
-    pub fn to_json_string(&self) -> String {
-        return serde_json::to_string(self).unwrap();
-    }
-}
-
-/*
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CategoryList {
-    pub categories: Vec<Category>,
+    // Budget month the money moved in, see crate::month_key
+    pub month: i64,
 }
 
-impl CategoryList {
-    pub fn new() -> CategoryList {
-        CategoryList { categories: vec![] }
+impl CategoryTransfer {
+    pub fn new(source: i64, dest: i64, amount: i64, month: i64) -> CategoryTransfer {
+        CategoryTransfer {
+            id: 0,
+            source,
+            dest,
+            amount,
+            month,
+        }
     }
 
     pub fn to_json_string(&self) -> String {
         return serde_json::to_string(self).unwrap();
     }
 }
-*/
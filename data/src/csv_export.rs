@@ -0,0 +1,97 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::import_profile::AmountFormat;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum ExportColumn {
+    Date,
+    Payee,
+    Account,
+    Category,
+    Notes,
+
+    // One column for AmountFormat::Signed, outflow and inflow columns for AmountFormat::OutflowInflow
+    Amount,
+}
+
+impl ExportColumn {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportColumn::Date => "Date",
+            ExportColumn::Payee => "Payee",
+            ExportColumn::Account => "Account",
+            ExportColumn::Category => "Category",
+            ExportColumn::Notes => "Notes",
+            ExportColumn::Amount => "Amount",
+        }
+    }
+}
+
+// What goes into a CSV export and which transactions are included
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CsvExportOptions {
+    // In the order they are written
+    pub columns: Vec<ExportColumn>,
+
+    pub amount_format: AmountFormat,
+
+    pub account_id: Option<i64>,
+
+    // Some(UNCATEGORIZED_ID) exports only uncategorized transactions
+    pub category_id: Option<i64>,
+
+    // unix timestamps, both inclusive
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+}
+
+impl CsvExportOptions {
+    // Everything, laid out so the file reads back in with ImportProfile::new_default
+    pub fn new_default() -> CsvExportOptions {
+        CsvExportOptions {
+            columns: vec![
+                ExportColumn::Account,
+                ExportColumn::Date,
+                ExportColumn::Payee,
+                ExportColumn::Amount,
+                ExportColumn::Category,
+                ExportColumn::Notes,
+            ],
+            amount_format: AmountFormat::OutflowInflow,
+            account_id: None,
+            category_id: None,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    // Column names for the first line of the file
+    pub fn header(&self) -> Vec<String> {
+        let mut ret: Vec<String> = vec![];
+        for c in &self.columns {
+            match (c, self.amount_format) {
+                (ExportColumn::Amount, AmountFormat::OutflowInflow) => {
+                    ret.push("Outflow".to_string());
+                    ret.push("Inflow".to_string());
+                }
+                _ => ret.push(c.display_name().to_string()),
+            }
+        }
+        return ret;
+    }
+}
+
+#[test]
+fn export_header() {
+    let mut options = CsvExportOptions::new_default();
+    assert_eq!(
+        options.header(),
+        vec!["Account", "Date", "Payee", "Outflow", "Inflow", "Category", "Notes"]
+    );
+
+    options.amount_format = AmountFormat::Signed;
+    options.columns = vec![ExportColumn::Amount, ExportColumn::Payee];
+    assert_eq!(options.header(), vec!["Amount", "Payee"]);
+}
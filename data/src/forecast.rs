@@ -0,0 +1,197 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::scheduled_transaction::UpcomingTransaction;
+use crate::transaction::Transaction;
+
+const DAY: i64 = 24 * 60 * 60;
+
+// How far ahead the account boxes look
+pub const DEFAULT_FORECAST_DAYS: i64 = 30;
+
+// Days of past spending averaged when a forecast includes it
+pub const AVERAGE_SPENDING_DAYS: i64 = 90;
+
+// Payee of the daily average spending entries
+pub const AVERAGE_SPENDING_PAYEE: &str = "Average Spending";
+
+// One change to the projected balance
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ForecastEntry {
+    // unix timestamp
+    pub date: i64,
+
+    pub payee: String,
+    pub amount: i64,
+
+    // After this entry
+    pub balance: i64,
+}
+
+// An account's balance projected forward from today
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct AccountForecast {
+    pub account_id: i64,
+
+    // unix timestamps the forecast covers
+    pub start_date: i64,
+    pub end_date: i64,
+
+    // The balance at start_date, without the transactions dated after it
+    pub starting_balance: i64,
+
+    // Transactions already entered for later dates, scheduled occurrences, and a day's average
+    // spending for each day when asked for
+    pub entries: Vec<ForecastEntry>,
+
+    // Lowest the balance gets and the first date it gets there, the start when it never drops
+    pub low_balance: i64,
+    pub low_date: i64,
+
+    pub ending_balance: i64,
+}
+
+impl AccountForecast {
+    // Projects starting_balance forward days days from start_date. future are the account's
+    // transactions dated after start_date, upcoming its scheduled occurrences, ones already
+    // overdue count from the start. daily_spending is taken off at the end of each day, 0
+    // leaves it out.
+    pub fn new(
+        account_id: i64,
+        starting_balance: i64,
+        start_date: i64,
+        days: i64,
+        future: &[Transaction],
+        upcoming: &[UpcomingTransaction],
+        daily_spending: i64,
+    ) -> AccountForecast {
+        let end_date = start_date + days * DAY;
+
+        let mut entries: Vec<ForecastEntry> = future
+            .iter()
+            .filter(|t| t.date > start_date && t.date <= end_date)
+            .map(|t| ForecastEntry {
+                date: t.date,
+                payee: t.payee.clone(),
+                amount: t.amount,
+                balance: 0,
+            })
+            .collect();
+
+        for u in upcoming.iter().filter(|u| u.date <= end_date) {
+            entries.push(ForecastEntry {
+                date: u.date.max(start_date),
+                payee: u.payee.clone(),
+                amount: u.amount,
+                balance: 0,
+            });
+        }
+
+        if daily_spending > 0 {
+            for day in 1..=days {
+                entries.push(ForecastEntry {
+                    date: start_date + day * DAY,
+                    payee: AVERAGE_SPENDING_PAYEE.to_string(),
+                    amount: -daily_spending,
+                    balance: 0,
+                });
+            }
+        }
+
+        // Stable, so entered and scheduled ones come before the spending of the same moment
+        entries.sort_by_key(|e| e.date);
+
+        let mut balance = starting_balance;
+        let mut low_balance = starting_balance;
+        let mut low_date = start_date;
+        for entry in entries.iter_mut() {
+            balance += entry.amount;
+            entry.balance = balance;
+
+            if balance < low_balance {
+                low_balance = balance;
+                low_date = entry.date;
+            }
+        }
+
+        AccountForecast {
+            account_id,
+            start_date,
+            end_date,
+            starting_balance,
+            entries,
+            low_balance,
+            low_date,
+            ending_balance: balance,
+        }
+    }
+
+    pub fn goes_negative(&self) -> bool {
+        return self.low_balance < 0;
+    }
+}
+
+#[cfg(test)]
+fn test_upcoming(payee: &str, amount: i64, date: i64) -> UpcomingTransaction {
+    return UpcomingTransaction {
+        scheduled_id: 1,
+        payee: payee.to_string(),
+        account_id: 1,
+        amount,
+        date,
+    };
+}
+
+#[test]
+fn forecast_low_point() {
+    // 2024-03-01
+    let start = 1709251200;
+
+    let upcoming = vec![
+        test_upcoming("Payday", 200_000, start + 14 * DAY),
+        test_upcoming("Rent", -150_000, start + 5 * DAY),
+        test_upcoming("Overdue", -10_000, start - 2 * DAY),
+        test_upcoming("Too Late", -1_000_000, start + 31 * DAY),
+    ];
+
+    let forecast = AccountForecast::new(1, 100_000, start, 30, &[], &upcoming, 0);
+    let payees: Vec<&str> = forecast.entries.iter().map(|e| e.payee.as_str()).collect();
+    assert_eq!(payees, vec!["Overdue", "Rent", "Payday"]);
+    assert_eq!(forecast.entries[0].date, start);
+    assert_eq!(
+        (forecast.low_balance, forecast.low_date),
+        (-60_000, start + 5 * DAY)
+    );
+    assert_eq!(forecast.ending_balance, 140_000);
+    assert!(forecast.goes_negative());
+
+    // A day's spending at the end of every day
+    let forecast = AccountForecast::new(1, 100_000, start, 30, &[], &upcoming, 1_000);
+    assert_eq!(forecast.entries.len(), 33);
+    assert_eq!(
+        (forecast.low_balance, forecast.low_date),
+        (-73_000, start + 13 * DAY)
+    );
+    assert_eq!(forecast.ending_balance, 110_000);
+
+    // Nothing coming up, the low point is today
+    let forecast = AccountForecast::new(1, 5_000, start, 30, &[], &[], 0);
+    assert!(forecast.entries.is_empty());
+    assert_eq!((forecast.low_balance, forecast.low_date), (5_000, start));
+    assert!(!forecast.goes_negative());
+
+    // Transactions already entered for later dates fall in between the scheduled ones
+    let future = vec![
+        Transaction::new_raw("Car Payment".to_string(), -50_000, start + 3 * DAY, 1),
+        Transaction::new_raw("Next Year".to_string(), -50_000, start + 365 * DAY, 1),
+    ];
+    let forecast = AccountForecast::new(1, 100_000, start, 30, &future, &upcoming, 0);
+    let payees: Vec<&str> = forecast.entries.iter().map(|e| e.payee.as_str()).collect();
+    assert_eq!(payees, vec!["Overdue", "Car Payment", "Rent", "Payday"]);
+    assert_eq!(
+        (forecast.low_balance, forecast.low_date),
+        (-110_000, start + 5 * DAY)
+    );
+    assert_eq!(forecast.ending_balance, 90_000);
+}
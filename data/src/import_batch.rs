@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+// One committed import. Every transaction it created records the batch id, so the whole import
+// can be undone.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ImportBatch {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub account_id: i64,
+
+    // unix timestamp of when the import was committed
+    pub date: i64,
+
+    pub file_name: String,
+    pub transaction_count: i64,
+}
+
+impl ImportBatch {
+    pub fn new(
+        account_id: i64,
+        date: i64,
+        file_name: String,
+        transaction_count: i64,
+    ) -> ImportBatch {
+        ImportBatch {
+            id: 0,
+            account_id,
+            date,
+            file_name,
+            transaction_count,
+        }
+    }
+}
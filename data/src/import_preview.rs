@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::transaction::Transaction;
+use crate::RytError;
+
+// A transaction read from an import file, not yet in the database
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ImportCandidate {
+    pub trans: Transaction,
+
+    // Category name as written in the file. Created on commit if it doesn't exist.
+    pub category: String,
+
+    // 1 based line in the file
+    pub line: u64,
+
+    // Existing transaction this looks like a copy of, 0 when there isn't one
+    pub duplicate_of: i64,
+
+    // Left out of the commit. Duplicates start out skipped.
+    pub skip: bool,
+}
+
+// A line which couldn't be read as a transaction
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ImportLineError {
+    // 1 based line in the file
+    pub line: u64,
+    pub error: RytError,
+
+    // The fields of the line, for showing the user what went wrong
+    pub text: String,
+}
+
+// Balance the bank reported at the end of a statement
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct LedgerBalance {
+    pub amount: i64,
+
+    // unix timestamp the balance was taken
+    pub date: i64,
+}
+
+// Everything read from a file. Nothing is written until the candidates are committed.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ImportPreview {
+    pub account_id: i64,
+
+    // Name of the file read, without the folder
+    pub file_name: String,
+
+    pub candidates: Vec<ImportCandidate>,
+    pub errors: Vec<ImportLineError>,
+
+    // Only statement formats such as OFX report a balance
+    pub ledger_balance: Option<LedgerBalance>,
+
+    // Balance of the account before the import
+    pub account_balance: i64,
+}
+
+impl ImportPreview {
+    pub fn new(account_id: i64, file_name: String) -> ImportPreview {
+        ImportPreview {
+            account_id,
+            file_name,
+            candidates: vec![],
+            errors: vec![],
+            ledger_balance: None,
+            account_balance: 0,
+        }
+    }
+
+    // Account balance if the candidates which aren't skipped are committed
+    pub fn balance_after_import(&self) -> i64 {
+        let imported: i64 = self
+            .candidates
+            .iter()
+            .filter(|c| !c.skip)
+            .map(|c| c.trans.amount)
+            .sum();
+        return self.account_balance + imported;
+    }
+
+    // How far the account would be from the bank's balance after the import. 0 means they agree.
+    pub fn ledger_difference(&self) -> Option<i64> {
+        return self
+            .ledger_balance
+            .as_ref()
+            .map(|v| v.amount - self.balance_after_import());
+    }
+}
+
+#[test]
+fn preview_ledger_difference() {
+    let candidate = |amount: i64, skip: bool| ImportCandidate {
+        trans: Transaction::new_raw("payee".to_string(), amount, 0, 1),
+        category: String::new(),
+        line: 1,
+        duplicate_of: 0,
+        skip,
+    };
+
+    let mut preview = ImportPreview::new(1, "statement.ofx".to_string());
+    preview.account_balance = 1_000;
+    preview.candidates = vec![
+        candidate(-300, false),
+        candidate(500, false),
+        candidate(-50, true),
+    ];
+
+    assert_eq!(preview.balance_after_import(), 1_200);
+    assert_eq!(preview.ledger_difference(), None);
+
+    preview.ledger_balance = Some(LedgerBalance {
+        amount: 1_150,
+        date: 0,
+    });
+    assert_eq!(preview.ledger_difference(), Some(-50));
+}
@@ -0,0 +1,123 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::RytError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum AmountFormat {
+    // One column, negative is outflow
+    Signed,
+
+    // Separate outflow and inflow columns, both positive
+    OutflowInflow,
+}
+
+impl AmountFormat {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            AmountFormat::Signed => 0,
+            AmountFormat::OutflowInflow => 1,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<AmountFormat> {
+        match value {
+            0 => Some(AmountFormat::Signed),
+            1 => Some(AmountFormat::OutflowInflow),
+            _ => None,
+        }
+    }
+}
+
+// Describes how one bank lays out its CSV exports.
+// Column numbers start at 0.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ImportProfile {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub display_name: String,
+
+    // Single character between fields
+    pub delimiter: String,
+
+    // Header and preamble rows before the first transaction
+    pub skip_rows: i64,
+
+    pub date_column: i64,
+
+    // chrono strftime format, for example %Y-%m-%d or %m/%d/%Y
+    pub date_format: String,
+
+    pub payee_column: i64,
+
+    pub amount_format: AmountFormat,
+
+    // Used by AmountFormat::Signed
+    pub amount_column: i64,
+
+    // Flip the sign of AmountFormat::Signed amounts, for banks which report spending as positive
+    pub negate_amounts: bool,
+
+    // Used by AmountFormat::OutflowInflow
+    pub outflow_column: i64,
+    pub inflow_column: i64,
+
+    pub category_column: Option<i64>,
+    pub notes_column: Option<i64>,
+}
+
+impl ImportProfile {
+    // The layout LastFinance has always read.
+    // account, date, payee, outflow, inflow, category
+    pub fn new_default() -> ImportProfile {
+        ImportProfile {
+            id: 0,
+            display_name: "LastFinance CSV".to_string(),
+            delimiter: ",".to_string(),
+            skip_rows: 1,
+            date_column: 1,
+            date_format: "%Y-%m-%d".to_string(),
+            payee_column: 2,
+            amount_format: AmountFormat::OutflowInflow,
+            amount_column: 0,
+            negate_amounts: false,
+            outflow_column: 3,
+            inflow_column: 4,
+            category_column: Some(5),
+            notes_column: None,
+        }
+    }
+
+    // Row counts and column numbers are used as indexes, so none can be negative
+    pub fn validate(&self) -> Result<(), RytError> {
+        let numbers = [
+            Some(self.skip_rows),
+            Some(self.date_column),
+            Some(self.payee_column),
+            Some(self.amount_column),
+            Some(self.outflow_column),
+            Some(self.inflow_column),
+            self.category_column,
+            self.notes_column,
+        ];
+        if numbers.iter().flatten().any(|v| *v < 0) {
+            return Err(RytError::ImportProfileNegative);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn import_profile_validate() {
+    let mut profile = ImportProfile::new_default();
+    assert_eq!(profile.validate(), Ok(()));
+
+    profile.skip_rows = -1;
+    assert_eq!(profile.validate(), Err(RytError::ImportProfileNegative));
+
+    profile.skip_rows = 0;
+    profile.notes_column = Some(-2);
+    assert_eq!(profile.validate(), Err(RytError::ImportProfileNegative));
+}
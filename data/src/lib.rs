@@ -9,8 +9,24 @@ use regex::Regex;
 
 pub mod account;
 pub mod category;
+pub mod category_assignment;
+pub mod category_group;
+pub mod category_target;
 pub mod category_transfer;
+pub mod csv_export;
+pub mod forecast;
+pub mod import_batch;
+pub mod import_preview;
+pub mod import_profile;
+pub mod payee_rule;
+pub mod report;
+pub mod scheduled_transaction;
 pub mod transaction;
+pub mod transaction_query;
+pub mod transaction_split;
+
+#[cfg(feature = "rusqlite")]
+mod sql;
 
 pub fn dollars_to_cents(dollars: f64) -> i64 {
     return (dollars * 100.0).round() as i64;
@@ -20,25 +36,85 @@ pub fn cents_to_dollars(cents: i64) -> f64 {
     return (cents as f64) / 100.0;
 }
 
+// Budget months are stored as YYYYMM, so 2024-03 is 202403
+pub fn month_key(year: i32, month: u32) -> i64 {
+    return (year as i64) * 100 + (month as i64);
+}
+
 // TODO handle cents here? num_format doesn't seem to handle reals.
 pub fn amount_to_display(cents: i64) -> String {
     let dollars = cents_to_dollars(cents) as i64;
     return format!("${}", dollars.to_formatted_string(&Locale::en)).to_string();
 }
 
+// For files other programs read, -4210 is -42.10
+pub fn amount_to_plain(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    return format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100);
+}
+
 // NOTE the handle_invoke in the frontent can't handle params in the enum,
 // so don't add any.
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub enum RytError {
     LockingDB,
     Rusqlite,
 
+    OpenDatabase,
+    Migration,
+    DatabaseNewerThanApp,
+
     CreateTransactionInflowAndOutlow,
     CreateTransactionNoInflowOrOutflow,
 
+    TransferSameCategory,
+    TransferAmountNotPositive,
+    TransferSameAccount,
+    AccountMissing,
+    AccountClosed,
+    AccountHasTransactions,
+    AccountHasSchedules,
+    AccountBalanceNotZero,
+    TransactionMissing,
+    TransactionReconciled,
+    CategoryMissing,
+    CategoryGroupMissing,
+    TargetAmountNotPositive,
+    TargetMissingMonth,
+
     PickFileNone,
     PathBufToStringFail,
 
+    ImportReadFile,
+    ImportProfileDelimiter,
+    ImportProfileNegative,
+    ImportMissingColumn,
+    ImportParseDate,
+    ImportParseAmount,
+    ImportInvalidLine,
+    ImportNoAmount,
+    ImportInflowAndOutflow,
+    ImportBatchMissing,
+    ImportOfxInvalid,
+    ImportOfxMissingField,
+    ImportQifInvalid,
+    ImportQifMissingField,
+
+    ExportWriteFile,
+
+    SplitTooFewLines,
+    SplitAmountMismatch,
+
+    ScheduleNoAmount,
+    ScheduleEndBeforeStart,
+
+    RuleNoAction,
+    RuleInvalidRegex,
+
+    RestoreReadFile,
+    RestoreInvalidSnapshot,
+    RestoreOverOpenDatabase,
+
     // handle_invoke erorrs
     FromBindingRegexError,
     BindingDeserializationError,
@@ -79,3 +155,16 @@ pub struct DatabaseInfo {
     pub file_name: String,
     pub file_path: String,
 }
+
+#[test]
+fn month_key_ordering() {
+    assert_eq!(month_key(2024, 3), 202403);
+    assert!(month_key(2023, 12) < month_key(2024, 1));
+}
+
+#[test]
+fn plain_amounts() {
+    assert_eq!(amount_to_plain(-4210), "-42.10");
+    assert_eq!(amount_to_plain(5), "0.05");
+    assert_eq!(amount_to_plain(123456), "1234.56");
+}
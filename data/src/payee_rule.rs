@@ -0,0 +1,202 @@
+#![allow(dead_code)]
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::transaction::Transaction;
+
+// How a rule's pattern is compared with the payee. All ignore case.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum PayeeMatch {
+    Contains,
+    Equals,
+    Regex,
+}
+
+impl PayeeMatch {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            PayeeMatch::Contains => 0,
+            PayeeMatch::Equals => 1,
+            PayeeMatch::Regex => 2,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<PayeeMatch> {
+        match value {
+            0 => Some(PayeeMatch::Contains),
+            1 => Some(PayeeMatch::Equals),
+            2 => Some(PayeeMatch::Regex),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PayeeMatch::Contains => "Contains",
+            PayeeMatch::Equals => "Equals",
+            PayeeMatch::Regex => "Matches Regex",
+        }
+    }
+}
+
+// Fills in transactions which match it. Rules are tried in the order they were made and the
+// first match wins.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct PayeeRule {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub match_kind: PayeeMatch,
+
+    // Empty matches every payee
+    pub pattern: String,
+
+    // 0 matches every account
+    pub account_id: i64,
+
+    // Signed like Transaction::amount, both inclusive
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+
+    // What to set. 0 or empty leaves that part alone.
+    pub category_id: i64,
+    pub rename_payee: String,
+    pub notes: String,
+}
+
+impl PayeeRule {
+    pub fn new(match_kind: PayeeMatch, pattern: String) -> PayeeRule {
+        PayeeRule {
+            id: 0,
+            match_kind,
+            pattern,
+            account_id: 0,
+            min_amount: None,
+            max_amount: None,
+            category_id: 0,
+            rename_payee: String::new(),
+            notes: String::new(),
+        }
+    }
+
+    // Checks the rule can be used, before it is saved
+    pub fn validate(&self) -> Result<(), crate::RytError> {
+        if self.category_id == 0 && self.rename_payee.is_empty() && self.notes.is_empty() {
+            return Err(crate::RytError::RuleNoAction);
+        }
+
+        if self.match_kind == PayeeMatch::Regex
+            && RegexBuilder::new(&self.pattern)
+                .case_insensitive(true)
+                .build()
+                .is_err()
+        {
+            return Err(crate::RytError::RuleInvalidRegex);
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, trans: &Transaction) -> bool {
+        if self.account_id != 0 && self.account_id != trans.account_id {
+            return false;
+        }
+        if self.min_amount.is_some_and(|min| trans.amount < min) {
+            return false;
+        }
+        if self.max_amount.is_some_and(|max| trans.amount > max) {
+            return false;
+        }
+
+        let payee = trans.payee.to_lowercase();
+        let pattern = self.pattern.to_lowercase();
+        return match self.match_kind {
+            PayeeMatch::Contains => payee.contains(&pattern),
+            PayeeMatch::Equals => payee.trim() == pattern.trim(),
+            PayeeMatch::Regex => match RegexBuilder::new(&self.pattern)
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(re) => re.is_match(&trans.payee),
+                Err(_) => false,
+            },
+        };
+    }
+
+    // Category and notes are only filled in when the transaction has none
+    pub fn apply(&self, trans: &mut Transaction) {
+        if self.category_id != 0 && trans.category_id == 0 {
+            trans.category_id = self.category_id;
+        }
+        if !self.rename_payee.is_empty() {
+            trans.payee = self.rename_payee.clone();
+        }
+        if !self.notes.is_empty() && trans.notes.is_empty() {
+            trans.notes = self.notes.clone();
+        }
+    }
+}
+
+// Apply the first rule which matches. Returns whether any did.
+pub fn apply_rules(rules: &[PayeeRule], trans: &mut Transaction) -> bool {
+    return match rules.iter().find(|r| r.matches(trans)) {
+        Some(rule) => {
+            rule.apply(trans);
+            true
+        }
+        None => false,
+    };
+}
+
+#[test]
+fn payee_rule_matching() {
+    let trans = Transaction::new_raw("AMAZON MKTPLACE #123".to_string(), -2_500, 0, 1);
+
+    assert!(PayeeRule::new(PayeeMatch::Contains, "amazon".to_string()).matches(&trans));
+    assert!(!PayeeRule::new(PayeeMatch::Equals, "amazon".to_string()).matches(&trans));
+    assert!(
+        PayeeRule::new(PayeeMatch::Equals, "Amazon Mktplace #123 ".to_string()).matches(&trans)
+    );
+    assert!(PayeeRule::new(PayeeMatch::Regex, r"^amazon.*#\d+$".to_string()).matches(&trans));
+    assert!(!PayeeRule::new(PayeeMatch::Regex, "(".to_string()).matches(&trans));
+
+    let mut rule = PayeeRule::new(PayeeMatch::Contains, String::new());
+    rule.min_amount = Some(-3_000);
+    rule.max_amount = Some(-2_500);
+    assert!(rule.matches(&trans));
+    rule.max_amount = Some(-2_501);
+    assert!(!rule.matches(&trans));
+
+    let mut rule = PayeeRule::new(PayeeMatch::Contains, "amazon".to_string());
+    rule.account_id = 2;
+    assert!(!rule.matches(&trans));
+}
+
+#[test]
+fn payee_rule_apply() {
+    let mut shop = PayeeRule::new(PayeeMatch::Contains, "amazon".to_string());
+    assert_eq!(shop.validate(), Err(crate::RytError::RuleNoAction));
+    shop.category_id = 4;
+    shop.rename_payee = "Amazon".to_string();
+    shop.notes = "online".to_string();
+    assert_eq!(shop.validate(), Ok(()));
+
+    let mut other = PayeeRule::new(PayeeMatch::Regex, "[".to_string());
+    other.category_id = 5;
+    assert_eq!(other.validate(), Err(crate::RytError::RuleInvalidRegex));
+
+    let rules = vec![shop, PayeeRule::new(PayeeMatch::Contains, String::new())];
+
+    let mut trans = Transaction::new_raw("AMAZON MKTPLACE".to_string(), -2_500, 0, 1);
+    trans.notes = "gift".to_string();
+    assert!(apply_rules(&rules, &mut trans));
+    assert_eq!(trans.payee, "Amazon");
+    assert_eq!(trans.category_id, 4);
+    assert_eq!(trans.notes, "gift");
+
+    let mut trans = Transaction::new_raw("Grocer".to_string(), -2_500, 0, 1);
+    trans.category_id = 2;
+    assert!(!apply_rules(&rules[..1], &mut trans));
+    assert_eq!(trans.category_id, 2);
+}
@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+// Reports leave out transfers between accounts and everything in tracking accounts. Amounts are
+// positive cents. Income is inflow without a category, what the budget counts as ready to assign.
+// Spending is everything else, so refunds into a category reduce it.
+
+// Money in and out during one budget month
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct MonthlyIncomeExpense {
+    // See crate::month_key
+    pub month: i64,
+
+    pub income: i64,
+    pub expense: i64,
+}
+
+impl MonthlyIncomeExpense {
+    pub fn net(&self) -> i64 {
+        return self.income - self.expense;
+    }
+}
+
+// category_id UNCATEGORIZED_ID is uncategorized spending
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CategorySpending {
+    pub category_id: i64,
+    pub display_name: String,
+    pub amount: i64,
+}
+
+// Outflows only, refunds aren't taken off
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct PayeeSpending {
+    pub payee: String,
+    pub amount: i64,
+    pub transaction_count: i64,
+}
+
+// One category's spending in a month next to the month before
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct CategoryComparison {
+    pub category_id: i64,
+    pub display_name: String,
+    pub previous: i64,
+    pub current: i64,
+}
+
+impl CategoryComparison {
+    pub fn change(&self) -> i64 {
+        return self.current - self.previous;
+    }
+
+    // None when there was nothing to compare with
+    pub fn change_percent(&self) -> Option<i64> {
+        if self.previous == 0 {
+            return None;
+        }
+        return Some(self.change() * 100 / self.previous);
+    }
+}
+
+// Balances of every account at the end of a budget month, including tracking and closed ones
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct NetWorthSnapshot {
+    // See crate::month_key
+    pub month: i64,
+
+    // Balance of the accounts which aren't liabilities, see AccountType::is_liability
+    pub assets: i64,
+
+    // What the liability accounts owe, positive when they are below zero
+    pub liabilities: i64,
+}
+
+impl NetWorthSnapshot {
+    pub fn net_worth(&self) -> i64 {
+        return self.assets - self.liabilities;
+    }
+}
+
+// The budget month before a month key, 202401 comes after 202312
+pub fn previous_month(month: i64) -> i64 {
+    if month % 100 <= 1 {
+        return (month / 100 - 1) * 100 + 12;
+    }
+    return month - 1;
+}
+
+pub fn next_month(month: i64) -> i64 {
+    if month % 100 >= 12 {
+        return (month / 100 + 1) * 100 + 1;
+    }
+    return month + 1;
+}
+
+#[test]
+fn report_comparison() {
+    assert_eq!(previous_month(202401), 202312);
+    assert_eq!(previous_month(202411), 202410);
+    assert_eq!(next_month(202412), 202501);
+    assert_eq!(next_month(202402), 202403);
+
+    let mut comparison = CategoryComparison {
+        category_id: 1,
+        display_name: "groceries".to_string(),
+        previous: 40_000,
+        current: 50_000,
+    };
+    assert_eq!(comparison.change(), 10_000);
+    assert_eq!(comparison.change_percent(), Some(25));
+
+    comparison.previous = 0;
+    assert_eq!(comparison.change_percent(), None);
+}
@@ -0,0 +1,109 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Biweekly,
+
+    // Same day each month, or the last day for months too short for it
+    Monthly,
+
+    Yearly,
+}
+
+impl Frequency {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            Frequency::Daily => 0,
+            Frequency::Weekly => 1,
+            Frequency::Biweekly => 2,
+            Frequency::Monthly => 3,
+            Frequency::Yearly => 4,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<Frequency> {
+        match value {
+            0 => Some(Frequency::Daily),
+            1 => Some(Frequency::Weekly),
+            2 => Some(Frequency::Biweekly),
+            3 => Some(Frequency::Monthly),
+            4 => Some(Frequency::Yearly),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "Daily",
+            Frequency::Weekly => "Weekly",
+            Frequency::Biweekly => "Every Two Weeks",
+            Frequency::Monthly => "Monthly",
+            Frequency::Yearly => "Yearly",
+        }
+    }
+}
+
+// A transaction which repeats. Each occurrence becomes a real transaction once its date arrives.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ScheduledTransaction {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub payee: String,
+    pub notes: String,
+    pub account_id: i64,
+    pub category_id: i64,
+
+    // negative is outflow, positive is inflow
+    pub amount: i64,
+
+    pub frequency: Frequency,
+
+    // unix timestamp of the first occurrence, later ones are counted from it
+    pub start_date: i64,
+
+    // unix timestamp, no occurrences after it. None repeats forever.
+    pub end_date: Option<i64>,
+
+    // Occurrences already made into transactions
+    pub occurrence_count: i64,
+}
+
+impl ScheduledTransaction {
+    pub fn new(
+        payee: String,
+        amount: i64,
+        account_id: i64,
+        frequency: Frequency,
+        start_date: i64,
+    ) -> ScheduledTransaction {
+        ScheduledTransaction {
+            id: 0,
+            payee,
+            notes: String::new(),
+            account_id,
+            category_id: 0,
+            amount,
+            frequency,
+            start_date,
+            end_date: None,
+            occurrence_count: 0,
+        }
+    }
+}
+
+// An occurrence which hasn't happened yet
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct UpcomingTransaction {
+    pub scheduled_id: i64,
+    pub payee: String,
+    pub account_id: i64,
+    pub amount: i64,
+
+    // unix timestamp
+    pub date: i64,
+}
@@ -0,0 +1,27 @@
+// Conversions for enums stored as INTEGER columns. Each enum provides to_i64 and from_i64.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+macro_rules! integer_enum {
+    ($t:ty) => {
+        impl ToSql for $t {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(ToSqlOutput::from(self.to_i64()))
+            }
+        }
+
+        impl FromSql for $t {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                let v = i64::column_result(value)?;
+                <$t>::from_i64(v).ok_or(FromSqlError::OutOfRange(v))
+            }
+        }
+    };
+}
+
+integer_enum!(crate::account::AccountType);
+integer_enum!(crate::category_target::TargetKind);
+integer_enum!(crate::import_profile::AmountFormat);
+integer_enum!(crate::payee_rule::PayeeMatch);
+integer_enum!(crate::scheduled_transaction::Frequency);
+integer_enum!(crate::transaction::ClearedStatus);
@@ -2,9 +2,51 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::transaction_split::TransactionSplitDisplay;
+
+// Whether the bank has the transaction yet
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum ClearedStatus {
+    Uncleared,
+    Cleared,
+
+    // Matched against a statement, locked from edits
+    Reconciled,
+}
+
+impl ClearedStatus {
+    pub fn to_i64(&self) -> i64 {
+        match self {
+            ClearedStatus::Uncleared => 0,
+            ClearedStatus::Cleared => 1,
+            ClearedStatus::Reconciled => 2,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Option<ClearedStatus> {
+        match value {
+            0 => Some(ClearedStatus::Uncleared),
+            1 => Some(ClearedStatus::Cleared),
+            2 => Some(ClearedStatus::Reconciled),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ClearedStatus::Uncleared => "Uncleared",
+            ClearedStatus::Cleared => "Cleared",
+            ClearedStatus::Reconciled => "Reconciled",
+        }
+    }
+}
+
 // a real life bank transaction
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct Transaction {
+    // rowid, 0 until inserted
+    pub id: i64,
+
     pub payee: String,
     pub notes: String,
     pub account_id: i64,
@@ -15,6 +57,17 @@ pub struct Transaction {
 
     // negative is outflow, positive is inflow
     pub amount: i64,
+
+    // Import which created this transaction, 0 when entered by hand
+    pub import_batch_id: i64,
+
+    // The bank's id for the transaction from an OFX statement, empty otherwise
+    pub fitid: String,
+
+    // rowid of the other side of a transfer between accounts, 0 when not a transfer
+    pub transfer_id: i64,
+
+    pub cleared: ClearedStatus,
 }
 
 impl Transaction {
@@ -40,27 +93,45 @@ impl Transaction {
         }
 
         Ok(Transaction {
+            id: 0,
             payee: payee,
             amount: mount,
             date,
             account_id,
             category_id: 0,
             notes: "".to_string(),
+            import_batch_id: 0,
+            fitid: String::new(),
+            transfer_id: 0,
+            cleared: ClearedStatus::Uncleared,
         })
     }
 
     // no validation on input
     pub fn new_raw(payee: String, amount: i64, date: i64, account_id: i64) -> Transaction {
         Transaction {
+            id: 0,
             payee: payee,
             amount,
             date,
             account_id,
             category_id: 0,
             notes: "".to_string(),
+            import_batch_id: 0,
+            fitid: String::new(),
+            transfer_id: 0,
+            cleared: ClearedStatus::Uncleared,
         }
     }
 
+    pub fn is_transfer(&self) -> bool {
+        return self.transfer_id != 0;
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        return self.cleared == ClearedStatus::Reconciled;
+    }
+
     pub fn to_json_schema(&self) -> String {
         return serde_json::to_string(self).unwrap();
     }
@@ -90,6 +161,9 @@ pub struct TransactionDisplay {
     pub trans_raw: Transaction,
     pub category_display: String,
     pub account_display: String,
+
+    // Empty unless the transaction is split
+    pub splits: Vec<TransactionSplitDisplay>,
 }
 
 impl TransactionDisplay {
@@ -102,6 +176,7 @@ impl TransactionDisplay {
             trans_raw,
             category_display,
             account_display,
+            splits: vec![],
         }
     }
 }
@@ -0,0 +1,159 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::transaction::{ClearedStatus, TransactionDisplay};
+
+// Rows on one page of the transactions page
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+// Largest page a query returns, whatever it asks for
+pub const MAX_PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum TransactionSort {
+    DateNewest,
+    DateOldest,
+    AmountLargest,
+    AmountSmallest,
+    Payee,
+}
+
+impl TransactionSort {
+    pub fn all() -> Vec<TransactionSort> {
+        return vec![
+            TransactionSort::DateNewest,
+            TransactionSort::DateOldest,
+            TransactionSort::AmountLargest,
+            TransactionSort::AmountSmallest,
+            TransactionSort::Payee,
+        ];
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TransactionSort::DateNewest => "Newest First",
+            TransactionSort::DateOldest => "Oldest First",
+            TransactionSort::AmountLargest => "Largest Inflow First",
+            TransactionSort::AmountSmallest => "Largest Outflow First",
+            TransactionSort::Payee => "Payee",
+        }
+    }
+}
+
+// Which transactions a query returns. Empty or None fields match everything.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Default)]
+pub struct TransactionFilter {
+    // unix timestamps, both inclusive
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+
+    pub account_id: Option<i64>,
+
+    // Split transactions match when any of their lines is in the category
+    pub category_id: Option<i64>,
+
+    // Part of the payee, ignoring case
+    pub payee: String,
+
+    // Signed like Transaction::amount, both inclusive
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+
+    // Only transactions without a category, leaving out transfers and split transactions
+    pub uncategorized_only: bool,
+
+    pub cleared: Option<ClearedStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct TransactionQuery {
+    pub filter: TransactionFilter,
+    pub sort: TransactionSort,
+
+    // Rows skipped and rows returned, see MAX_PAGE_SIZE
+    pub offset: i64,
+    pub limit: i64,
+}
+
+// The first page of everything, newest first
+impl Default for TransactionQuery {
+    fn default() -> TransactionQuery {
+        TransactionQuery {
+            filter: TransactionFilter::default(),
+            sort: TransactionSort::DateNewest,
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl TransactionQuery {
+    pub fn new() -> TransactionQuery {
+        return TransactionQuery::default();
+    }
+}
+
+// One page of the transactions a query matched
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionDisplay>,
+
+    // Every match, not only the ones on this page
+    pub total_count: i64,
+
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl Default for TransactionPage {
+    fn default() -> TransactionPage {
+        TransactionPage {
+            transactions: vec![],
+            total_count: 0,
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl TransactionPage {
+    pub fn new() -> TransactionPage {
+        return TransactionPage::default();
+    }
+
+    pub fn has_previous(&self) -> bool {
+        return self.offset > 0;
+    }
+
+    pub fn has_next(&self) -> bool {
+        return self.offset + self.limit < self.total_count;
+    }
+
+    // 1 based, for showing "Page 2 of 5"
+    pub fn page_number(&self) -> i64 {
+        return self.offset / self.limit.max(1) + 1;
+    }
+
+    pub fn page_count(&self) -> i64 {
+        let limit = self.limit.max(1);
+        return ((self.total_count + limit - 1) / limit).max(1);
+    }
+}
+
+#[test]
+fn transaction_page_numbers() {
+    let mut page = TransactionPage::new();
+    assert_eq!((page.page_number(), page.page_count()), (1, 1));
+    assert!(!page.has_previous());
+    assert!(!page.has_next());
+
+    page.total_count = 250;
+    assert_eq!(page.page_count(), 3);
+    assert!(page.has_next());
+
+    page.offset = 200;
+    assert_eq!(page.page_number(), 3);
+    assert!(page.has_previous());
+    assert!(!page.has_next());
+}
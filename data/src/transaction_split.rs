@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+// One category line of a split transaction. The lines of a transaction sum to its amount, and
+// count toward their categories in place of the transaction's own category.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct TransactionSplit {
+    // rowid, 0 until inserted
+    pub id: i64,
+
+    pub transaction_id: i64,
+    pub category_id: i64,
+
+    // negative is outflow, positive is inflow, same as the transaction
+    pub amount: i64,
+
+    pub notes: String,
+}
+
+impl TransactionSplit {
+    pub fn new(category_id: i64, amount: i64) -> TransactionSplit {
+        TransactionSplit {
+            id: 0,
+            transaction_id: 0,
+            category_id,
+            amount,
+            notes: String::new(),
+        }
+    }
+}
+
+// Split line for displaying to user
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct TransactionSplitDisplay {
+    pub split_raw: TransactionSplit,
+    pub category_display: String,
+}
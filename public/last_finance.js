@@ -32,3 +32,12 @@ export function build_graph(element_id, dat) {
 
 		myChart.setOption(option);
 }
+
+// option is an echarts option as JSON, replacing whatever the element showed before
+export function build_chart(element_id, option) {
+
+		var element = document.getElementById(element_id);
+		var myChart = echarts.getInstanceByDom(element) || echarts.init(element);
+
+		myChart.setOption(JSON.parse(option), true);
+}
@@ -1,21 +1,40 @@
 #![allow(unused_macros)]
 
+mod backup;
+mod csv_export;
+mod csv_import;
+mod duplicates;
+mod migrations;
+mod ofx_import;
+mod qif;
+mod schedule;
 mod table_actions;
 use table_actions::TableActions;
 
-use rusqlite::{Connection, Result};
+use rusqlite::{named_params, params, Connection, OptionalExtension, Result};
 
-use time::format_description::well_known::Iso8601;
-use time::PrimitiveDateTime;
+use chrono::{Datelike, TimeZone};
 
-use std::fs::{File, OpenOptions};
-use std::io::{prelude::*, BufReader};
+use std::collections::HashMap;
+use std::fs::File;
 use std::path::{Path, PathBuf};
 
 use data::account::*;
 use data::category::*;
+use data::category_group::*;
+use data::category_target::*;
 use data::category_transfer::CategoryTransfer;
+use data::csv_export::CsvExportOptions;
+use data::forecast::*;
+use data::import_batch::ImportBatch;
+use data::import_preview::*;
+use data::import_profile::ImportProfile;
+use data::payee_rule::*;
+use data::report::*;
+use data::scheduled_transaction::*;
 use data::transaction::*;
+use data::transaction_query::*;
+use data::transaction_split::*;
 use data::RytError;
 
 use super::persistent_data::PersistentData;
@@ -23,6 +42,183 @@ use super::persistent_data::PersistentData;
 #[cfg(test)]
 mod tests;
 
+// Name of the file without the folder
+fn file_name(file_path: &str) -> String {
+    return Path::new(file_path)
+        .file_name()
+        .map_or(String::new(), |v| v.to_string_lossy().to_string());
+}
+
+// Swap id with its neighbour in the list. Nothing moves past either end. False if id isn't there.
+fn move_id(ids: &mut [i64], id: i64, up: bool) -> bool {
+    let pos = match ids.iter().position(|v| *v == id) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    if up && pos > 0 {
+        ids.swap(pos, pos - 1);
+    } else if !up && pos + 1 < ids.len() {
+        ids.swap(pos, pos + 1);
+    }
+    return true;
+}
+
+// Payee of one side of a transfer, named after the other side's account
+fn transfer_payee(amount: i64, other_account_name: &str) -> String {
+    return match amount < 0 {
+        true => format!("Transfer to {}", other_account_name),
+        false => format!("Transfer from {}", other_account_name),
+    };
+}
+
+// Budget month key of a unix timestamp
+fn month_key_of(unix_date: i64) -> Option<i64> {
+    let date = chrono::DateTime::from_timestamp(unix_date, 0)?;
+    return Some(data::month_key(date.year(), date.month()));
+}
+
+// Unix first and last second of a budget month key
+fn month_bounds(month: i64) -> Option<(i64, i64)> {
+    let start = chrono::Utc
+        .with_ymd_and_hms((month / 100) as i32, (month % 100) as u32, 1, 0, 0, 0)
+        .single()?;
+    let end = start.checked_add_months(chrono::Months::new(1))?;
+    return Some((start.timestamp(), end.timestamp() - 1));
+}
+
+// Where clause and named params of a transaction filter. Only the filters which are set are
+// written, as plain comparisons SQLite can use the date, account and category indexes for.
+fn transaction_filter_sql(
+    filter: &TransactionFilter,
+) -> (String, Vec<(&str, &dyn rusqlite::ToSql)>) {
+    let mut conditions: Vec<&str> = vec![];
+    let mut filter_params: Vec<(&str, &dyn rusqlite::ToSql)> = vec![];
+
+    if let Some(v) = &filter.start_date {
+        conditions.push("transactions.date >= :start_date");
+        filter_params.push((":start_date", v));
+    }
+    if let Some(v) = &filter.end_date {
+        conditions.push("transactions.date <= :end_date");
+        filter_params.push((":end_date", v));
+    }
+    if let Some(v) = &filter.account_id {
+        conditions.push("transactions.account_id = :account_id");
+        filter_params.push((":account_id", v));
+    }
+    if let Some(v) = &filter.category_id {
+        // Split transactions match on their lines
+        conditions.push(
+            "((transactions.category_id = :category_id
+                and transactions.rowid not in (select transaction_id from transaction_splits))
+            or transactions.rowid in (select transaction_id from transaction_splits where category_id = :category_id))",
+        );
+        filter_params.push((":category_id", v));
+    }
+    if !filter.payee.is_empty() {
+        conditions.push("instr(lower(transactions.payee), lower(:payee)) > 0");
+        filter_params.push((":payee", &filter.payee));
+    }
+    if let Some(v) = &filter.min_amount {
+        conditions.push("transactions.amount >= :min_amount");
+        filter_params.push((":min_amount", v));
+    }
+    if let Some(v) = &filter.max_amount {
+        conditions.push("transactions.amount <= :max_amount");
+        filter_params.push((":max_amount", v));
+    }
+    if filter.uncategorized_only {
+        conditions.push(
+            "transactions.category_id = 0 and transactions.transfer_id = 0
+            and transactions.rowid not in (select transaction_id from transaction_splits)",
+        );
+    }
+    if let Some(v) = &filter.cleared {
+        conditions.push("transactions.cleared = :cleared");
+        filter_params.push((":cleared", v));
+    }
+
+    if conditions.is_empty() {
+        return ("1".to_string(), filter_params);
+    }
+    return (conditions.join(" and "), filter_params);
+}
+
+// Every transaction as the lines that count toward categories. A split transaction is its split
+// lines, any other transaction is one line. Used as `with category_lines as ( ... )`.
+// Lines in tracking accounts, and transfers between two budget accounts, have budgeted = 0 and
+// BUDGET_LINES leaves them out. A transfer out to a tracking account leaves the budget.
+const CATEGORY_LINES: &str = "
+    select
+        transactions.rowid as transaction_id,
+        0 as split_id,
+        transactions.category_id,
+        transactions.amount,
+        transactions.date,
+        transactions.notes,
+        transactions.transfer_id,
+        case
+            when transactions.account_id in (select rowid from accounts where on_budget = 0) then 0
+            when transactions.transfer_id != 0 and (select account_id from transactions as other where other.rowid = transactions.transfer_id)
+                not in (select rowid from accounts where on_budget = 0) then 0
+            else 1
+        end as budgeted
+    from transactions
+    where transactions.rowid not in (select transaction_id from transaction_splits)
+    union all
+    select
+        transaction_splits.transaction_id,
+        transaction_splits.rowid,
+        transaction_splits.category_id,
+        transaction_splits.amount,
+        transactions.date,
+        case when transaction_splits.notes = '' then transactions.notes else transaction_splits.notes end,
+        transactions.transfer_id,
+        case
+            when transactions.account_id in (select rowid from accounts where on_budget = 0) then 0
+            else 1
+        end
+    from transaction_splits
+        join transactions on transactions.rowid = transaction_splits.transaction_id
+    ";
+
+// The category lines reports count, with :unix_start and :unix_end bound. See data::report.
+const REPORT_LINES: &str = "
+    budgeted = 1 and transfer_id = 0 and date between :unix_start and :unix_end
+    ";
+
+// Report lines that are income rather than spending
+const REPORT_INCOME: &str = "
+    amount > 0 and category_id not in (select id from categories)
+    ";
+
+// What each category's balance is made of: the budgeted category lines, plus the credit card
+// payment lines. Spending on a card moves the money from its category to the card's payment
+// category, paying the card from a budget account spends from the payment category.
+// Used as `with category_lines as ( ... ), budget_lines as ( ... )`.
+const BUDGET_LINES: &str = "
+    select category_id, amount, date
+    from category_lines
+    where budgeted = 1
+    union all
+    select payment.id, -category_lines.amount, category_lines.date
+    from category_lines
+        join transactions on transactions.rowid = category_lines.transaction_id
+        join categories as payment on payment.payment_account_id = transactions.account_id
+            and payment.payment_account_id != 0
+    where category_lines.budgeted = 1
+        and category_lines.category_id in (select id from categories where payment_account_id = 0)
+    union all
+    select payment.id, transactions.amount, transactions.date
+    from transactions
+        join transactions as card_side on card_side.rowid = transactions.transfer_id
+        join categories as payment on payment.payment_account_id = card_side.account_id
+            and payment.payment_account_id != 0
+    where transactions.account_id in (select rowid from accounts where on_budget = 1)
+        and transactions.account_id not in (select payment_account_id from categories)
+    ";
+
 pub enum OrderBy {
     None,
     Date,
@@ -35,16 +231,14 @@ pub struct Database {
 }
 
 impl Database {
-    pub fn new(file_path: PathBuf, persist_data: &mut PersistentData, do_persist: bool) -> Database {
-        let path_str: &str = file_path.to_str().unwrap();
+    pub fn new(file_path: PathBuf, persist_data: &mut PersistentData, do_persist: bool) -> Result<Database, RytError> {
+        let path_str: &str = file_path.to_str().ok_or(RytError::PathBufToStringFail)?;
         println!("Opening DB {0}", path_str);
 
-        if do_persist {
-            persist_data.set_last_db(path_str);
-        }
+        let mut connection = Connection::open(path_str).map_err(|_| RytError::OpenDatabase)?;
 
-        // TODO handle error
-        let connection = Connection::open(path_str).unwrap();
+        // Create or upgrade the schema
+        migrations::run(&mut connection)?;
 
         let db = Database {
             connection: connection,
@@ -52,26 +246,64 @@ impl Database {
             folder_dir: file_path.parent().unwrap().to_str().unwrap().to_string(),
         };
 
-        // Create schema if needed
-        {
-            fn setup_table<T: TableActions>(db: &Database) {
-                let table_name = &T::get_table_name();
-
-                if !db.table_exists(table_name) {
-                    let query =
-                        format!("CREATE TABLE {} ( {} )", table_name, T::get_table_schema());
-                    db.connection.execute(&query, ()).unwrap();
-                    println!("Created table {}", table_name);
-                }
-            }
+        // Catch up on recurring transactions which came due while the file was closed
+        db.create_due_scheduled(chrono::Utc::now().timestamp())?;
+
+        // Only remember files which actually opened
+        if do_persist {
+            persist_data.set_last_db(path_str);
+        }
+
+        Ok(db)
+    }
+
+    // An empty database which is never saved, for when the last file can't be opened
+    pub fn new_in_memory() -> Result<Database, RytError> {
+        return Database::new(
+            PathBuf::from(":memory:"),
+            &mut PersistentData::new_empty(),
+            false,
+        );
+    }
 
-            setup_table::<Category>(&db);
-            setup_table::<Account>(&db);
-            setup_table::<Transaction>(&db);
-            setup_table::<CategoryTransfer>(&db);
+    // Build a new file at db_path from a backup made by Database::backup, then open it.
+    // An existing file at db_path is replaced, but only once the new one is complete.
+    pub fn restore(
+        snapshot_path: PathBuf,
+        db_path: PathBuf,
+        persist_data: &mut PersistentData,
+        do_persist: bool,
+    ) -> Result<Database, RytError> {
+        let text =
+            std::fs::read_to_string(&snapshot_path).map_err(|_| RytError::RestoreReadFile)?;
+        let snapshot: backup::Snapshot =
+            serde_json::from_str(&text).map_err(|_| RytError::RestoreInvalidSnapshot)?;
+
+        let mut building_path = db_path.clone();
+        building_path.set_extension("restoring");
+        let _ = std::fs::remove_file(&building_path);
+
+        let mut connection =
+            Connection::open(&building_path).map_err(|_| RytError::OpenDatabase)?;
+        let result = backup::load(&mut connection, &snapshot);
+        let _ = connection.close();
+
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&building_path);
+            return Err(e);
         }
 
-        return db;
+        std::fs::rename(&building_path, &db_path).map_err(|_| RytError::OpenDatabase)?;
+        return Database::new(db_path, persist_data, do_persist);
+    }
+
+    // Every table as versioned JSON, see backup.rs
+    pub fn backup(&self, path: PathBuf) -> Result<(), RytError> {
+        let snapshot = backup::dump(&self.connection)?;
+        let json =
+            serde_json::to_string_pretty(&snapshot).map_err(|_| RytError::ExportWriteFile)?;
+        std::fs::write(&path, json).map_err(|_| RytError::ExportWriteFile)?;
+        Ok(())
     }
 
     // Returns the row id
@@ -84,31 +316,54 @@ impl Database {
         query.push_str(&T::get_insert_schema());
         query.push_str(" ) ");
 
+        // One numbered placeholder per bound value
+        let params = data.to_insert_params();
+        let placeholders: Vec<String> = (1..=params.len()).map(|i| format!("?{i}")).collect();
+
         query.push_str(" VALUES (");
-        query.push_str(&data.to_insert_data());
+        query.push_str(&placeholders.join(", "));
         query.push_str(")");
 
-        self.connection.execute(&query, ())?;
+        self.connection.execute(&query, params.as_slice())?;
         Ok(self.connection.last_insert_rowid())
     }
 
+    // Overwrite every insert column of an existing row. Returns the number of rows changed.
+    pub fn update<T: TableActions>(&self, id: i64, data: T) -> Result<usize, rusqlite::Error> {
+        let mut params = data.to_insert_params();
+
+        let columns: Vec<String> = T::get_insert_schema()
+            .split(',')
+            .enumerate()
+            .map(|(i, column)| format!("{} = ?{}", column.trim(), i + 1))
+            .collect();
+
+        let query = format!(
+            "UPDATE {} SET {} WHERE ROWID = ?{}",
+            T::get_table_name(),
+            columns.join(", "),
+            params.len() + 1
+        );
+
+        params.push(&id);
+        return self.connection.execute(&query, params.as_slice());
+    }
+
     pub fn delete<T: TableActions>(&self, id: i64) -> Result<usize, rusqlite::Error> {
-        let query = format!("DELETE FROM {} WHERE ROWID = {}", T::get_table_name(), id);
-        return self.connection.execute(&query, ());
+        let query = format!("DELETE FROM {} WHERE ROWID = ?1", T::get_table_name());
+        return self.connection.execute(&query, params![id]);
     }
 
-    pub fn get<T: TableActions>(&self, id: i64) -> T {
+    pub fn get<T: TableActions>(&self, id: i64) -> Result<T, rusqlite::Error> {
         let query = format!(
-            "SELECT {} FROM {} WHERE ROWID={}",
+            "SELECT {} FROM {} WHERE ROWID = ?1",
             T::get_fetch_schema(),
             T::get_table_name(),
-            id
         );
 
-        // TODO handle error. Don't unwrap. send error up.
-        self.connection
-            .query_row(&query, [], |row| Ok(T::row_to_data(row)))
-            .unwrap()
+        return self
+            .connection
+            .query_row(&query, params![id], |row| Ok(T::row_to_data(row)));
     }
 
     pub fn get_all<T: TableActions>(&self, order_by: OrderBy) -> Result<Vec<T>, rusqlite::Error> {
@@ -135,134 +390,557 @@ impl Database {
         return Ok(ret);
     }
 
-    // TODO handle error this whole method here
-    pub fn export_csv(&self, mut path: PathBuf) -> Result<(), String> {
-        println!("Exporting to {} ", path.to_str().unwrap());
+    // Transactions matching the filters in options, oldest first
+    pub fn export_csv(&self, path: PathBuf, options: &CsvExportOptions) -> Result<(), RytError> {
+        // Split transactions are written as one line per split
+        let query = format!(
+            "
+            with category_lines as ( {} )
+            select
+                category_lines.date,
+                transactions.payee,
+                ifnull(accounts.display_name, ''),
+                ifnull(categories.display_name, ''),
+                category_lines.notes,
+                category_lines.amount
+            from category_lines
+                join transactions on transactions.rowid = category_lines.transaction_id
+                left join categories on category_lines.category_id = categories.rowid
+                left join accounts on transactions.account_id = accounts.rowid
+            where (:account_id is null or transactions.account_id = :account_id)
+                and (:category_id is null or category_lines.category_id = :category_id)
+                and (:start_date is null or category_lines.date >= :start_date)
+                and (:end_date is null or category_lines.date <= :end_date)
+            order by category_lines.date, category_lines.transaction_id, category_lines.split_id
+            ",
+            CATEGORY_LINES
+        );
+
+        let mut stmt = self
+            .connection
+            .prepare(&query)
+            .map_err(|_| RytError::Rusqlite)?;
+        let iter = stmt
+            .query_map(
+                named_params! {
+                    ":account_id": options.account_id,
+                    ":category_id": options.category_id,
+                    ":start_date": options.start_date,
+                    ":end_date": options.end_date,
+                },
+                |row| {
+                    Ok(csv_export::CsvExportRow {
+                        date: row.get(0)?,
+                        payee: row.get(1)?,
+                        account: row.get(2)?,
+                        category: row.get(3)?,
+                        notes: row.get(4)?,
+                        amount: row.get(5)?,
+                    })
+                },
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut rows: Vec<csv_export::CsvExportRow> = vec![];
+        for r in iter {
+            rows.push(r.map_err(|_| RytError::Rusqlite)?);
+        }
+
+        let file = File::create(&path).map_err(|_| RytError::ExportWriteFile)?;
+        return csv_export::write(file, &rows, options);
+    }
+
+    // Every account and its transactions as QIF, for moving to other finance software
+    pub fn export_qif(&self, path: PathBuf) -> Result<(), RytError> {
+        let accounts: Vec<Account> = self
+            .get_all(OrderBy::None)
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut transactions = self
+            .get_transaction_list_display()
+            .map_err(|_| RytError::Rusqlite)?
+            .transactions;
+        transactions.sort_by_key(|t| (t.trans_raw.date, t.trans_raw.id));
+
+        let categories = self
+            .get_qif_category_names()
+            .map_err(|_| RytError::Rusqlite)?;
+        let category_name = |category_id: i64| -> String {
+            return categories.get(&category_id).cloned().unwrap_or_default();
+        };
+
+        // Transfers are written as the other side's account, [Savings]
+        let account_names: HashMap<i64, &str> = accounts
+            .iter()
+            .map(|a| (a.id, a.display_name.as_str()))
+            .collect();
+        let transaction_accounts: HashMap<i64, i64> = transactions
+            .iter()
+            .map(|t| (t.trans_raw.id, t.trans_raw.account_id))
+            .collect();
+        let transfer_name = |transfer_id: i64| -> String {
+            let name = transaction_accounts
+                .get(&transfer_id)
+                .and_then(|account_id| account_names.get(account_id))
+                .copied()
+                .unwrap_or_default();
+            return format!("[{}]", name);
+        };
+
+        let mut out = String::new();
+        for account in &accounts {
+            let rows: Vec<qif::QifExportRow> = transactions
+                .iter()
+                .filter(|t| t.trans_raw.account_id == account.id)
+                .map(|t| qif::QifExportRow {
+                    date: t.trans_raw.date,
+                    amount: t.trans_raw.amount,
+                    payee: t.trans_raw.payee.clone(),
+                    notes: t.trans_raw.notes.clone(),
+                    category: match t.trans_raw.is_transfer() {
+                        true => transfer_name(t.trans_raw.transfer_id),
+                        false => category_name(t.trans_raw.category_id),
+                    },
+                    splits: t
+                        .splits
+                        .iter()
+                        .map(|s| qif::QifSplit {
+                            category: category_name(s.split_raw.category_id),
+                            memo: s.split_raw.notes.clone(),
+                            amount: s.split_raw.amount,
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            qif::write_account(&mut out, &account.display_name, account.account_type, &rows);
+        }
+
+        std::fs::write(&path, out).map_err(|_| RytError::ExportWriteFile)?;
+        return Ok(());
+    }
+
+    // Category names as QIF writes them, Group:Category when the category is in a group.
+    // Uncategorized has no entry.
+    fn get_qif_category_names(&self) -> Result<HashMap<i64, String>, rusqlite::Error> {
+        let mut stmt = self.connection.prepare(
+            "select categories.rowid, categories.display_name, ifnull(category_groups.display_name, '')
+            from categories
+            left join category_groups on categories.group_id = category_groups.rowid",
+        )?;
+        let iter = stmt.query_map([], |row| {
+            let id: i64 = row.get(0)?;
+            let name: String = row.get(1)?;
+            let group: String = row.get(2)?;
+            return Ok((id, name, group));
+        })?;
+
+        let mut ret: HashMap<i64, String> = HashMap::new();
+        for c in iter {
+            let (id, name, group) = c?;
+            if id == UNCATEGORIZED_ID {
+                continue;
+            }
+            match group.is_empty() {
+                true => ret.insert(id, name),
+                false => ret.insert(id, format!("{}:{}", group, name)),
+            };
+        }
+
+        return Ok(ret);
+    }
+
+    // The transaction, checked that it exists and isn't reconciled. Changing one side of a
+    // transfer changes the other, so that side is checked too.
+    fn get_unlocked_transaction(&self, transaction_id: i64) -> Result<Transaction, RytError> {
+        let existing: Transaction = self
+            .get(transaction_id)
+            .map_err(|_| RytError::TransactionMissing)?;
+        if existing.is_reconciled() {
+            return Err(RytError::TransactionReconciled);
+        }
 
-        // Remove past file
-        let _ = std::fs::remove_file(&path);
+        if existing.is_transfer() {
+            let other_side: Transaction = self
+                .get(existing.transfer_id)
+                .map_err(|_| RytError::Rusqlite)?;
+            if other_side.is_reconciled() {
+                return Err(RytError::TransactionReconciled);
+            }
+        }
 
-        // Create append handle
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .unwrap();
+        return Ok(existing);
+    }
 
-        // Write column headers
-        file.write("payee, amount, date, account, category".as_bytes())
-            .unwrap();
-        file.write("\n".as_bytes()).unwrap();
+    // This will error if the transaction doesn't exist or is reconciled.
+    // Split lines no longer add up once the amount changes, so they are removed.
+    // The other side of a transfer follows the amount and date.
+    pub fn update_transaction(&self, trans: Transaction) -> Result<(), RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+        self.apply_transaction_update(trans)?;
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
 
-        struct Row {
-            payee: String,
-            amount: i64,
-            date: i64,
-            account: String,
-            category: String,
+    // update_transaction without its own sqlite transaction, for callers that already have one
+    fn apply_transaction_update(&self, mut trans: Transaction) -> Result<(), RytError> {
+        let existing = self.get_unlocked_transaction(trans.id)?;
+
+        // Where a transaction came from isn't editable, cleared status has its own setter
+        trans.import_batch_id = existing.import_batch_id;
+        trans.fitid = existing.fitid;
+        trans.transfer_id = existing.transfer_id;
+        trans.cleared = existing.cleared;
+
+        if trans.amount != existing.amount {
+            self.connection
+                .execute(
+                    "DELETE FROM transaction_splits WHERE transaction_id = ?1",
+                    params![trans.id],
+                )
+                .map_err(|_| RytError::Rusqlite)?;
         }
 
-        let query = "
-            select 
-                payee, 
-                amount, 
-                date, 
-                ifnull(accounts.display_name, '') as account_display_name,
-                ifnull(categories.display_name, '') as category_display_name
-            from transactions 
-                left join categories on transactions.category_id = categories.rowid
-                left join accounts on transactions.account_id = accounts.rowid
-            ORDER BY date
-            ";
+        if trans.is_transfer() {
+            trans.category_id = UNCATEGORIZED_ID;
+            self.connection
+                .execute(
+                    "UPDATE transactions SET amount = ?1, date = ?2 WHERE rowid = ?3",
+                    params![-trans.amount, trans.date, trans.transfer_id],
+                )
+                .map_err(|_| RytError::Rusqlite)?;
+
+            // Moving a side to another account renames both sides after their new accounts
+            if trans.account_id != existing.account_id {
+                let other_side: Transaction = self
+                    .get(trans.transfer_id)
+                    .map_err(|_| RytError::Rusqlite)?;
+                if trans.account_id == other_side.account_id {
+                    return Err(RytError::TransferSameAccount);
+                }
 
-        let mut stmt = self.connection.prepare(&query).unwrap();
-        let mut iter: Vec<_> = stmt
-            .query_map([], |row| {
-                let r = Row {
-                    payee: row.get(0).unwrap(),
-                    amount: row.get(1).unwrap(),
-                    date: row.get(2).unwrap(),
-                    account: row.get(3).unwrap(),
-                    category: row.get(4).unwrap(),
-                };
-
-                // payee
-                file.write(r.payee.as_bytes()).unwrap();
-                file.write(",".as_bytes()).unwrap();
-
-                // amount
-                file.write(format!("{}", data::cents_to_dollars(r.amount)).as_bytes())
-                    .unwrap();
-                file.write(",".as_bytes()).unwrap();
-
-                // date
-                let date_offset = time::OffsetDateTime::from_unix_timestamp(r.date).unwrap();
-                let format_desc = time::format_description::parse("[year]-[month]-[day]").unwrap();
-                let date_disp: String = date_offset.format(&format_desc).unwrap();
-                file.write(date_disp.as_bytes()).unwrap();
-                file.write(",".as_bytes()).unwrap();
-
-                // account
-                file.write(r.account.as_bytes()).unwrap();
-                file.write(",".as_bytes()).unwrap();
-
-                // category
-                file.write(r.category.as_bytes()).unwrap();
-
-                file.write("\n".as_bytes()).unwrap();
-                Ok(())
-            })
-            .unwrap()
-            .collect();
+                let account: Account = self
+                    .get(trans.account_id)
+                    .map_err(|_| RytError::Rusqlite)?;
+                let other_account: Account = self
+                    .get(other_side.account_id)
+                    .map_err(|_| RytError::Rusqlite)?;
+
+                trans.payee = transfer_payee(trans.amount, &other_account.display_name);
+                self.connection
+                    .execute(
+                        "UPDATE transactions SET payee = ?1 WHERE rowid = ?2",
+                        params![
+                            transfer_payee(-trans.amount, &account.display_name),
+                            trans.transfer_id
+                        ],
+                    )
+                    .map_err(|_| RytError::Rusqlite)?;
+            }
+        }
 
-        println!("Successfully exported");
+        self.update(trans.id, trans)
+            .map_err(|_| RytError::Rusqlite)?;
         Ok(())
     }
 
-    fn table_exists(&self, table: &str) -> bool {
-        #[derive(Debug)]
-        struct Entry {
-            name: String,
+    // Any status can be set, setting a reconciled transaction back to cleared unlocks it
+    pub fn set_transaction_cleared(
+        &self,
+        transaction_id: i64,
+        cleared: ClearedStatus,
+    ) -> Result<(), RytError> {
+        let count = self
+            .connection
+            .execute(
+                "UPDATE transactions SET cleared = ?1 WHERE rowid = ?2",
+                params![cleared, transaction_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        match count {
+            0 => Err(RytError::TransactionMissing),
+            _ => Ok(()),
+        }
+    }
+
+    // Compare the cleared transactions of an account up to a statement date with the statement's
+    // ending balance. When they agree the cleared transactions are locked as reconciled.
+    pub fn reconcile_account(
+        &self,
+        account_id: i64,
+        statement_date: i64,
+        statement_balance: i64,
+    ) -> Result<ReconcileSummary, RytError> {
+        let account: Account = self
+            .get(account_id)
+            .map_err(|_| RytError::AccountMissing)?;
+        if account.closed {
+            return Err(RytError::AccountClosed);
         }
 
-        let query: String =
-            format!("SELECT name FROM sqlite_master WHERE type='table' AND name='{table}';");
-        let mut statement = self.connection.prepare(&query).unwrap();
-        let rows_iter = statement
-            .query_map([], |row| {
-                Ok(Entry {
-                    name: row.get(0).unwrap(),
-                })
-            })
-            .unwrap();
+        // The whole statement day counts, whatever time the transactions were entered at
+        let day_end = statement_date + 24 * 60 * 60;
+
+        let cleared_balance: i64 = self
+            .connection
+            .query_row(
+                "SELECT ifnull(sum(amount), 0) FROM transactions WHERE account_id = ?1 AND cleared != 0 AND date < ?2",
+                params![account_id, day_end],
+                |row| row.get(0),
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut summary = ReconcileSummary {
+            account_id,
+            statement_date,
+            statement_balance,
+            cleared_balance,
+            difference: statement_balance - cleared_balance,
+            reconciled_count: 0,
+        };
+        if summary.difference != 0 {
+            return Ok(summary);
+        }
+
+        summary.reconciled_count = self
+            .connection
+            .execute(
+                "UPDATE transactions SET cleared = ?1 WHERE account_id = ?2 AND cleared = ?3 AND date < ?4",
+                params![
+                    ClearedStatus::Reconciled,
+                    account_id,
+                    ClearedStatus::Cleared,
+                    day_end
+                ],
+            )
+            .map_err(|_| RytError::Rusqlite)? as i64;
+
+        return Ok(summary);
+    }
+
+    // Checks the lines add up to amount, then replaces the lines of the transaction.
+    // Callers wrap this in a sqlite transaction.
+    fn replace_splits(
+        &self,
+        transaction_id: i64,
+        amount: i64,
+        splits: Vec<TransactionSplit>,
+    ) -> Result<(), RytError> {
+        if splits.len() == 1 {
+            return Err(RytError::SplitTooFewLines);
+        }
+        if !splits.is_empty() && splits.iter().map(|s| s.amount).sum::<i64>() != amount {
+            return Err(RytError::SplitAmountMismatch);
+        }
+
+        self.connection
+            .execute(
+                "DELETE FROM transaction_splits WHERE transaction_id = ?1",
+                params![transaction_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        if splits.is_empty() {
+            return Ok(());
+        }
+
+        for mut split in splits {
+            split.transaction_id = transaction_id;
+            self.insert(split).map_err(|_| RytError::Rusqlite)?;
+        }
+
+        // The lines are the categories now
+        self.connection
+            .execute(
+                "UPDATE transactions SET category_id = ?1 WHERE rowid = ?2",
+                params![UNCATEGORIZED_ID, transaction_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        Ok(())
+    }
+
+    // Insert a transaction along with its split lines. Returns the row id of the transaction.
+    pub fn insert_split_transaction(
+        &self,
+        trans: Transaction,
+        splits: Vec<TransactionSplit>,
+    ) -> Result<i64, RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let transaction_id = self.insert_with_splits(trans, splits)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(transaction_id);
+    }
+
+    // insert_split_transaction for callers already inside a transaction, like the imports
+    fn insert_with_splits(
+        &self,
+        trans: Transaction,
+        splits: Vec<TransactionSplit>,
+    ) -> Result<i64, RytError> {
+        let amount = trans.amount;
+        let transaction_id = self.insert(trans).map_err(|_| RytError::Rusqlite)?;
+        self.replace_splits(transaction_id, amount, splits)?;
+        return Ok(transaction_id);
+    }
+
+    // Edit a transaction and replace its split lines together, so a new amount and lines adding
+    // up to it are saved at once. No lines makes it an ordinary transaction again.
+    pub fn update_split_transaction(
+        &self,
+        trans: Transaction,
+        splits: Vec<TransactionSplit>,
+    ) -> Result<(), RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let transaction_id = trans.id;
+        let amount = trans.amount;
+        self.apply_transaction_update(trans)?;
+        self.replace_splits(transaction_id, amount, splits)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    pub fn get_transaction_splits(
+        &self,
+        transaction_id: i64,
+    ) -> Result<Vec<TransactionSplit>, rusqlite::Error> {
+        let query = format!(
+            "SELECT {} FROM {} WHERE transaction_id = ?1 ORDER BY rowid",
+            TransactionSplit::get_fetch_schema(),
+            TransactionSplit::get_table_name(),
+        );
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let iter = stmt.query_map(params![transaction_id], |row| {
+            Ok(TransactionSplit::row_to_data(row))
+        })?;
+
+        let mut ret: Vec<TransactionSplit> = vec![];
+        for c in iter {
+            ret.push(c?);
+        }
+
+        Ok(ret)
+    }
 
-        for p in rows_iter {
-            return true;
+    // This will error if the transaction doesn't exist or is reconciled.
+    // Deleting either side of a transfer deletes both.
+    pub fn delete_transaction(&self, transaction_id: i64) -> Result<(), RytError> {
+        let existing = self.get_unlocked_transaction(transaction_id)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+        if existing.is_transfer() {
+            self.delete::<Transaction>(existing.transfer_id)
+                .map_err(|_| RytError::Rusqlite)?;
+        }
+        self.delete::<Transaction>(transaction_id)
+            .map_err(|_| RytError::Rusqlite)?;
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+
+        Ok(())
+    }
+
+    // Move money between accounts as a pair of linked transactions, an outflow from one and an
+    // inflow to the other. Neither counts toward a category. Returns the row ids, from side first.
+    pub fn create_account_transfer(
+        &self,
+        from_account_id: i64,
+        to_account_id: i64,
+        amount: i64,
+        date: i64,
+        notes: String,
+    ) -> Result<(i64, i64), RytError> {
+        if from_account_id == to_account_id {
+            return Err(RytError::TransferSameAccount);
         }
-        return false;
+        if amount <= 0 {
+            return Err(RytError::TransferAmountNotPositive);
+        }
+
+        let from_account: Account = self.get(from_account_id).map_err(|_| RytError::Rusqlite)?;
+        let to_account: Account = self.get(to_account_id).map_err(|_| RytError::Rusqlite)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut from_side = Transaction::new_raw(
+            transfer_payee(-amount, &to_account.display_name),
+            -amount,
+            date,
+            from_account_id,
+        );
+        from_side.notes = notes.clone();
+        let from_id = self.insert(from_side).map_err(|_| RytError::Rusqlite)?;
+
+        let mut to_side = Transaction::new_raw(
+            transfer_payee(amount, &from_account.display_name),
+            amount,
+            date,
+            to_account_id,
+        );
+        to_side.notes = notes;
+        to_side.transfer_id = from_id;
+        let to_id = self.insert(to_side).map_err(|_| RytError::Rusqlite)?;
+
+        self.connection
+            .execute(
+                "UPDATE transactions SET transfer_id = ?1 WHERE rowid = ?2",
+                params![to_id, from_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok((from_id, to_id));
     }
 
     pub fn get_transaction_list_display(&self) -> Result<TransactionDisplayList, rusqlite::Error> {
         let query = "
             select 
-            payee, 
-        amount, 
-        date, 
-        account_id,
-        ifnull(categories.display_name, '') as category_display_name,
-        ifnull(accounts.display_name, '') as account_display_name
+            transactions.payee, 
+            transactions.amount, 
+            transactions.date, 
+            transactions.notes,
+            transactions.account_id,
+            transactions.category_id,
+            transactions.import_batch_id,
+            transactions.fitid,
+            transactions.transfer_id,
+            transactions.cleared,
+            transactions.rowid,
+            ifnull(categories.display_name, '') as category_display_name,
+            ifnull(accounts.display_name, '') as account_display_name
             from transactions 
             left join categories on transactions.category_id = categories.rowid
             left join accounts on transactions.account_id = accounts.rowid
             ";
 
+        // First columns match the Transaction fetch schema
         let mut stmt = self.connection.prepare(query)?;
         let mut iter = stmt.query_map([], |row| {
-            Ok(TransactionDisplay {
-                trans_raw: Transaction::new_raw(row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?),
-                category_display: row.get(4).unwrap(),
-                account_display: row.get(5).unwrap(),
-            })
+            Ok(TransactionDisplay::new(
+                Transaction::row_to_data(row),
+                row.get(11).unwrap(),
+                row.get(12).unwrap(),
+            ))
         })?;
         let mut ret = TransactionDisplayList {
             transactions: vec![],
@@ -271,48 +949,223 @@ impl Database {
             ret.transactions.push(c.unwrap());
         }
 
+        self.attach_splits(&mut ret.transactions, "1")?;
+        Ok(ret)
+    }
+
+    // Fill in the split lines of the transactions, from the split lines matching split_filter
+    fn attach_splits(
+        &self,
+        transactions: &mut [TransactionDisplay],
+        split_filter: &str,
+    ) -> Result<(), rusqlite::Error> {
+        // First columns match the TransactionSplit fetch schema
+        let split_query = format!(
+            "
+            select
+            transaction_splits.transaction_id,
+            transaction_splits.category_id,
+            transaction_splits.amount,
+            transaction_splits.notes,
+            transaction_splits.rowid,
+            ifnull(categories.display_name, '') as category_display_name
+            from transaction_splits
+            left join categories on transaction_splits.category_id = categories.rowid
+            where {}
+            order by transaction_splits.rowid
+            ",
+            split_filter
+        );
+
+        let mut stmt = self.connection.prepare(&split_query)?;
+        let iter = stmt.query_map([], |row| {
+            Ok(TransactionSplitDisplay {
+                split_raw: TransactionSplit::row_to_data(row),
+                category_display: row.get(5)?,
+            })
+        })?;
+
+        let mut splits: HashMap<i64, Vec<TransactionSplitDisplay>> = HashMap::new();
+        for c in iter {
+            let split = c?;
+            splits
+                .entry(split.split_raw.transaction_id)
+                .or_default()
+                .push(split);
+        }
+
+        for t in transactions.iter_mut() {
+            if let Some(v) = splits.remove(&t.trans_raw.id) {
+                t.splits = v;
+            }
+        }
+
+        Ok(())
+    }
+
+    // One page of the transactions matching the query's filter, in its sort order
+    pub fn query_transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> Result<TransactionPage, rusqlite::Error> {
+        let (where_clause, filter_params) = transaction_filter_sql(&query.filter);
+        let filter_params = filter_params.as_slice();
+
+        let total_count: i64 = self.connection.query_row(
+            &format!("select count(*) from transactions where {}", where_clause),
+            filter_params,
+            |row| row.get(0),
+        )?;
+
+        // rowid last so pages don't shift between equal rows
+        let order_by = match query.sort {
+            TransactionSort::DateNewest => "transactions.date desc, transactions.rowid desc",
+            TransactionSort::DateOldest => "transactions.date, transactions.rowid",
+            TransactionSort::AmountLargest => "transactions.amount desc, transactions.rowid",
+            TransactionSort::AmountSmallest => "transactions.amount, transactions.rowid",
+            TransactionSort::Payee => {
+                "lower(transactions.payee), transactions.date desc, transactions.rowid desc"
+            }
+        };
+        let offset = query.offset.max(0);
+        let limit = query.limit.clamp(1, MAX_PAGE_SIZE);
+
+        let page_query = format!(
+            "
+            select 
+            transactions.payee, 
+            transactions.amount, 
+            transactions.date, 
+            transactions.notes,
+            transactions.account_id,
+            transactions.category_id,
+            transactions.import_batch_id,
+            transactions.fitid,
+            transactions.transfer_id,
+            transactions.cleared,
+            transactions.rowid,
+            ifnull(categories.display_name, '') as category_display_name,
+            ifnull(accounts.display_name, '') as account_display_name
+            from transactions 
+            left join categories on transactions.category_id = categories.rowid
+            left join accounts on transactions.account_id = accounts.rowid
+            where {}
+            order by {}
+            limit {} offset {}
+            ",
+            where_clause, order_by, limit, offset
+        );
+
+        // First columns match the Transaction fetch schema
+        let mut stmt = self.connection.prepare(&page_query)?;
+        let iter = stmt.query_map(filter_params, |row| {
+            Ok(TransactionDisplay::new(
+                Transaction::row_to_data(row),
+                row.get(11)?,
+                row.get(12)?,
+            ))
+        })?;
+
+        let mut ret = TransactionPage {
+            transactions: vec![],
+            total_count,
+            offset,
+            limit,
+        };
+        for c in iter {
+            ret.transactions.push(c?);
+        }
+
+        // Only the lines of this page
+        let ids: Vec<String> = ret
+            .transactions
+            .iter()
+            .map(|t| t.trans_raw.id.to_string())
+            .collect();
+        if !ids.is_empty() {
+            self.attach_splits(
+                &mut ret.transactions,
+                &format!("transaction_splits.transaction_id in ({})", ids.join(", ")),
+            )?;
+        }
+
         Ok(ret)
     }
 
     pub fn category_exists(&self, name: &str) -> Result<bool, rusqlite::Error> {
-        let query = format!(
-            "select COUNT(*) as count
+        let query = "
+            select COUNT(*) as count
             from categories
-            where display_name='{}'",
-            name
-        );
+            where display_name = ?1";
         let count: i64 = self
             .connection
-            .query_row(&query, [], |row| Ok(row.get(0)?))?;
+            .query_row(query, params![name], |row| Ok(row.get(0)?))?;
         Ok(count > 0)
     }
 
     // This will error if the category doesn't exist
     pub fn get_category_id(&self, name: &str) -> Result<i64, rusqlite::Error> {
-        let query = format!("SELECT rowid FROM categories WHERE display_name='{name}'");
+        let query = "SELECT rowid FROM categories WHERE display_name = ?1";
         let id: i64 = self
             .connection
-            .query_row(&query, [], |row| Ok(row.get(0)?))?;
+            .query_row(query, params![name], |row| Ok(row.get(0)?))?;
         Ok(id)
     }
 
+    // Returns the row id. Credit cards on the budget get a category to save up their payments in.
+    pub fn create_account(&self, account: Account) -> Result<i64, RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let payment_name = format!("{} Payment", account.display_name);
+        let has_payment = account.on_budget && account.account_type == AccountType::CreditCard;
+        let account_id = self.insert(account).map_err(|_| RytError::Rusqlite)?;
+
+        if has_payment {
+            let mut payment = Category::new(&payment_name);
+            payment.payment_account_id = account_id;
+            self.insert(payment).map_err(|_| RytError::Rusqlite)?;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(account_id);
+    }
+
+    // Open accounts
     pub fn get_account_display_list(&self) -> Result<Vec<AccountDisplay>, rusqlite::Error> {
+        return self.account_display_list(false);
+    }
+
+    pub fn get_closed_account_display_list(&self) -> Result<Vec<AccountDisplay>, rusqlite::Error> {
+        return self.account_display_list(true);
+    }
+
+    fn account_display_list(&self, closed: bool) -> Result<Vec<AccountDisplay>, rusqlite::Error> {
         let query = "
             SELECT 
             accounts.rowid,
             accounts.display_name,
-            sum(amount) as balance
+            accounts.account_type,
+            accounts.on_budget,
+            ifnull(sum(amount), 0) as balance,
+            ifnull(sum(case when cleared != 0 then amount else 0 end), 0) as cleared_balance
                 from accounts
                 left join transactions on transactions.account_id = accounts.rowid           
-                group by account_id
+                where accounts.closed = ?1
+                group by accounts.rowid
                 ";
 
         let mut stmt = self.connection.prepare(query)?;
-        let mut iter = stmt.query_map([], |row| {
+        let mut iter = stmt.query_map(params![closed], |row| {
             Ok(AccountDisplay {
                 account_id: row.get(0)?,
                 display_name: row.get(1)?,
-                balance: row.get(2)?,
+                account_type: row.get(2)?,
+                on_budget: row.get(3)?,
+                balance: row.get(4)?,
+                cleared_balance: row.get(5)?,
             })
         })?;
 
@@ -324,27 +1177,306 @@ impl Database {
         Ok(ret)
     }
 
-    pub fn get_account_history(
-        &self,
-        account_id: i64,
-    ) -> Result<Vec<AccountHistoryEntry>, rusqlite::Error> {
-        let query = format!(
-            "
-            SELECT 
-            accounts.rowid,
-            accounts.display_name,
-            sum(transactions.amount) over (order by date asc) as running_total,
-            transactions.date
-            from accounts
-            left join transactions on transactions.account_id = accounts.rowid
-            where accounts.rowid = {}
-            order by date asc
+    pub fn rename_account(&self, account_id: i64, new_name: String) -> Result<(), RytError> {
+        let count = self
+            .connection
+            .execute(
+                "UPDATE accounts SET display_name = ?1 WHERE rowid = ?2",
+                params![new_name, account_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        match count {
+            0 => Err(RytError::AccountMissing),
+            _ => Ok(()),
+        }
+    }
+
+    // Transaction count, balance and recurring transaction count of an account
+    fn account_usage(&self, account_id: i64) -> Result<(i64, i64, i64), RytError> {
+        let account: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT rowid FROM accounts WHERE rowid = ?1",
+                params![account_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|_| RytError::Rusqlite)?;
+        if account.is_none() {
+            return Err(RytError::AccountMissing);
+        }
+
+        let (count, balance): (i64, i64) = self
+            .connection
+            .query_row(
+                "SELECT count(*), ifnull(sum(amount), 0) FROM transactions WHERE account_id = ?1",
+                params![account_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let schedules: i64 = self
+            .connection
+            .query_row(
+                "SELECT count(*) FROM scheduled_transactions WHERE account_id = ?1",
+                params![account_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        return Ok((count, balance, schedules));
+    }
+
+    // Hide an account which is done with. It has to be empty and have nothing scheduled, so a
+    // closed account never changes the budget.
+    pub fn close_account(&self, account_id: i64) -> Result<(), RytError> {
+        let (_, balance, schedules) = self.account_usage(account_id)?;
+        if balance != 0 {
+            return Err(RytError::AccountBalanceNotZero);
+        }
+        if schedules != 0 {
+            return Err(RytError::AccountHasSchedules);
+        }
+
+        self.connection
+            .execute(
+                "UPDATE accounts SET closed = 1 WHERE rowid = ?1",
+                params![account_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    pub fn reopen_account(&self, account_id: i64) -> Result<(), RytError> {
+        let count = self
+            .connection
+            .execute(
+                "UPDATE accounts SET closed = 0 WHERE rowid = ?1",
+                params![account_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        match count {
+            0 => Err(RytError::AccountMissing),
+            _ => Ok(()),
+        }
+    }
+
+    // Only accounts without any transactions can be deleted, others can be closed instead
+    pub fn delete_account(&self, account_id: i64) -> Result<(), RytError> {
+        let (count, _, schedules) = self.account_usage(account_id)?;
+        if count != 0 {
+            return Err(RytError::AccountHasTransactions);
+        }
+        if schedules != 0 {
+            return Err(RytError::AccountHasSchedules);
+        }
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        // Its payment category becomes an ordinary one, keeping whatever was assigned to it
+        self.connection
+            .execute(
+                "UPDATE categories SET payment_account_id = 0 WHERE payment_account_id = ?1",
+                params![account_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        self.delete::<Account>(account_id)
+            .map_err(|_| RytError::Rusqlite)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    // Income and spending of every month from unix_start to unix_end, months without any included
+    pub fn get_income_expense_by_month(
+        &self,
+        unix_start: i64,
+        unix_end: i64,
+    ) -> Result<Vec<MonthlyIncomeExpense>, rusqlite::Error> {
+        let query = format!(
+            "
+            with category_lines as ( {} )
+            select
+            cast(strftime('%Y%m', date, 'unixepoch') as integer) as month,
+            coalesce(sum(case when {} then amount end), 0) as income,
+            coalesce(-sum(case when not ({}) then amount end), 0) as expense
+            from category_lines
+            where {}
+            group by month
             ",
-            account_id
+            CATEGORY_LINES, REPORT_INCOME, REPORT_INCOME, REPORT_LINES
         );
 
         let mut stmt = self.connection.prepare(&query)?;
-        let mut iter = stmt.query_map([], |row| {
+        let iter = stmt.query_map(
+            named_params! {":unix_start": unix_start, ":unix_end": unix_end},
+            |row| {
+                Ok(MonthlyIncomeExpense {
+                    month: row.get(0)?,
+                    income: row.get(1)?,
+                    expense: row.get(2)?,
+                })
+            },
+        )?;
+
+        let mut found: HashMap<i64, MonthlyIncomeExpense> = HashMap::new();
+        for c in iter {
+            let month = c?;
+            found.insert(month.month, month);
+        }
+
+        let mut ret: Vec<MonthlyIncomeExpense> = vec![];
+        let (Some(mut month), Some(last)) = (month_key_of(unix_start), month_key_of(unix_end))
+        else {
+            return Ok(ret);
+        };
+        while month <= last {
+            ret.push(found.remove(&month).unwrap_or(MonthlyIncomeExpense {
+                month,
+                income: 0,
+                expense: 0,
+            }));
+            month = next_month(month);
+        }
+        Ok(ret)
+    }
+
+    // Categories with spending from unix_start to unix_end, biggest first
+    pub fn get_spending_by_category(
+        &self,
+        unix_start: i64,
+        unix_end: i64,
+    ) -> Result<Vec<CategorySpending>, rusqlite::Error> {
+        let query = format!(
+            "
+            with category_lines as ( {} )
+            select
+            ifnull(categories.id, 0) as report_category,
+            ifnull(categories.display_name, ''),
+            -sum(category_lines.amount) as spent
+            from category_lines
+                left join categories on categories.id = category_lines.category_id
+            where {} and not ({})
+            group by report_category
+            having spent > 0
+            order by spent desc, report_category
+            ",
+            CATEGORY_LINES, REPORT_LINES, REPORT_INCOME
+        );
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let iter = stmt.query_map(
+            named_params! {":unix_start": unix_start, ":unix_end": unix_end},
+            |row| {
+                Ok(CategorySpending {
+                    category_id: row.get(0)?,
+                    display_name: row.get(1)?,
+                    amount: row.get(2)?,
+                })
+            },
+        )?;
+
+        let mut ret: Vec<CategorySpending> = vec![];
+        for c in iter {
+            ret.push(c?);
+        }
+        Ok(ret)
+    }
+
+    // The payees with the most outflow from unix_start to unix_end, at most limit of them
+    pub fn get_top_payees(
+        &self,
+        unix_start: i64,
+        unix_end: i64,
+        limit: i64,
+    ) -> Result<Vec<PayeeSpending>, rusqlite::Error> {
+        let query = "
+            select payee, -sum(amount) as spent, count(*)
+            from transactions
+            where transfer_id = 0 and amount < 0
+                and account_id not in (select rowid from accounts where on_budget = 0)
+                and date between :unix_start and :unix_end
+            group by payee
+            order by spent desc, payee
+            limit :limit
+            ";
+
+        let mut stmt = self.connection.prepare(query)?;
+        let iter = stmt.query_map(
+            named_params! {":unix_start": unix_start, ":unix_end": unix_end, ":limit": limit},
+            |row| {
+                Ok(PayeeSpending {
+                    payee: row.get(0)?,
+                    amount: row.get(1)?,
+                    transaction_count: row.get(2)?,
+                })
+            },
+        )?;
+
+        let mut ret: Vec<PayeeSpending> = vec![];
+        for c in iter {
+            ret.push(c?);
+        }
+        Ok(ret)
+    }
+
+    // Spending by category in a budget month next to the month before, biggest first
+    pub fn get_category_comparison(
+        &self,
+        month: i64,
+    ) -> Result<Vec<CategoryComparison>, rusqlite::Error> {
+        let (start, end) = month_bounds(month).ok_or(rusqlite::Error::InvalidQuery)?;
+        let (previous_start, previous_end) =
+            month_bounds(previous_month(month)).ok_or(rusqlite::Error::InvalidQuery)?;
+
+        let mut ret: Vec<CategoryComparison> = self
+            .get_spending_by_category(start, end)?
+            .into_iter()
+            .map(|c| CategoryComparison {
+                category_id: c.category_id,
+                display_name: c.display_name,
+                previous: 0,
+                current: c.amount,
+            })
+            .collect();
+
+        for c in self.get_spending_by_category(previous_start, previous_end)? {
+            match ret.iter_mut().find(|v| v.category_id == c.category_id) {
+                Some(v) => v.previous = c.amount,
+                None => ret.push(CategoryComparison {
+                    category_id: c.category_id,
+                    display_name: c.display_name,
+                    previous: c.amount,
+                    current: 0,
+                }),
+            }
+        }
+        Ok(ret)
+    }
+
+    pub fn get_account_history(
+        &self,
+        account_id: i64,
+    ) -> Result<Vec<AccountHistoryEntry>, rusqlite::Error> {
+        let query = "
+            SELECT 
+            accounts.rowid,
+            accounts.display_name,
+            sum(transactions.amount) over (order by date asc) as running_total,
+            transactions.date
+            from accounts
+            left join transactions on transactions.account_id = accounts.rowid
+            where accounts.rowid = ?1
+            order by date asc
+            ";
+
+        let mut stmt = self.connection.prepare(query)?;
+        let mut iter = stmt.query_map(params![account_id], |row| {
             Ok(AccountHistoryEntry {
                 account_id: row.get(0)?,
                 display_name: row.get(1)?,
@@ -361,148 +1493,1254 @@ impl Database {
         Ok(ret)
     }
 
+    // Net worth at the end of every month from the first transaction to the month of unix_end.
+    // Like get_account_history, but every account's running balance is taken month by month.
+    pub fn get_net_worth_history(
+        &self,
+        unix_end: i64,
+    ) -> Result<Vec<NetWorthSnapshot>, rusqlite::Error> {
+        let query = "
+            with monthly as (
+                select
+                account_id,
+                cast(strftime('%Y%m', date, 'unixepoch') as integer) as month,
+                sum(amount) as change
+                from transactions
+                where date <= ?1
+                group by account_id, month
+            )
+            select
+            monthly.month,
+            monthly.account_id,
+            accounts.account_type,
+            sum(monthly.change) over (partition by monthly.account_id order by monthly.month) as running_balance
+            from monthly
+                join accounts on accounts.rowid = monthly.account_id
+            order by monthly.month
+            ";
+
+        let mut stmt = self.connection.prepare(query)?;
+        let iter = stmt.query_map(params![unix_end], |row| {
+            Ok((
+                row.get::<usize, i64>(0)?,
+                row.get::<usize, i64>(1)?,
+                row.get::<usize, AccountType>(2)?,
+                row.get::<usize, i64>(3)?,
+            ))
+        })?;
+
+        // Month, account, type and the balance at the end of that month
+        let mut balances: Vec<(i64, i64, AccountType, i64)> = vec![];
+        for c in iter {
+            balances.push(c?);
+        }
+
+        let mut ret: Vec<NetWorthSnapshot> = vec![];
+        let (Some(mut month), Some(last)) = (balances.first().map(|b| b.0), month_key_of(unix_end))
+        else {
+            return Ok(ret);
+        };
+
+        // Accounts without transactions in a month keep their last balance
+        let mut current: HashMap<i64, (AccountType, i64)> = HashMap::new();
+        let mut next = balances.into_iter().peekable();
+        while month <= last {
+            while let Some((_, account_id, account_type, balance)) = next.next_if(|b| b.0 <= month)
+            {
+                current.insert(account_id, (account_type, balance));
+            }
+
+            let mut snapshot = NetWorthSnapshot {
+                month,
+                assets: 0,
+                liabilities: 0,
+            };
+            for (account_type, balance) in current.values() {
+                if account_type.is_liability() {
+                    snapshot.liabilities -= balance;
+                } else {
+                    snapshot.assets += balance;
+                }
+            }
+            ret.push(snapshot);
+
+            month = next_month(month);
+        }
+
+        Ok(ret)
+    }
+
+    // month is the budget month key (see data::month_key) that unix_start and unix_end cover
     pub fn get_category_display_list(
         &self,
         unix_start: i64,
         unix_end: i64,
+        month: i64,
     ) -> Result<Vec<CategoryDisplay>, rusqlite::Error> {
+        // available rolls forward, everything assigned, transferred and spent up to the end of the month.
+        // Split transactions count toward the categories of their lines.
         let query = format!(
             "
+            with category_lines as ( {} ), budget_lines as ( {} )
             SELECT 
-            id, 
-            display_name,
-            coalesce(avg(amount), 0) as transactions_average,
-            coalesce(sum(amount), 0) as transactions_total
+            categories.id, 
+            categories.display_name,
+            coalesce((
+                select avg(amount) from budget_lines 
+                where category_id = categories.id and date between :unix_start and :unix_end
+            ), 0) as transactions_average,
+            coalesce((
+                select sum(amount) from budget_lines 
+                where category_id = categories.id and date between :unix_start and :unix_end
+            ), 0) as transactions_total,
+            coalesce((
+                select amount from category_assignments 
+                where category_id = categories.id and month = :month
+            ), 0) as assigned,
+            coalesce((
+                select sum(case when dest = categories.id then amount else -amount end) 
+                from category_transfer 
+                where (dest = categories.id or source = categories.id) and month = :month
+            ), 0) as transferred,
+            coalesce((
+                select sum(amount) from category_assignments 
+                where category_id = categories.id and month <= :month
+            ), 0) + coalesce((
+                select sum(case when dest = categories.id then amount else -amount end) 
+                from category_transfer 
+                where (dest = categories.id or source = categories.id) and month <= :month
+            ), 0) + coalesce((
+                select sum(amount) from budget_lines 
+                where category_id = categories.id and date <= :unix_end
+            ), 0) as available,
+            categories.payment_account_id,
+            categories.group_id,
+            categories.sort_order
             from categories 
-            left join transactions on transactions.category_id = categories.rowid
-            where coalesce(transactions.date, 0) between {unix_start} and {unix_end}
-            group by id
-            order by id
+                left join category_groups on category_groups.rowid = categories.group_id
+            order by category_groups.sort_order, category_groups.rowid, categories.sort_order, categories.id
+            ",
+            CATEGORY_LINES, BUDGET_LINES
+        );
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let mut iter = stmt.query_map(
+            named_params! {":unix_start": unix_start, ":unix_end": unix_end, ":month": month},
+            |row| {
+                Ok(CategoryDisplay {
+                    category_id: row.get(0)?,
+                    display_name: row.get(1)?,
+                    transaction_average: row.get(2)?,
+                    transaction_total: row.get(3)?,
+                    assigned: row.get(4)?,
+                    transferred: row.get(5)?,
+                    available: row.get(6)?,
+                    payment_account_id: row.get(7)?,
+                    group_id: row.get(8)?,
+                    sort_order: row.get(9)?,
+                    target: None,
+                    target_needed: 0,
+                })
+            },
+        )?;
+
+        let mut targets: HashMap<i64, CategoryTarget> = self
+            .get_category_targets()?
+            .into_iter()
+            .map(|t| (t.category_id, t))
+            .collect();
+
+        let mut ret: Vec<CategoryDisplay> = vec![];
+        for c in iter {
+            let mut category = c.unwrap();
+            if let Some(target) = targets.remove(&category.category_id) {
+                category.target_needed =
+                    target.needed(month, category.assigned, category.available);
+                category.target = Some(target);
+            }
+            ret.push(category);
+        }
+
+        Ok(ret)
+    }
+
+    pub fn get_category_targets(&self) -> Result<Vec<CategoryTarget>, rusqlite::Error> {
+        return self.get_all(OrderBy::None);
+    }
+
+    // Replaces the category's target if it has one
+    pub fn set_category_target(&self, target: CategoryTarget) -> Result<(), RytError> {
+        target.validate()?;
+        self.get::<Category>(target.category_id)
+            .map_err(|_| RytError::CategoryMissing)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        tx.execute(
+            "DELETE FROM category_targets WHERE category_id = ?1",
+            params![target.category_id],
+        )
+        .map_err(|_| RytError::Rusqlite)?;
+        self.insert(target).map_err(|_| RytError::Rusqlite)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    pub fn delete_category_target(&self, category_id: i64) -> Result<(), rusqlite::Error> {
+        let deleted = self.connection.execute(
+            "DELETE FROM category_targets WHERE category_id = ?1",
+            params![category_id],
+        )?;
+        match deleted {
+            0 => Err(rusqlite::Error::QueryReturnedNoRows),
+            _ => Ok(()),
+        }
+    }
+
+    // Assign every underfunded category what its target still needs this month, even past
+    // what is ready to assign. Returns the total added.
+    pub fn assign_to_targets(
+        &self,
+        unix_start: i64,
+        unix_end: i64,
+        month: i64,
+    ) -> Result<i64, RytError> {
+        let categories = self
+            .get_category_display_list(unix_start, unix_end, month)
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut total: i64 = 0;
+        for c in categories.iter().filter(|c| c.target_needed > 0) {
+            self.assign_category(c.category_id, month, c.assigned + c.target_needed)
+                .map_err(|_| RytError::Rusqlite)?;
+            total += c.target_needed;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(total);
+    }
+
+    // Sets the amount assigned to a category for a month, replacing any previous assignment
+    pub fn assign_category(
+        &self,
+        category_id: i64,
+        month: i64,
+        amount: i64,
+    ) -> Result<(), rusqlite::Error> {
+        let query = "
+            INSERT INTO category_assignments (category_id, month, amount) 
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(category_id, month) DO UPDATE SET amount = excluded.amount
+            ";
+
+        self.connection
+            .execute(query, params![category_id, month, amount])?;
+        Ok(())
+    }
+
+    // Income not yet given to a category, as of the end of the month.
+    // Transactions without a category, or whose category was deleted, count as income.
+    // Money moved between accounts isn't income.
+    // Transfers only change it when one side is a deleted category.
+    pub fn get_ready_to_assign(&self, unix_end: i64, month: i64) -> Result<i64, rusqlite::Error> {
+        let query = format!(
             "
+            with category_lines as ( {} ), budget_lines as ( {} )
+            SELECT
+            coalesce((
+                select sum(amount) from budget_lines 
+                where category_id not in (select id from categories) and date <= :unix_end
+            ), 0) - coalesce((
+                select sum(amount) from category_assignments 
+                where category_id in (select id from categories) and month <= :month
+            ), 0) - coalesce((
+                select sum(amount) from category_transfer 
+                where dest in (select id from categories) 
+                and source not in (select id from categories) 
+                and month <= :month
+            ), 0) + coalesce((
+                select sum(amount) from category_transfer 
+                where source in (select id from categories) 
+                and dest not in (select id from categories) 
+                and month <= :month
+            ), 0)
+            ",
+            CATEGORY_LINES, BUDGET_LINES
+        );
+
+        return self.connection.query_row(
+            &query,
+            named_params! {":unix_end": unix_end, ":month": month},
+            |row| row.get(0),
+        );
+    }
+
+    // Returns the row id of the transfer
+    pub fn transfer_between_categories(&self, transfer: CategoryTransfer) -> Result<i64, RytError> {
+        if transfer.source == transfer.dest {
+            return Err(RytError::TransferSameCategory);
+        }
+        if transfer.amount <= 0 {
+            return Err(RytError::TransferAmountNotPositive);
+        }
+
+        return self.insert(transfer).map_err(|_| RytError::Rusqlite);
+    }
+
+    // Transfers into or out of a category, newest first
+    pub fn get_category_transfers(
+        &self,
+        category_id: i64,
+    ) -> Result<Vec<CategoryTransfer>, rusqlite::Error> {
+        let query = format!(
+            "SELECT {} FROM {} WHERE source = ?1 OR dest = ?1 ORDER BY month DESC, rowid DESC",
+            CategoryTransfer::get_fetch_schema(),
+            CategoryTransfer::get_table_name(),
         );
 
         let mut stmt = self.connection.prepare(&query)?;
-        let mut iter = stmt.query_map([], |row| {
-            Ok(CategoryDisplay {
-                category_id: row.get(0)?,
-                display_name: row.get(1)?,
-                transaction_average: row.get(2)?,
-                transaction_total: row.get(3)?,
-            })
+        let iter = stmt.query_map(params![category_id], |row| {
+            Ok(CategoryTransfer::row_to_data(row))
         })?;
 
-        let mut ret: Vec<CategoryDisplay> = vec![];
+        let mut ret: Vec<CategoryTransfer> = vec![];
         for c in iter {
             ret.push(c.unwrap());
         }
 
-        // get list of all categories and add any missing to the list
-        let mut new_cats: Vec<CategoryDisplay> = vec![];
+        Ok(ret)
+    }
 
-        let all_cats: Vec<Category> = self.get_all(OrderBy::None).unwrap();
-        for c_all in &all_cats {
-            let mut found = false;
+    pub fn rename_category(
+        &self,
+        category_id: i64,
+        new_name: String,
+    ) -> Result<(), rusqlite::Error> {
+        let query = "
+                update categories
+                set display_name = ?1
+                where ROWID = ?2
+            ";
 
-            for c_ret in &ret {
-                if c_ret.category_id == c_all.id {
-                    found = true;
-                    break;
+        self.connection
+            .execute(query, params![new_name, category_id])?;
+        Ok(())
+    }
+
+    // Existing category with the name, otherwise a new one. "Parent:Child" names, as QIF files and
+    // other apps write them, are the category Child in the group Parent.
+    pub fn get_or_create_category(&self, name: &str) -> Result<i64, rusqlite::Error> {
+        if self.category_exists(name)? {
+            return self.get_category_id(name);
+        }
+
+        if let Some((group_name, child_name)) = name.split_once(':') {
+            let (group_name, child_name) = (group_name.trim(), child_name.trim());
+            if !group_name.is_empty() && !child_name.is_empty() {
+                let group_id = self.get_or_create_category_group(group_name)?;
+
+                let found: Option<i64> = self
+                    .connection
+                    .query_row(
+                        "select id from categories where group_id = ?1 and display_name = ?2",
+                        params![group_id, child_name],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if let Some(id) = found {
+                    return Ok(id);
                 }
+
+                let mut category = Category::new(child_name);
+                category.group_id = group_id;
+                category.sort_order = self.next_category_sort_order(group_id)?;
+                return self.insert(category);
             }
+        }
+
+        return self.insert(Category::new(name));
+    }
 
-            if !found {
-                new_cats.push(CategoryDisplay {
-                    category_id: c_all.id,
-                    display_name: c_all.display_name.clone(),
-                    transaction_average: 0.0,
-                    transaction_total: 0,
+    // Sorted the way they are listed
+    pub fn get_category_groups(&self) -> Result<Vec<CategoryGroup>, rusqlite::Error> {
+        let query = format!(
+            "SELECT {} FROM {} ORDER BY sort_order, rowid",
+            CategoryGroup::get_fetch_schema(),
+            CategoryGroup::get_table_name(),
+        );
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let iter = stmt.query_map([], |row| Ok(CategoryGroup::row_to_data(row)))?;
+
+        let mut ret: Vec<CategoryGroup> = vec![];
+        for c in iter {
+            ret.push(c?);
+        }
+        Ok(ret)
+    }
+
+    // The month's categories under their groups, with group subtotals
+    pub fn get_category_group_display_list(
+        &self,
+        unix_start: i64,
+        unix_end: i64,
+        month: i64,
+    ) -> Result<Vec<CategoryGroupDisplay>, rusqlite::Error> {
+        let categories = self.get_category_display_list(unix_start, unix_end, month)?;
+        return Ok(group_categories(self.get_category_groups()?, categories));
+    }
+
+    // Returns the row id. New groups go at the end of the list.
+    pub fn create_category_group(&self, name: &str) -> Result<i64, rusqlite::Error> {
+        let mut group = CategoryGroup::new(name);
+        group.sort_order = self.connection.query_row(
+            "select ifnull(max(sort_order) + 1, 0) from category_groups",
+            [],
+            |row| row.get(0),
+        )?;
+        return self.insert(group);
+    }
+
+    // Existing group with the name, otherwise a new one
+    pub fn get_or_create_category_group(&self, name: &str) -> Result<i64, rusqlite::Error> {
+        let found: Option<i64> = self
+            .connection
+            .query_row(
+                "select rowid from category_groups where display_name = ?1 order by sort_order, rowid",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match found {
+            Some(id) => Ok(id),
+            None => self.create_category_group(name),
+        }
+    }
+
+    pub fn rename_category_group(&self, group_id: i64, new_name: &str) -> Result<(), RytError> {
+        let changed = self
+            .connection
+            .execute(
+                "UPDATE category_groups SET display_name = ?1 WHERE rowid = ?2",
+                params![new_name, group_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        match changed {
+            0 => Err(RytError::CategoryGroupMissing),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn set_category_group_collapsed(
+        &self,
+        group_id: i64,
+        collapsed: bool,
+    ) -> Result<(), RytError> {
+        let changed = self
+            .connection
+            .execute(
+                "UPDATE category_groups SET collapsed = ?1 WHERE rowid = ?2",
+                params![collapsed, group_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        match changed {
+            0 => Err(RytError::CategoryGroupMissing),
+            _ => Ok(()),
+        }
+    }
+
+    // Its categories are kept, after the ungrouped ones
+    pub fn delete_category_group(&self, group_id: i64) -> Result<(), RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let first_sort_order = self
+            .next_category_sort_order(UNGROUPED_ID)
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let deleted = self
+            .delete::<CategoryGroup>(group_id)
+            .map_err(|_| RytError::Rusqlite)?;
+        if deleted == 0 {
+            return Err(RytError::CategoryGroupMissing);
+        }
+
+        tx.execute(
+            "UPDATE categories SET group_id = ?1, sort_order = sort_order + ?2 WHERE group_id = ?3",
+            params![UNGROUPED_ID, first_sort_order, group_id],
+        )
+        .map_err(|_| RytError::Rusqlite)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    fn next_category_sort_order(&self, group_id: i64) -> Result<i64, rusqlite::Error> {
+        return self.connection.query_row(
+            "select ifnull(max(sort_order) + 1, 0) from categories where group_id = ?1",
+            params![group_id],
+            |row| row.get(0),
+        );
+    }
+
+    // Move a category to the end of a group, UNGROUPED_ID takes it out of its group
+    pub fn set_category_group(&self, category_id: i64, group_id: i64) -> Result<(), RytError> {
+        if group_id != UNGROUPED_ID {
+            self.get::<CategoryGroup>(group_id)
+                .map_err(|_| RytError::CategoryGroupMissing)?;
+        }
+
+        let sort_order = self
+            .next_category_sort_order(group_id)
+            .map_err(|_| RytError::Rusqlite)?;
+        let changed = self
+            .connection
+            .execute(
+                "UPDATE categories SET group_id = ?1, sort_order = ?2 WHERE id = ?3",
+                params![group_id, sort_order, category_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        match changed {
+            0 => Err(RytError::CategoryMissing),
+            _ => Ok(()),
+        }
+    }
+
+    // Swap a group with the one listed before or after it
+    pub fn move_category_group(&self, group_id: i64, up: bool) -> Result<(), RytError> {
+        let mut ids: Vec<i64> = self
+            .get_category_groups()
+            .map_err(|_| RytError::Rusqlite)?
+            .iter()
+            .map(|g| g.id)
+            .collect();
+        if !move_id(&mut ids, group_id, up) {
+            return Err(RytError::CategoryGroupMissing);
+        }
+
+        return self.write_sort_order("category_groups", &ids);
+    }
+
+    // Swap a category with the one listed before or after it in its group
+    pub fn move_category(&self, category_id: i64, up: bool) -> Result<(), RytError> {
+        let category: Category = self
+            .get(category_id)
+            .map_err(|_| RytError::CategoryMissing)?;
+
+        let mut stmt = self
+            .connection
+            .prepare("SELECT id FROM categories WHERE group_id = ?1 ORDER BY sort_order, id")
+            .map_err(|_| RytError::Rusqlite)?;
+        let iter = stmt
+            .query_map(params![category.group_id], |row| row.get(0))
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut ids: Vec<i64> = vec![];
+        for c in iter {
+            ids.push(c.map_err(|_| RytError::Rusqlite)?);
+        }
+        move_id(&mut ids, category_id, up);
+
+        return self.write_sort_order("categories", &ids);
+    }
+
+    // Number the rows in the order given
+    fn write_sort_order(&self, table: &str, ids: &[i64]) -> Result<(), RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let query = format!("UPDATE {} SET sort_order = ?1 WHERE rowid = ?2", table);
+        for (i, id) in ids.iter().enumerate() {
+            tx.execute(&query, params![i as i64, id])
+                .map_err(|_| RytError::Rusqlite)?;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    // Existing account with the name, otherwise a new one
+    pub fn get_or_create_account(&self, name: &str) -> Result<i64, rusqlite::Error> {
+        let found: Option<i64> = self
+            .connection
+            .query_row(
+                "select rowid from accounts where display_name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match found {
+            Some(id) => Ok(id),
+            None => self.insert(Account::new(name)),
+        }
+    }
+
+    // Returns the row id. Occurrences start counting from the start date.
+    pub fn create_scheduled_transaction(
+        &self,
+        mut scheduled: ScheduledTransaction,
+    ) -> Result<i64, RytError> {
+        if scheduled.amount == 0 {
+            return Err(RytError::ScheduleNoAmount);
+        }
+        if let Some(end_date) = scheduled.end_date {
+            if end_date < scheduled.start_date {
+                return Err(RytError::ScheduleEndBeforeStart);
+            }
+        }
+
+        scheduled.occurrence_count = 0;
+        return self.insert(scheduled).map_err(|_| RytError::Rusqlite);
+    }
+
+    // Transactions it already made are kept
+    pub fn delete_scheduled_transaction(&self, scheduled_id: i64) -> Result<(), rusqlite::Error> {
+        match self.delete::<ScheduledTransaction>(scheduled_id)? {
+            0 => Err(rusqlite::Error::QueryReturnedNoRows),
+            _ => Ok(()),
+        }
+    }
+
+    // Make a transaction for every occurrence dated up to now which doesn't have one yet.
+    // Returns how many were made.
+    pub fn create_due_scheduled(&self, now: i64) -> Result<i64, RytError> {
+        let all_scheduled: Vec<ScheduledTransaction> = self
+            .get_all(OrderBy::None)
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut created: i64 = 0;
+        for scheduled in all_scheduled {
+            let dates = schedule::pending_dates(&scheduled, now);
+            if dates.is_empty() {
+                continue;
+            }
+
+            for date in &dates {
+                let mut trans = Transaction::new_raw(
+                    scheduled.payee.clone(),
+                    scheduled.amount,
+                    *date,
+                    scheduled.account_id,
+                );
+                trans.category_id = scheduled.category_id;
+                trans.notes = scheduled.notes.clone();
+                self.insert(trans).map_err(|_| RytError::Rusqlite)?;
+            }
+
+            self.connection
+                .execute(
+                    "UPDATE scheduled_transactions SET occurrence_count = ?1 WHERE rowid = ?2",
+                    params![
+                        scheduled.occurrence_count + dates.len() as i64,
+                        scheduled.id
+                    ],
+                )
+                .map_err(|_| RytError::Rusqlite)?;
+            created += dates.len() as i64;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(created);
+    }
+
+    // Occurrences for an account not made into transactions yet, up to until, soonest first
+    pub fn get_upcoming_transactions(
+        &self,
+        account_id: i64,
+        until: i64,
+    ) -> Result<Vec<UpcomingTransaction>, rusqlite::Error> {
+        let all_scheduled: Vec<ScheduledTransaction> = self.get_all(OrderBy::None)?;
+
+        let mut ret: Vec<UpcomingTransaction> = vec![];
+        for scheduled in all_scheduled.iter().filter(|s| s.account_id == account_id) {
+            for date in schedule::pending_dates(scheduled, until) {
+                ret.push(UpcomingTransaction {
+                    scheduled_id: scheduled.id,
+                    payee: scheduled.payee.clone(),
+                    account_id: scheduled.account_id,
+                    amount: scheduled.amount,
+                    date,
                 });
             }
         }
-        ret.append(&mut new_cats);
 
+        ret.sort_by_key(|u| (u.date, u.scheduled_id));
         Ok(ret)
     }
 
-    pub fn rename_category(
+    // Every open account's balance projected days days forward from unix_now, with its scheduled
+    // occurrences and, when include_spending, its average daily spending
+    pub fn get_account_forecasts(
         &self,
-        category_id: i64,
-        new_name: String,
-    ) -> Result<(), rusqlite::Error> {
+        unix_now: i64,
+        days: i64,
+        include_spending: bool,
+    ) -> Result<Vec<AccountForecast>, rusqlite::Error> {
+        let until = unix_now + days * 24 * 60 * 60;
+        let spending = match include_spending {
+            true => self.average_daily_spending(unix_now)?,
+            false => HashMap::new(),
+        };
+
+        // The balances today, transactions dated later are added in order by the forecast
+        let mut balances: HashMap<i64, i64> = HashMap::new();
+        {
+            let mut stmt = self.connection.prepare(
+                "SELECT account_id, sum(amount) FROM transactions WHERE date <= ?1 GROUP BY account_id",
+            )?;
+            let iter = stmt.query_map(params![unix_now], |row| {
+                Ok((row.get::<usize, i64>(0)?, row.get::<usize, i64>(1)?))
+            })?;
+            for c in iter {
+                let (account_id, balance) = c?;
+                balances.insert(account_id, balance);
+            }
+        }
+
+        let future_query = format!(
+            "SELECT {} FROM {} WHERE account_id = ?1 AND date > ?2 AND date <= ?3 ORDER BY date, rowid",
+            Transaction::get_fetch_schema(),
+            Transaction::get_table_name(),
+        );
+        let mut future_stmt = self.connection.prepare(&future_query)?;
+
+        let mut ret: Vec<AccountForecast> = vec![];
+        for account in self.get_account_display_list()? {
+            let mut future: Vec<Transaction> = vec![];
+            let iter = future_stmt
+                .query_map(params![account.account_id, unix_now, until], |row| {
+                    Ok(Transaction::row_to_data(row))
+                })?;
+            for c in iter {
+                future.push(c?);
+            }
+
+            let upcoming = self.get_upcoming_transactions(account.account_id, until)?;
+            ret.push(AccountForecast::new(
+                account.account_id,
+                balances.get(&account.account_id).copied().unwrap_or(0),
+                unix_now,
+                days,
+                &future,
+                &upcoming,
+                spending.get(&account.account_id).copied().unwrap_or(0),
+            ));
+        }
+        Ok(ret)
+    }
+
+    // Categorized outflow per day over the AVERAGE_SPENDING_DAYS before unix_now, by account.
+    // Categories the account has scheduled transactions in are left out, the forecast already
+    // has those.
+    fn average_daily_spending(&self, unix_now: i64) -> Result<HashMap<i64, i64>, rusqlite::Error> {
         let query = format!(
             "
-                update categories
-                set display_name = '{0}'
-                where ROWID = {1}
+            with category_lines as ( {} )
+            select
+            transactions.account_id,
+            -sum(category_lines.amount) as spent
+            from category_lines
+                join transactions on transactions.rowid = category_lines.transaction_id
+            where category_lines.transfer_id = 0
+                and category_lines.date between :unix_start and :unix_end
+                and category_lines.category_id in (select id from categories)
+                and category_lines.category_id not in
+                    (select category_id from scheduled_transactions where account_id = transactions.account_id)
+            group by transactions.account_id
+            having spent > 0
             ",
-            new_name, category_id
+            CATEGORY_LINES
         );
 
-        self.connection.execute(&query, ())?;
-        Ok(())
+        let mut stmt = self.connection.prepare(&query)?;
+        let iter = stmt.query_map(
+            named_params! {
+                ":unix_start": unix_now - AVERAGE_SPENDING_DAYS * 24 * 60 * 60,
+                ":unix_end": unix_now,
+            },
+            |row| Ok((row.get::<usize, i64>(0)?, row.get::<usize, i64>(1)?)),
+        )?;
+
+        let mut ret: HashMap<i64, i64> = HashMap::new();
+        for c in iter {
+            let (account_id, spent) = c?;
+            ret.insert(account_id, spent / AVERAGE_SPENDING_DAYS);
+        }
+        Ok(ret)
+    }
+
+    pub fn create_payee_rule(&self, rule: PayeeRule) -> Result<i64, RytError> {
+        rule.validate()?;
+        return self.insert(rule).map_err(|_| RytError::Rusqlite);
+    }
+
+    // In the order they are tried
+    pub fn get_payee_rules(&self) -> Result<Vec<PayeeRule>, rusqlite::Error> {
+        let mut rules: Vec<PayeeRule> = self.get_all(OrderBy::None)?;
+        rules.sort_by_key(|r| r.id);
+        Ok(rules)
+    }
+
+    pub fn delete_payee_rule(&self, rule_id: i64) -> Result<(), rusqlite::Error> {
+        match self.delete::<PayeeRule>(rule_id)? {
+            0 => Err(rusqlite::Error::QueryReturnedNoRows),
+            _ => Ok(()),
+        }
     }
 
-    pub fn import(&self, file_path: &str, account_id: i64) -> Result<(), RytError> {
-        let mut headers = true;
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
+    // Run the rules over transactions already in the file which have no category. Transfers,
+    // split and reconciled transactions are left alone. Returns how many changed.
+    pub fn apply_payee_rules(&self) -> Result<i64, RytError> {
+        let rules = self.get_payee_rules().map_err(|_| RytError::Rusqlite)?;
+        if rules.is_empty() {
+            return Ok(0);
+        }
+
+        let query = format!(
+            "SELECT {} FROM {} WHERE category_id = 0 AND transfer_id = 0
+            AND NOT EXISTS ( SELECT 1 FROM transaction_splits WHERE transaction_id = transactions.rowid )",
+            Transaction::get_fetch_schema(),
+            Transaction::get_table_name(),
+        );
+
+        let mut uncategorized: Vec<Transaction> = vec![];
+        {
+            let mut stmt = self
+                .connection
+                .prepare(&query)
+                .map_err(|_| RytError::Rusqlite)?;
+            let iter = stmt
+                .query_map([], |row| Ok(Transaction::row_to_data(row)))
+                .map_err(|_| RytError::Rusqlite)?;
+            for c in iter {
+                uncategorized.push(c.map_err(|_| RytError::Rusqlite)?);
+            }
+        }
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
 
-        for line in reader.lines() {
-            // to appease the borrow checker
-            let line_str = line.unwrap();
+        let mut changed: i64 = 0;
+        for trans in uncategorized {
+            if trans.is_reconciled() {
+                continue;
+            }
 
-            if headers {
-                headers = false;
+            let mut updated = trans.clone();
+            if !apply_rules(&rules, &mut updated) || updated == trans {
                 continue;
             }
 
-            // account, date, payee, outflow, inflow, categories
-            let parts: Vec<&str> = line_str.split(',').collect();
+            self.connection
+                .execute(
+                    "UPDATE transactions SET payee = ?1, category_id = ?2, notes = ?3 WHERE rowid = ?4",
+                    params![updated.payee, updated.category_id, updated.notes, updated.id],
+                )
+                .map_err(|_| RytError::Rusqlite)?;
+            changed += 1;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(changed);
+    }
 
-            // 1 date
-            let mut date_str: String = parts.get(1).unwrap().to_string();
-            date_str.push_str("T00:00:00");
-            let date = PrimitiveDateTime::parse(&date_str, &Iso8601::DEFAULT).unwrap();
-            let unix_date = date.assume_utc().unix_timestamp();
+    pub fn create_import_profile(&self, profile: ImportProfile) -> Result<i64, RytError> {
+        profile.validate()?;
 
-            // 2 payee
-            let payee: String = parts.get(2).unwrap().to_string();
+        // Check the profile can build a reader
+        csv_import::reader_builder(&profile)?;
 
-            // 3 outflow
-            let outflow = parts.get(3).unwrap();
-            let outflow: i64 = match outflow.trim().parse::<f64>() {
-                Ok(v) => data::dollars_to_cents(v),
-                Err(v) => 0,
-            };
+        return self.insert(profile).map_err(|_| RytError::Rusqlite);
+    }
 
-            // 4 inflow
-            let inflow = parts.get(4).unwrap();
-            let inflow: i64 = match inflow.trim().parse::<f64>() {
-                Ok(v) => data::dollars_to_cents(v),
-                Err(v) => 0,
+    // Read a file without writing anything. Lines which can't be read are reported with their
+    // line number instead of stopping the import.
+    pub fn import_preview(
+        &self,
+        file_path: &str,
+        account_id: i64,
+        profile_id: i64,
+    ) -> Result<ImportPreview, RytError> {
+        let profile: ImportProfile = self.get(profile_id).map_err(|_| RytError::Rusqlite)?;
+
+        let mut reader = csv_import::reader_builder(&profile)?
+            .from_path(file_path)
+            .map_err(|_| RytError::ImportReadFile)?;
+
+        let mut preview = ImportPreview::new(account_id, file_name(file_path));
+        for (i, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(v) => v,
+                Err(e) => {
+                    // Usually bad utf8. Nothing after this can be trusted if the file itself
+                    // is unreadable.
+                    let line = match e.position() {
+                        Some(v) => v.line(),
+                        None => return Err(RytError::ImportReadFile),
+                    };
+
+                    if i >= profile.skip_rows as usize {
+                        preview.errors.push(ImportLineError {
+                            line,
+                            error: RytError::ImportInvalidLine,
+                            text: String::new(),
+                        });
+                    }
+                    continue;
+                }
             };
 
-            // 5 category
-            let category_str: String = parts.get(5).unwrap().to_string();
+            if i < profile.skip_rows as usize {
+                continue;
+            }
+
+            let line = record.position().map_or(0, |v| v.line());
+            match csv_import::parse_record(&record, &profile, account_id) {
+                Ok(row) => {
+                    let mut candidate = ImportCandidate {
+                        trans: row.trans,
+                        category: row.category,
+                        line,
+                        duplicate_of: 0,
+                        skip: false,
+                    };
+
+                    // Only add categories for outflow
+                    if candidate.trans.amount > 0 {
+                        candidate.category = String::new();
+                    }
+
+                    preview.candidates.push(candidate);
+                }
+                Err(error) => preview.errors.push(ImportLineError {
+                    line,
+                    error,
+                    text: csv_import::record_text(&record, &profile),
+                }),
+            }
+        }
+
+        self.finish_preview(&mut preview)?;
+        return Ok(preview);
+    }
 
-            // build transaction
-            let mut trans = Transaction::new(payee, inflow, outflow, unix_date, account_id)?;
+    // Read an OFX or QFX statement without writing anything
+    pub fn import_ofx_preview(
+        &self,
+        file_path: &str,
+        account_id: i64,
+    ) -> Result<ImportPreview, RytError> {
+        let bytes = std::fs::read(file_path).map_err(|_| RytError::ImportReadFile)?;
+
+        // QFX files are often windows-1252 rather than utf8
+        let text = ofx_import::decode(&bytes);
+        let statement = ofx_import::parse_statement(&text)?;
+
+        let mut preview = ImportPreview::new(account_id, file_name(file_path));
+        for ofx_trans in &statement.transactions {
+            match ofx_import::to_transaction(ofx_trans, account_id) {
+                Ok(trans) => preview.candidates.push(ImportCandidate {
+                    trans,
+                    category: String::new(),
+                    line: ofx_trans.line,
+                    duplicate_of: 0,
+                    skip: false,
+                }),
+                Err(error) => preview.errors.push(ImportLineError {
+                    line: ofx_trans.line,
+                    error,
+                    text: ofx_import::transaction_text(ofx_trans),
+                }),
+            }
+        }
+        preview.ledger_balance = ofx_import::ledger_balance(&statement);
+
+        self.finish_preview(&mut preview)?;
+        return Ok(preview);
+    }
+
+    // Flag duplicates and fill in the account balance, for any import format
+    fn finish_preview(&self, preview: &mut ImportPreview) -> Result<(), RytError> {
+        // Flag copies of transactions already in the account. Each existing transaction is only
+        // the copy of one line, so repeated identical purchases aren't all skipped.
+        let mut claimed: Vec<i64> = vec![];
+        for candidate in &mut preview.candidates {
+            candidate.duplicate_of = self
+                .find_duplicate(&candidate.trans, &claimed)
+                .map_err(|_| RytError::Rusqlite)?;
+
+            if candidate.duplicate_of != 0 {
+                claimed.push(candidate.duplicate_of);
+                candidate.skip = true;
+            }
+        }
+
+        let accounts = self
+            .get_account_display_list()
+            .map_err(|_| RytError::Rusqlite)?;
+        preview.account_balance = accounts
+            .iter()
+            .find(|a| a.account_id == preview.account_id)
+            .map_or(0, |a| a.balance);
+
+        Ok(())
+    }
+
+    // Id of an existing transaction which trans looks like a copy of, or 0
+    fn find_duplicate(&self, trans: &Transaction, claimed: &[i64]) -> Result<i64, rusqlite::Error> {
+        // The bank's own id is certain
+        if !trans.fitid.is_empty() {
+            let found: Option<i64> = self
+                .connection
+                .query_row(
+                    "select rowid from transactions where account_id = ?1 and fitid = ?2",
+                    params![trans.account_id, trans.fitid],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            if let Some(id) = found {
+                return Ok(id);
+            }
+        }
+
+        // Two different bank ids are never the same transaction
+        let query = "
+            select payee, rowid
+            from transactions
+            where account_id = ?1 and amount = ?2 and date between ?3 and ?4
+            and (fitid = '' or ?6 = '')
+            order by abs(date - ?5)
+            ";
 
-            // Only add categories for outflow
-            if outflow > 0 {
-                // get category id, otherwise create the category
-                if self.category_exists(&category_str).unwrap() {
-                    trans.category_id = self.get_category_id(&category_str).unwrap();
+        let mut stmt = self.connection.prepare(query)?;
+        let iter = stmt.query_map(
+            params![
+                trans.account_id,
+                trans.amount,
+                trans.date - duplicates::DATE_WINDOW_SECONDS,
+                trans.date + duplicates::DATE_WINDOW_SECONDS,
+                trans.date,
+                trans.fitid
+            ],
+            |row| Ok((row.get::<usize, String>(0)?, row.get::<usize, i64>(1)?)),
+        )?;
+
+        for c in iter {
+            let (payee, id) = c?;
+            if !claimed.contains(&id) && duplicates::is_same_payee(&payee, &trans.payee) {
+                return Ok(id);
+            }
+        }
+
+        Ok(0)
+    }
+
+    // Write the previewed transactions which aren't skipped. Either all of them are inserted or
+    // none are. Returns the import batch id.
+    pub fn import_commit(&self, preview: ImportPreview) -> Result<i64, RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let candidates: Vec<ImportCandidate> =
+            preview.candidates.into_iter().filter(|c| !c.skip).collect();
+
+        let batch = ImportBatch::new(
+            preview.account_id,
+            chrono::Utc::now().timestamp(),
+            preview.file_name,
+            candidates.len() as i64,
+        );
+        let batch_id = self.insert(batch).map_err(|_| RytError::Rusqlite)?;
+
+        let rules = self.get_payee_rules().map_err(|_| RytError::Rusqlite)?;
+        for candidate in candidates {
+            let mut trans = candidate.trans;
+            trans.import_batch_id = batch_id;
+
+            if !candidate.category.is_empty() {
+                trans.category_id = self
+                    .get_or_create_category(&candidate.category)
+                    .map_err(|_| RytError::Rusqlite)?;
+            }
+
+            // A category from the file wins over one from a rule
+            apply_rules(&rules, &mut trans);
+
+            self.insert(trans).map_err(|_| RytError::Rusqlite)?;
+        }
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        return Ok(batch_id);
+    }
+
+    // Remove an import and every transaction it created
+    pub fn import_rollback(&self, batch_id: i64) -> Result<(), RytError> {
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        // Reconciled transactions are locked, see get_unlocked_transaction
+        let reconciled: i64 = self
+            .connection
+            .query_row(
+                "SELECT count(*) FROM transactions
+                WHERE import_batch_id = ?1
+                    AND (cleared = ?2 OR transfer_id IN (SELECT rowid FROM transactions WHERE cleared = ?2))",
+                params![batch_id, ClearedStatus::Reconciled],
+                |row| row.get(0),
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+        if reconciled > 0 {
+            return Err(RytError::TransactionReconciled);
+        }
+
+        let count = self
+            .delete::<ImportBatch>(batch_id)
+            .map_err(|_| RytError::Rusqlite)?;
+        if count == 0 {
+            return Err(RytError::ImportBatchMissing);
+        }
+
+        self.connection
+            .execute(
+                "DELETE FROM transactions WHERE import_batch_id = ?1",
+                params![batch_id],
+            )
+            .map_err(|_| RytError::Rusqlite)?;
+
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
+        Ok(())
+    }
+
+    // Read a QIF file straight into the database. Sections under a named account go into the
+    // account with that name, created if needed, others go into account_id. Each account gets its
+    // own import batch so it can be undone. Nothing is written if any record is bad.
+    pub fn import_qif(&self, file_path: &str, account_id: i64) -> Result<(), RytError> {
+        let bytes = std::fs::read(file_path).map_err(|_| RytError::ImportReadFile)?;
+        let text = String::from_utf8_lossy(&bytes);
+        let qif_accounts = qif::parse(&text)?;
+
+        let tx = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let now = chrono::Utc::now().timestamp();
+        let rules = self.get_payee_rules().map_err(|_| RytError::Rusqlite)?;
+        for qif_account in qif_accounts {
+            let target_account = match qif_account.name.is_empty() {
+                true => account_id,
+                false => self
+                    .get_or_create_account(&qif_account.name)
+                    .map_err(|_| RytError::Rusqlite)?,
+            };
+
+            let mut batch = ImportBatch::new(target_account, now, file_name(file_path), 0);
+            batch.id = self.insert(batch.clone()).map_err(|_| RytError::Rusqlite)?;
+
+            for qif_trans in qif_account.transactions {
+                let mut trans = Transaction::new_raw(
+                    qif_trans.payee.clone(),
+                    qif_trans.amount,
+                    qif_trans.date,
+                    target_account,
+                );
+                trans.notes = qif_trans.memo.clone();
+                trans.import_batch_id = batch.id;
+
+                // Split lines become the transaction's splits, a lone line is just its category
+                let mut splits: Vec<TransactionSplit> = vec![];
+                let mut category = qif_trans.category.clone();
+                if qif_trans.splits.len() == 1 {
+                    category = qif_trans.splits[0].category.clone();
                 } else {
-                    // create that category
-                    self.insert(Category::new(&category_str)).unwrap();
-                    trans.category_id = self.get_category_id(&category_str).unwrap();
+                    for split in &qif_trans.splits {
+                        let mut line = TransactionSplit::new(
+                            self.qif_category_id(&split.category, split.amount)?,
+                            split.amount,
+                        );
+                        line.notes = split.memo.clone();
+                        splits.push(line);
+                    }
+
+                    // Splits which don't add up leave the rest uncategorized
+                    let split_total: i64 = splits.iter().map(|s| s.amount).sum();
+                    if !splits.is_empty() && split_total != qif_trans.amount {
+                        splits.push(TransactionSplit::new(
+                            UNCATEGORIZED_ID,
+                            qif_trans.amount - split_total,
+                        ));
+                    }
                 }
+
+                trans.category_id = self.qif_category_id(&category, qif_trans.amount)?;
+                apply_rules(&rules, &mut trans);
+
+                self.insert_with_splits(trans, splits)?;
+                batch.transaction_count += 1;
             }
 
-            self.insert(trans).unwrap();
+            self.update(batch.id, batch)
+                .map_err(|_| RytError::Rusqlite)?;
         }
 
+        tx.commit().map_err(|_| RytError::Rusqlite)?;
         Ok(())
     }
+
+    // Category of an imported QIF line. Only outflow gets a category, inflow is ready to assign.
+    fn qif_category_id(&self, qif_category: &str, amount: i64) -> Result<i64, RytError> {
+        let category_name = qif::category_name(qif_category);
+        if amount >= 0 || category_name.is_empty() {
+            return Ok(UNCATEGORIZED_ID);
+        }
+        return self
+            .get_or_create_category(&category_name)
+            .map_err(|_| RytError::Rusqlite);
+    }
+
+    // Newest first
+    pub fn get_import_batches(&self, account_id: i64) -> Result<Vec<ImportBatch>, rusqlite::Error> {
+        let query = format!(
+            "SELECT {} FROM {} WHERE account_id = ?1 ORDER BY date DESC, rowid DESC",
+            ImportBatch::get_fetch_schema(),
+            ImportBatch::get_table_name(),
+        );
+
+        let mut stmt = self.connection.prepare(&query)?;
+        let iter = stmt.query_map(params![account_id], |row| Ok(ImportBatch::row_to_data(row)))?;
+
+        let mut ret: Vec<ImportBatch> = vec![];
+        for c in iter {
+            ret.push(c.unwrap());
+        }
+
+        Ok(ret)
+    }
 }
@@ -0,0 +1,215 @@
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+
+use data::RytError;
+
+// Snapshots copy every table row for row, including rowids, so ids inside the data stay valid.
+// They record the schema version they came from. Restoring builds a new file at that version,
+// fills it, then runs the remaining migrations, so old snapshots keep working.
+
+// Bump when the layout of Snapshot itself changes
+pub const SNAPSHOT_FORMAT: i64 = 1;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TableDump {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Snapshot {
+    pub format: i64,
+
+    // PRAGMA user_version of the file the snapshot came from
+    pub schema_version: i64,
+
+    pub tables: Vec<TableDump>,
+}
+
+fn quote_identifier(name: &str) -> String {
+    return format!("\"{}\"", name.replace('"', "\"\""));
+}
+
+// sqlite_sequence is kept so autoincrement ids of deleted rows aren't handed out again
+fn table_names(connection: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = connection.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND ( name NOT LIKE 'sqlite_%' OR name = 'sqlite_sequence' ) ORDER BY name",
+    )?;
+    let iter = stmt.query_map([], |row| row.get(0))?;
+
+    let mut ret: Vec<String> = vec![];
+    for c in iter {
+        ret.push(c?);
+    }
+    return Ok(ret);
+}
+
+// Columns of a table. The rowid is added first unless an INTEGER PRIMARY KEY column already is it.
+fn table_columns(connection: &Connection, table: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt =
+        connection.prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))?;
+
+    // name, type, primary key position
+    let iter = stmt.query_map([], |row| {
+        Ok((
+            row.get::<usize, String>(1)?,
+            row.get::<usize, String>(2)?,
+            row.get::<usize, i64>(5)?,
+        ))
+    })?;
+
+    let mut columns: Vec<String> = vec![];
+    let mut rowid_alias = false;
+    let mut key_count = 0;
+    for c in iter {
+        let (name, column_type, key) = c?;
+        if key > 0 {
+            key_count += 1;
+            rowid_alias = column_type.eq_ignore_ascii_case("INTEGER");
+        }
+        columns.push(name);
+    }
+
+    if !(rowid_alias && key_count == 1) {
+        columns.insert(0, "rowid".to_string());
+    }
+    return Ok(columns);
+}
+
+fn to_json(value: ValueRef) -> serde_json::Value {
+    return match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(v) => serde_json::Value::from(v),
+        ValueRef::Real(v) => serde_json::Value::from(v),
+        ValueRef::Text(v) => serde_json::Value::from(String::from_utf8_lossy(v).to_string()),
+        ValueRef::Blob(v) => serde_json::Value::from(v.to_vec()),
+    };
+}
+
+fn from_json(value: &serde_json::Value) -> Result<Value, RytError> {
+    return match value {
+        serde_json::Value::Null => Ok(Value::Null),
+        serde_json::Value::Bool(v) => Ok(Value::Integer(*v as i64)),
+        serde_json::Value::Number(v) => match v.as_i64() {
+            Some(i) => Ok(Value::Integer(i)),
+            None => Ok(Value::Real(
+                v.as_f64().ok_or(RytError::RestoreInvalidSnapshot)?,
+            )),
+        },
+        serde_json::Value::String(v) => Ok(Value::Text(v.clone())),
+        serde_json::Value::Array(v) => {
+            let mut bytes: Vec<u8> = vec![];
+            for b in v {
+                let byte = b.as_u64().ok_or(RytError::RestoreInvalidSnapshot)?;
+                bytes.push(u8::try_from(byte).map_err(|_| RytError::RestoreInvalidSnapshot)?);
+            }
+            Ok(Value::Blob(bytes))
+        }
+        serde_json::Value::Object(_) => Err(RytError::RestoreInvalidSnapshot),
+    };
+}
+
+pub fn dump(connection: &Connection) -> Result<Snapshot, RytError> {
+    let mut snapshot = Snapshot {
+        format: SNAPSHOT_FORMAT,
+        schema_version: super::migrations::get_version(connection)?,
+        tables: vec![],
+    };
+
+    for name in table_names(connection).map_err(|_| RytError::Rusqlite)? {
+        let columns = table_columns(connection, &name).map_err(|_| RytError::Rusqlite)?;
+
+        let column_list: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+        let query = format!(
+            "SELECT {} FROM {} ORDER BY rowid",
+            column_list.join(", "),
+            quote_identifier(&name)
+        );
+
+        let mut stmt = connection.prepare(&query).map_err(|_| RytError::Rusqlite)?;
+        let iter = stmt
+            .query_map([], |row| {
+                let mut values: Vec<serde_json::Value> = vec![];
+                for i in 0..columns.len() {
+                    values.push(to_json(row.get_ref(i)?));
+                }
+                Ok(values)
+            })
+            .map_err(|_| RytError::Rusqlite)?;
+
+        let mut rows: Vec<Vec<serde_json::Value>> = vec![];
+        for c in iter {
+            rows.push(c.map_err(|_| RytError::Rusqlite)?);
+        }
+
+        snapshot.tables.push(TableDump {
+            name,
+            columns,
+            rows,
+        });
+    }
+
+    return Ok(snapshot);
+}
+
+// Fill a new, empty file from a snapshot and bring it up to date
+pub fn load(connection: &mut Connection, snapshot: &Snapshot) -> Result<(), RytError> {
+    if snapshot.format > SNAPSHOT_FORMAT {
+        return Err(RytError::DatabaseNewerThanApp);
+    }
+
+    // Create the tables exactly as the snapshot's version had them
+    super::migrations::run_to(connection, snapshot.schema_version)?;
+
+    let tx = connection.transaction().map_err(|_| RytError::Rusqlite)?;
+
+    let existing_tables = table_names(&tx).map_err(|_| RytError::Rusqlite)?;
+    for table in &snapshot.tables {
+        if !existing_tables.contains(&table.name) {
+            return Err(RytError::RestoreInvalidSnapshot);
+        }
+
+        // Migrations add some rows of their own, the snapshot replaces them
+        tx.execute(
+            &format!("DELETE FROM {}", quote_identifier(&table.name)),
+            [],
+        )
+        .map_err(|_| RytError::Rusqlite)?;
+
+        let column_list: Vec<String> = table.columns.iter().map(|c| quote_identifier(c)).collect();
+        let placeholders: Vec<String> =
+            (1..=table.columns.len()).map(|i| format!("?{i}")).collect();
+        let query = format!(
+            "INSERT INTO {} ( {} ) VALUES ( {} )",
+            quote_identifier(&table.name),
+            column_list.join(", "),
+            placeholders.join(", ")
+        );
+
+        let mut stmt = tx
+            .prepare(&query)
+            .map_err(|_| RytError::RestoreInvalidSnapshot)?;
+        for row in &table.rows {
+            if row.len() != table.columns.len() {
+                return Err(RytError::RestoreInvalidSnapshot);
+            }
+
+            let mut values: Vec<Value> = vec![];
+            for v in row {
+                values.push(from_json(v)?);
+            }
+
+            stmt.execute(params_from_iter(values.iter()))
+                .map_err(|_| RytError::RestoreInvalidSnapshot)?;
+        }
+    }
+
+    tx.commit().map_err(|_| RytError::Rusqlite)?;
+
+    // Then upgrade like any other old file
+    super::migrations::run(connection)?;
+
+    Ok(())
+}
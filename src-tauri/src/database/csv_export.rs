@@ -0,0 +1,103 @@
+use chrono::DateTime;
+
+use data::csv_export::{CsvExportOptions, ExportColumn};
+use data::import_profile::AmountFormat;
+use data::RytError;
+
+// One transaction to write, with names in place of ids
+pub struct CsvExportRow {
+    pub date: i64,
+    pub payee: String,
+    pub account: String,
+    pub category: String,
+    pub notes: String,
+    pub amount: i64,
+}
+
+// Same layout ImportProfile::new_default reads
+fn format_date(unix_date: i64) -> String {
+    return match DateTime::from_timestamp(unix_date, 0) {
+        Some(v) => v.format("%Y-%m-%d").to_string(),
+        None => String::new(),
+    };
+}
+
+// The fields of one line, in the order of options.columns
+pub fn record(row: &CsvExportRow, options: &CsvExportOptions) -> Vec<String> {
+    let mut ret: Vec<String> = vec![];
+    for c in &options.columns {
+        match c {
+            ExportColumn::Date => ret.push(format_date(row.date)),
+            ExportColumn::Payee => ret.push(row.payee.clone()),
+            ExportColumn::Account => ret.push(row.account.clone()),
+            ExportColumn::Category => ret.push(row.category.clone()),
+            ExportColumn::Notes => ret.push(row.notes.clone()),
+            ExportColumn::Amount => match options.amount_format {
+                AmountFormat::Signed => ret.push(data::amount_to_plain(row.amount)),
+
+                // Both positive, the unused one left empty
+                AmountFormat::OutflowInflow => {
+                    if row.amount < 0 {
+                        ret.push(data::amount_to_plain(-row.amount));
+                        ret.push(String::new());
+                    } else {
+                        ret.push(String::new());
+                        ret.push(data::amount_to_plain(row.amount));
+                    }
+                }
+            },
+        }
+    }
+    return ret;
+}
+
+// Quoting of commas, quotes and line breaks is left to the csv writer
+pub fn write<W: std::io::Write>(
+    out: W,
+    rows: &[CsvExportRow],
+    options: &CsvExportOptions,
+) -> Result<(), RytError> {
+    let mut writer = csv::Writer::from_writer(out);
+
+    writer
+        .write_record(options.header())
+        .map_err(|_| RytError::ExportWriteFile)?;
+    for row in rows {
+        writer
+            .write_record(record(row, options))
+            .map_err(|_| RytError::ExportWriteFile)?;
+    }
+
+    writer.flush().map_err(|_| RytError::ExportWriteFile)?;
+    Ok(())
+}
+
+#[test]
+fn csv_export_escaping() {
+    let rows = [CsvExportRow {
+        date: 1705276800,
+        payee: "Smith, \"Bob\"".to_string(),
+        account: "Checking".to_string(),
+        category: "".to_string(),
+        notes: "two\nlines".to_string(),
+        amount: -4210,
+    }];
+
+    let mut out: Vec<u8> = vec![];
+    write(&mut out, &rows, &CsvExportOptions::new_default()).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Account,Date,Payee,Outflow,Inflow,Category,Notes\n\
+         Checking,2024-01-15,\"Smith, \"\"Bob\"\"\",42.10,,,\"two\nlines\"\n"
+    );
+
+    let options = CsvExportOptions {
+        columns: vec![ExportColumn::Payee, ExportColumn::Amount],
+        amount_format: AmountFormat::Signed,
+        ..CsvExportOptions::new_default()
+    };
+    assert_eq!(
+        record(&rows[0], &options),
+        vec!["Smith, \"Bob\"".to_string(), "-42.10".to_string()]
+    );
+}
@@ -0,0 +1,287 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use csv::{ReaderBuilder, StringRecord, Trim};
+
+use data::import_profile::*;
+use data::transaction::Transaction;
+use data::RytError;
+
+// One transaction read from a CSV row. The category is the name as written in the file.
+pub struct CsvRow {
+    pub trans: Transaction,
+    pub category: String,
+}
+
+// RFC 4180 reader configured for the profile. skip_rows is left to the caller.
+pub fn reader_builder(profile: &ImportProfile) -> Result<ReaderBuilder, RytError> {
+    let delimiter: u8 = match profile.delimiter.as_bytes() {
+        [v] => *v,
+        _ => return Err(RytError::ImportProfileDelimiter),
+    };
+
+    let mut builder = ReaderBuilder::new();
+    builder
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .trim(Trim::All);
+
+    return Ok(builder);
+}
+
+pub fn parse_record(
+    record: &StringRecord,
+    profile: &ImportProfile,
+    account_id: i64,
+) -> Result<CsvRow, RytError> {
+    let column = |index: i64| -> Result<&str, RytError> {
+        return record
+            .get(index as usize)
+            .ok_or(RytError::ImportMissingColumn);
+    };
+
+    let date = parse_date(column(profile.date_column)?, &profile.date_format)?;
+    let payee = column(profile.payee_column)?.to_string();
+
+    let (inflow, outflow) = match profile.amount_format {
+        AmountFormat::Signed => {
+            let mut amount = parse_amount(column(profile.amount_column)?)?;
+            if profile.negate_amounts {
+                amount = -amount;
+            }
+
+            if amount > 0 {
+                (amount, 0)
+            } else {
+                (0, -amount)
+            }
+        }
+        AmountFormat::OutflowInflow => (
+            parse_amount(column(profile.inflow_column)?)?.abs(),
+            parse_amount(column(profile.outflow_column)?)?.abs(),
+        ),
+    };
+
+    let mut trans =
+        Transaction::new(payee, inflow, outflow, date, account_id).map_err(|e| match e {
+            RytError::CreateTransactionInflowAndOutlow => RytError::ImportInflowAndOutflow,
+            _ => RytError::ImportNoAmount,
+        })?;
+
+    if let Some(notes_column) = profile.notes_column {
+        trans.notes = column(notes_column)?.to_string();
+    }
+
+    let category = match profile.category_column {
+        Some(category_column) => column(category_column)?.to_string(),
+        None => String::new(),
+    };
+
+    return Ok(CsvRow { trans, category });
+}
+
+// The fields of a record as they appeared in the file
+pub fn record_text(record: &StringRecord, profile: &ImportProfile) -> String {
+    return record
+        .iter()
+        .collect::<Vec<&str>>()
+        .join(&profile.delimiter);
+}
+
+// Unix timestamp of midnight UTC on the date
+fn parse_date(value: &str, format: &str) -> Result<i64, RytError> {
+    let date = match NaiveDate::parse_from_str(value, format) {
+        Ok(v) => v,
+
+        // Some banks add a time to the date
+        Err(_) => NaiveDateTime::parse_from_str(value, format)
+            .map_err(|_| RytError::ImportParseDate)?
+            .date(),
+    };
+
+    return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp());
+}
+
+// Dollars to cents. Accepts currency symbols, thousands separators and (1.00) for negatives.
+// An empty value is zero.
+fn parse_amount(value: &str) -> Result<i64, RytError> {
+    // A comma must separate thousands. "12,34" is a decimal comma and would
+    // otherwise import as 1234.00.
+    for group in value.split(',').skip(1) {
+        let digits = group.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits != 3 {
+            return Err(RytError::ImportParseAmount);
+        }
+    }
+
+    let mut cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+
+    if cleaned.is_empty() {
+        return Ok(0);
+    }
+
+    if cleaned.starts_with('(') && cleaned.ends_with(')') {
+        cleaned = format!("-{}", &cleaned[1..cleaned.len() - 1]);
+    }
+
+    let dollars = cleaned
+        .parse::<f64>()
+        .map_err(|_| RytError::ImportParseAmount)?;
+    return Ok(data::dollars_to_cents(dollars));
+}
+
+#[cfg(test)]
+fn read_all(input: &str, profile: &ImportProfile) -> Vec<StringRecord> {
+    reader_builder(profile)
+        .unwrap()
+        .from_reader(input.as_bytes())
+        .records()
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+#[test]
+fn parse_default_profile() {
+    let profile = ImportProfile::new_default();
+    let records = read_all(
+        "account, date, payee, outflow, inflow, category\nBOA,2024-01-02,Arbys,117.34,0.00,Extra\n",
+        &profile,
+    );
+
+    let row = parse_record(&records[1], &profile, 3).unwrap();
+    assert_eq!(row.trans.payee, "Arbys");
+    assert_eq!(row.trans.amount, -11734);
+    assert_eq!(row.trans.account_id, 3);
+    assert_eq!(row.trans.date, 1704153600);
+    assert_eq!(row.category, "Extra");
+}
+
+#[test]
+fn parse_quoted_fields() {
+    let profile = ImportProfile::new_default();
+    let records = read_all(
+        "BOA,2024-01-02,\"Smith, Jones \"\"and\"\" Co\",\"1,250.00\",,\"Bills, Utilities\"\n",
+        &profile,
+    );
+
+    let row = parse_record(&records[0], &profile, 0).unwrap();
+    assert_eq!(row.trans.payee, "Smith, Jones \"and\" Co");
+    assert_eq!(row.trans.amount, -125000);
+    assert_eq!(row.category, "Bills, Utilities");
+}
+
+#[test]
+fn parse_signed_amounts() {
+    let mut profile = ImportProfile::new_default();
+    profile.delimiter = ";".to_string();
+    profile.date_column = 0;
+    profile.date_format = "%m/%d/%Y".to_string();
+    profile.payee_column = 1;
+    profile.amount_format = AmountFormat::Signed;
+    profile.amount_column = 2;
+    profile.category_column = None;
+    profile.notes_column = Some(3);
+
+    let records = read_all(
+        "01/31/2024;Paycheck;$2,000.10;january\n02/01/2024;Coffee;(4.50);\n02/02/2024;Tea;-3;x\n",
+        &profile,
+    );
+
+    let row = parse_record(&records[0], &profile, 0).unwrap();
+    assert_eq!(row.trans.amount, 200010);
+    assert_eq!(row.trans.notes, "january");
+    assert_eq!(row.trans.date, 1706659200);
+    assert_eq!(row.category, "");
+
+    assert_eq!(
+        parse_record(&records[1], &profile, 0).unwrap().trans.amount,
+        -450
+    );
+    assert_eq!(
+        parse_record(&records[2], &profile, 0).unwrap().trans.amount,
+        -300
+    );
+
+    // Credit cards often report purchases as positive
+    profile.negate_amounts = true;
+    assert_eq!(
+        parse_record(&records[0], &profile, 0).unwrap().trans.amount,
+        -200010
+    );
+    assert_eq!(
+        parse_record(&records[1], &profile, 0).unwrap().trans.amount,
+        450
+    );
+}
+
+#[test]
+fn parse_decimal_comma() {
+    let mut profile = ImportProfile::new_default();
+    profile.delimiter = ";".to_string();
+    profile.date_column = 0;
+    profile.payee_column = 1;
+    profile.amount_format = AmountFormat::Signed;
+    profile.amount_column = 2;
+    profile.category_column = None;
+
+    let records = read_all(
+        "2024-01-02;Coffee;12,34\n2024-01-02;Rent;-1.234,56\n2024-01-02;Car;1,234,567.00\n2024-01-02;Tea;1,5\n",
+        &profile,
+    );
+
+    assert!(matches!(
+        parse_record(&records[0], &profile, 0),
+        Err(RytError::ImportParseAmount)
+    ));
+    assert!(matches!(
+        parse_record(&records[1], &profile, 0),
+        Err(RytError::ImportParseAmount)
+    ));
+    assert_eq!(
+        parse_record(&records[2], &profile, 0).unwrap().trans.amount,
+        123456700
+    );
+    assert!(matches!(
+        parse_record(&records[3], &profile, 0),
+        Err(RytError::ImportParseAmount)
+    ));
+}
+
+#[test]
+fn parse_errors() {
+    let profile = ImportProfile::new_default();
+    let records = read_all(
+        "BOA,01/02/2024,Arbys,1.00,,Extra\nBOA,2024-01-02,Arbys,abc,,Extra\nBOA,2024-01-02\nBOA,2024-01-02,Arbys,0,0,Extra\nBOA,2024-01-02,Arbys,1,2,Extra\n",
+        &profile,
+    );
+
+    assert!(matches!(
+        parse_record(&records[0], &profile, 0),
+        Err(RytError::ImportParseDate)
+    ));
+    assert!(matches!(
+        parse_record(&records[1], &profile, 0),
+        Err(RytError::ImportParseAmount)
+    ));
+    assert!(matches!(
+        parse_record(&records[2], &profile, 0),
+        Err(RytError::ImportMissingColumn)
+    ));
+    assert!(matches!(
+        parse_record(&records[3], &profile, 0),
+        Err(RytError::ImportNoAmount)
+    ));
+    assert!(matches!(
+        parse_record(&records[4], &profile, 0),
+        Err(RytError::ImportInflowAndOutflow)
+    ));
+
+    let mut profile = ImportProfile::new_default();
+    profile.delimiter = "::".to_string();
+    assert!(matches!(
+        reader_builder(&profile),
+        Err(RytError::ImportProfileDelimiter)
+    ));
+}
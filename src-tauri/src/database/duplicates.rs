@@ -0,0 +1,97 @@
+// Rules for deciding an imported transaction is already in the database.
+// Banks often shift the posted date by a few days between statements, and rewrite payee text,
+// so only the account and amount need to match exactly.
+
+// How far apart two dates can be and still be the same transaction
+pub const DATE_WINDOW_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+// Minimum payee_similarity for two payees to be the same
+const PAYEE_SIMILARITY: f64 = 0.7;
+
+// Lowercase letters and digits, with runs of anything else collapsed to one space
+fn normalize_payee(payee: &str) -> String {
+    let mut ret = String::new();
+    for word in payee
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        if !ret.is_empty() {
+            ret.push(' ');
+        }
+        ret.push_str(word);
+    }
+    return ret;
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Only the previous row of the edit distance table is needed
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let substitution = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + substitution);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    return prev[b.len()];
+}
+
+// 1.0 for the same payee, down to 0.0 for nothing in common
+pub fn payee_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_payee(a);
+    let b = normalize_payee(b);
+
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    return 1.0 - (levenshtein(&a, &b) as f64 / longest as f64);
+}
+
+pub fn is_same_payee(a: &str, b: &str) -> bool {
+    let norm_a = normalize_payee(a);
+    let norm_b = normalize_payee(b);
+
+    // One bank says "AMAZON MKTPLACE PMTS 1234", another says "Amazon"
+    let (shorter, longer) = if norm_a.len() < norm_b.len() {
+        (&norm_a, &norm_b)
+    } else {
+        (&norm_b, &norm_a)
+    };
+    if shorter.len() >= 3 && longer.contains(shorter.as_str()) {
+        return true;
+    }
+
+    return payee_similarity(a, b) >= PAYEE_SIMILARITY;
+}
+
+#[test]
+fn payee_similarity_values() {
+    assert_eq!(payee_similarity("Arbys", "ARBYS"), 1.0);
+    assert_eq!(payee_similarity("Trader Joe's", "trader joe s"), 1.0);
+    assert_eq!(payee_similarity("", ""), 1.0);
+    assert_eq!(payee_similarity("abc", "xyz"), 0.0);
+    assert!(payee_similarity("Starbucks", "Starbuks") > 0.85);
+}
+
+#[test]
+fn same_payee() {
+    assert!(is_same_payee("Arbys", "ARBYS #1234"));
+    assert!(is_same_payee("AMAZON MKTPLACE PMTS", "Amazon"));
+    assert!(is_same_payee("Starbucks", "Starbuks"));
+    assert!(!is_same_payee("Arbys", "The City"));
+
+    // Too short to trust a substring match
+    assert!(!is_same_payee("AT", "ATM WITHDRAWAL"));
+}
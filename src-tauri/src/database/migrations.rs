@@ -0,0 +1,132 @@
+use rusqlite::Connection;
+
+use data::RytError;
+
+// Ordered schema steps. PRAGMA user_version records how many have been applied to a file.
+// Never edit or reorder a step once it has shipped, append a new one instead. Each step is
+// frozen SQL on purpose, so opening an old file replays exactly what that version created.
+const MIGRATIONS: &[&str] = &[
+    // 1 - Initial schema. IF NOT EXISTS because files made before versioning already have these.
+    "
+    CREATE TABLE IF NOT EXISTS categories ( display_name TEXT NOT NULL, balance INTEGER NOT NULL, id INTEGER primary key autoincrement );
+    CREATE TABLE IF NOT EXISTS accounts ( display_name TEXT NOT NULL );
+    CREATE TABLE IF NOT EXISTS transactions ( payee TEXT NOT NULL, amount INTEGER NOT NULL, date INTEGER NOT NULL, notes TEXT NOT NULL, account_id INTEGER NOT NULL, category_id INTEGER NOT NULL );
+    CREATE TABLE IF NOT EXISTS category_transfer ( source INTEGER NOT NULL, dest INTEGER NOT NULL, amount INTEGER NOT NULL );
+    ",
+    // 2 - Monthly category assignments
+    "
+    CREATE TABLE category_assignments ( category_id INTEGER NOT NULL, month INTEGER NOT NULL, amount INTEGER NOT NULL, UNIQUE(category_id, month) );
+    ",
+    // 3 - Budget month for category transfers
+    "
+    ALTER TABLE category_transfer ADD COLUMN month INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 4 - CSV import profiles, starting with the layout import always used
+    "
+    CREATE TABLE import_profiles ( display_name TEXT NOT NULL, delimiter TEXT NOT NULL, skip_rows INTEGER NOT NULL, date_column INTEGER NOT NULL, date_format TEXT NOT NULL, payee_column INTEGER NOT NULL, amount_format INTEGER NOT NULL, amount_column INTEGER NOT NULL, negate_amounts INTEGER NOT NULL, outflow_column INTEGER NOT NULL, inflow_column INTEGER NOT NULL, category_column INTEGER, notes_column INTEGER );
+    INSERT INTO import_profiles VALUES ( 'LastFinance CSV', ',', 1, 1, '%Y-%m-%d', 2, 1, 0, 0, 3, 4, 5, NULL );
+    ",
+    // 5 - Import batches, so an import can be undone
+    "
+    CREATE TABLE import_batches ( account_id INTEGER NOT NULL, date INTEGER NOT NULL, file_name TEXT NOT NULL, transaction_count INTEGER NOT NULL );
+    ALTER TABLE transactions ADD COLUMN import_batch_id INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 6 - Bank transaction ids from OFX statements
+    "
+    ALTER TABLE transactions ADD COLUMN fitid TEXT NOT NULL DEFAULT '';
+    CREATE INDEX transactions_fitid ON transactions ( account_id, fitid );
+    ",
+    // 7 - Split transactions. Lines go when their transaction does.
+    "
+    CREATE TABLE transaction_splits ( transaction_id INTEGER NOT NULL, category_id INTEGER NOT NULL, amount INTEGER NOT NULL, notes TEXT NOT NULL );
+    CREATE INDEX transaction_splits_transaction ON transaction_splits ( transaction_id );
+    CREATE TRIGGER transaction_splits_delete AFTER DELETE ON transactions BEGIN DELETE FROM transaction_splits WHERE transaction_id = old.rowid; END;
+    ",
+    // 8 - Transfers between accounts, as two transactions pointing at each other
+    "
+    ALTER TABLE transactions ADD COLUMN transfer_id INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 9 - Recurring transactions
+    "
+    CREATE TABLE scheduled_transactions ( payee TEXT NOT NULL, notes TEXT NOT NULL, account_id INTEGER NOT NULL, category_id INTEGER NOT NULL, amount INTEGER NOT NULL, frequency INTEGER NOT NULL, start_date INTEGER NOT NULL, end_date INTEGER, occurrence_count INTEGER NOT NULL );
+    ",
+    // 10 - Payee rules
+    "
+    CREATE TABLE payee_rules ( match_kind INTEGER NOT NULL, pattern TEXT NOT NULL, account_id INTEGER NOT NULL, min_amount INTEGER, max_amount INTEGER, category_id INTEGER NOT NULL, rename_payee TEXT NOT NULL, notes TEXT NOT NULL );
+    ",
+    // 11 - Cleared and reconciled states
+    "
+    ALTER TABLE transactions ADD COLUMN cleared INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 12 - Account types, tracking and closed accounts
+    "
+    ALTER TABLE accounts ADD COLUMN account_type INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE accounts ADD COLUMN on_budget INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE accounts ADD COLUMN closed INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 13 - Credit card payment categories
+    "
+    ALTER TABLE categories ADD COLUMN payment_account_id INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 14 - Category groups and category order
+    "
+    CREATE TABLE category_groups ( display_name TEXT NOT NULL, sort_order INTEGER NOT NULL, collapsed INTEGER NOT NULL );
+    ALTER TABLE categories ADD COLUMN group_id INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE categories ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+    ",
+    // 15 - Category targets, one per category
+    "
+    CREATE TABLE category_targets ( category_id INTEGER NOT NULL UNIQUE, kind INTEGER NOT NULL, amount INTEGER NOT NULL, target_month INTEGER );
+    ",
+    // 16 - Indexes for filtering and sorting transactions
+    "
+    CREATE INDEX transactions_date ON transactions ( date );
+    CREATE INDEX transactions_account_date ON transactions ( account_id, date );
+    CREATE INDEX transactions_category ON transactions ( category_id );
+    CREATE INDEX transaction_splits_category ON transaction_splits ( category_id );
+    ",
+];
+
+// The schema version this build of the app writes
+pub fn latest_version() -> i64 {
+    return MIGRATIONS.len() as i64;
+}
+
+pub fn get_version(connection: &Connection) -> Result<i64, RytError> {
+    return connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|_| RytError::Migration);
+}
+
+// Bring the file up to the latest version. All pending steps run in one transaction so a failure
+// leaves the file exactly as it was.
+pub fn run(connection: &mut Connection) -> Result<(), RytError> {
+    return run_to(connection, latest_version());
+}
+
+// Bring the file up to a specific version, for rebuilding files made by older versions
+pub fn run_to(connection: &mut Connection, target_version: i64) -> Result<(), RytError> {
+    let version = get_version(connection)?;
+    if version > latest_version() || target_version > latest_version() {
+        return Err(RytError::DatabaseNewerThanApp);
+    }
+    if version >= target_version {
+        return Ok(());
+    }
+
+    let tx = connection.transaction().map_err(|_| RytError::Migration)?;
+    for (i, step) in MIGRATIONS
+        .iter()
+        .enumerate()
+        .take(target_version as usize)
+        .skip(version as usize)
+    {
+        println!("Running migration {}", i + 1);
+        tx.execute_batch(step).map_err(|_| RytError::Migration)?;
+    }
+    tx.pragma_update(None, "user_version", target_version)
+        .map_err(|_| RytError::Migration)?;
+    tx.commit().map_err(|_| RytError::Migration)?;
+
+    Ok(())
+}
@@ -0,0 +1,386 @@
+use chrono::NaiveDate;
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+use data::import_preview::LedgerBalance;
+use data::transaction::Transaction;
+use data::RytError;
+
+// OFX 1.x is SGML, where elements holding a value usually have no closing tag. OFX 2.x is XML.
+// Both are read the same way, as a flat list of tags where the text after a tag is its value.
+// Closing tags only matter for the aggregates, STMTTRN and LEDGERBAL, which 1.x also closes.
+
+// One STMTTRN, fields as written in the file
+#[derive(Default)]
+pub struct OfxTransaction {
+    // Line of the STMTTRN tag
+    pub line: u64,
+
+    pub fitid: String,
+    pub date_posted: String,
+    pub amount: String,
+    pub name: String,
+    pub memo: String,
+}
+
+#[derive(Default)]
+pub struct OfxStatement {
+    pub transactions: Vec<OfxTransaction>,
+
+    // LEDGERBAL, empty if the statement has none
+    pub ledger_amount: String,
+    pub ledger_date: String,
+}
+
+fn decode_entities(value: &str) -> String {
+    return value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+}
+
+// Text of an OFX file. 1.x headers give a CHARSET, usually 1252 for windows-1252, and 2.x has an
+// xml encoding. Files which say nothing and aren't utf8 are read as windows-1252.
+pub fn decode(bytes: &[u8]) -> String {
+    let lossy = String::from_utf8_lossy(bytes);
+    let header = &lossy[..lossy.find("<OFX>").unwrap_or(lossy.len())];
+
+    let mut label = String::new();
+    for line in header.lines() {
+        if let Some(v) = line.trim().strip_prefix("CHARSET:") {
+            label = v.trim().to_string();
+        }
+    }
+    if let Some(pos) = header.find("encoding=\"") {
+        let rest = &header[pos + "encoding=\"".len()..];
+        label = rest[..rest.find('"').unwrap_or(0)].to_string();
+    }
+
+    // Code pages are written as just their number
+    if !label.is_empty() && label.chars().all(|c| c.is_ascii_digit()) {
+        label = format!("windows-{label}");
+    }
+
+    let encoding = Encoding::for_label(label.as_bytes()).unwrap_or(UTF_8);
+    let (text, _, had_errors) = encoding.decode(bytes);
+    if had_errors && encoding == UTF_8 {
+        return WINDOWS_1252.decode(bytes).0.into_owned();
+    }
+    return text.into_owned();
+}
+
+// Only files with a single statement are read, one file is imported into one account
+pub fn parse_statement(text: &str) -> Result<OfxStatement, RytError> {
+    // Everything before is the header, either plain OFXHEADER lines or an xml declaration
+    let start = text.find("<OFX>").ok_or(RytError::ImportOfxInvalid)?;
+
+    let mut statement = OfxStatement::default();
+    let mut current: Option<OfxTransaction> = None;
+    let mut in_ledger = false;
+    let mut statement_count = 0;
+
+    let mut line: u64 = 1 + text[..start].matches('\n').count() as u64;
+    let mut rest = &text[start..];
+    while let Some(open) = rest.find('<') {
+        let close = match rest[open..].find('>') {
+            Some(v) => open + v,
+            None => return Err(RytError::ImportOfxInvalid),
+        };
+        line += rest[..close].matches('\n').count() as u64;
+
+        let tag = rest[open + 1..close].trim().to_uppercase();
+        rest = &rest[close + 1..];
+
+        let value_end = rest.find('<').unwrap_or(rest.len());
+        let value = decode_entities(rest[..value_end].trim());
+
+        match tag.as_str() {
+            "STMTTRN" => {
+                current = Some(OfxTransaction {
+                    line,
+                    ..Default::default()
+                })
+            }
+            "/STMTTRN" => {
+                if let Some(trans) = current.take() {
+                    statement.transactions.push(trans);
+                }
+            }
+            "STMTRS" | "CCSTMTRS" => {
+                statement_count += 1;
+                if statement_count > 1 {
+                    return Err(RytError::ImportOfxInvalid);
+                }
+            }
+            "LEDGERBAL" => in_ledger = true,
+            "/LEDGERBAL" => in_ledger = false,
+            _ => {}
+        }
+
+        if let Some(trans) = &mut current {
+            match tag.as_str() {
+                "FITID" => trans.fitid = value,
+                "DTPOSTED" => trans.date_posted = value,
+                "TRNAMT" => trans.amount = value,
+
+                // Either directly in STMTTRN or inside a PAYEE aggregate
+                "NAME" if trans.name.is_empty() => trans.name = value,
+
+                "MEMO" => trans.memo = value,
+                _ => {}
+            }
+        } else if in_ledger {
+            match tag.as_str() {
+                "BALAMT" => statement.ledger_amount = value,
+                "DTASOF" => statement.ledger_date = value,
+                _ => {}
+            }
+        }
+    }
+
+    return Ok(statement);
+}
+
+pub fn to_transaction(ofx: &OfxTransaction, account_id: i64) -> Result<Transaction, RytError> {
+    if ofx.fitid.is_empty() || ofx.date_posted.is_empty() || ofx.amount.is_empty() {
+        return Err(RytError::ImportOfxMissingField);
+    }
+
+    let amount = parse_amount(&ofx.amount)?;
+    if amount == 0 {
+        return Err(RytError::ImportNoAmount);
+    }
+
+    // Some banks only fill in the memo
+    let payee = if ofx.name.is_empty() {
+        ofx.memo.clone()
+    } else {
+        ofx.name.clone()
+    };
+
+    let mut trans = Transaction::new_raw(payee, amount, parse_date(&ofx.date_posted)?, account_id);
+    if !ofx.name.is_empty() {
+        trans.notes = ofx.memo.clone();
+    }
+    trans.fitid = ofx.fitid.clone();
+
+    return Ok(trans);
+}
+
+// None if the statement has no usable balance
+pub fn ledger_balance(statement: &OfxStatement) -> Option<LedgerBalance> {
+    return Some(LedgerBalance {
+        amount: parse_amount(&statement.ledger_amount).ok()?,
+        date: parse_date(&statement.ledger_date).ok()?,
+    });
+}
+
+// The fields of a transaction, for showing the user what went wrong
+pub fn transaction_text(ofx: &OfxTransaction) -> String {
+    return [&ofx.date_posted, &ofx.amount, &ofx.name, &ofx.memo]
+        .iter()
+        .filter(|v| !v.is_empty())
+        .map(|v| v.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ");
+}
+
+// OFX dates are YYYYMMDD followed by an optional time and timezone.
+// Unix timestamp of midnight UTC on the date.
+fn parse_date(value: &str) -> Result<i64, RytError> {
+    let day = value.get(..8).ok_or(RytError::ImportParseDate)?;
+    let date = NaiveDate::parse_from_str(day, "%Y%m%d").map_err(|_| RytError::ImportParseDate)?;
+    return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp());
+}
+
+// Dollars to cents. Some banks use a comma for the decimal point. A comma is only
+// taken as one when one or two digits follow it, otherwise commas separate thousands.
+fn parse_amount(value: &str) -> Result<i64, RytError> {
+    let decimal_comma = match value.rsplit_once(',') {
+        Some((_, cents)) => {
+            (1..=2).contains(&cents.len()) && cents.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    };
+    let normalized = match decimal_comma {
+        true => value.replace('.', "").replace(',', "."),
+        false => value.replace(',', ""),
+    };
+    let dollars = normalized
+        .parse::<f64>()
+        .map_err(|_| RytError::ImportParseAmount)?;
+    return Ok(data::dollars_to_cents(dollars));
+}
+
+#[test]
+fn parse_sgml() {
+    let text = "OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20240105120000.000[-5:EST]
+<TRNAMT>-42.10
+<FITID>2024010501
+<NAME>Smith &amp; Sons
+<MEMO>POS PURCHASE
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20240106
+<TRNAMT>1500,00
+<FITID>2024010602
+<MEMO>PAYROLL
+</STMTTRN>
+</BANKTRANLIST>
+<LEDGERBAL><BALAMT>2500.55<DTASOF>20240131</LEDGERBAL>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+    let statement = parse_statement(text).unwrap();
+    assert_eq!(statement.transactions.len(), 2);
+    assert_eq!(statement.transactions[0].line, 8);
+    assert_eq!(statement.transactions[1].line, 16);
+
+    let trans = to_transaction(&statement.transactions[0], 4).unwrap();
+    assert_eq!(trans.payee, "Smith & Sons");
+    assert_eq!(trans.notes, "POS PURCHASE");
+    assert_eq!(trans.amount, -4210);
+    assert_eq!(trans.date, 1704412800);
+    assert_eq!(trans.fitid, "2024010501");
+    assert_eq!(trans.account_id, 4);
+
+    let trans = to_transaction(&statement.transactions[1], 4).unwrap();
+    assert_eq!(trans.payee, "PAYROLL");
+    assert_eq!(trans.notes, "");
+    assert_eq!(trans.amount, 150000);
+
+    assert_eq!(
+        ledger_balance(&statement),
+        Some(LedgerBalance {
+            amount: 250055,
+            date: 1706659200,
+        })
+    );
+}
+
+#[test]
+fn parse_ofx_amounts() {
+    assert_eq!(parse_amount("-42.10"), Ok(-4210));
+    assert_eq!(parse_amount("1500,00"), Ok(150000));
+    assert_eq!(parse_amount("1,234.56"), Ok(123456));
+    assert_eq!(parse_amount("-1,234,567.89"), Ok(-123456789));
+    assert_eq!(parse_amount("1,234"), Ok(123400));
+    assert_eq!(parse_amount("1,234,567"), Ok(123456700));
+    assert_eq!(parse_amount("1.234,56"), Ok(123456));
+    assert_eq!(parse_amount("-4,5"), Ok(-450));
+}
+
+#[test]
+fn parse_xml() {
+    let text = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE"?>
+<OFX>
+  <CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS>
+    <BANKTRANLIST>
+      <STMTTRN>
+        <TRNTYPE>DEBIT</TRNTYPE>
+        <DTPOSTED>20240110</DTPOSTED>
+        <TRNAMT>-9.99</TRNAMT>
+        <FITID>abc-1</FITID>
+        <PAYEE><NAME>Streaming Co</NAME><ADDR1>1 Main St</ADDR1></PAYEE>
+      </STMTTRN>
+    </BANKTRANLIST>
+  </CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1>
+</OFX>"#;
+
+    let statement = parse_statement(text).unwrap();
+    assert_eq!(statement.transactions.len(), 1);
+
+    let trans = to_transaction(&statement.transactions[0], 1).unwrap();
+    assert_eq!(trans.payee, "Streaming Co");
+    assert_eq!(trans.amount, -999);
+    assert_eq!(trans.fitid, "abc-1");
+
+    assert_eq!(ledger_balance(&statement), None);
+}
+
+#[test]
+fn parse_ofx_errors() {
+    assert!(matches!(
+        parse_statement("date,payee,amount\n"),
+        Err(RytError::ImportOfxInvalid)
+    ));
+
+    let text = "<OFX>
+<STMTTRN><DTPOSTED>20240110<TRNAMT>-1.00<NAME>No id</STMTTRN>
+<STMTTRN><DTPOSTED>January<TRNAMT>-1.00<FITID>1</STMTTRN>
+<STMTTRN><DTPOSTED>20240110<TRNAMT>abc<FITID>2</STMTTRN>
+<STMTTRN><DTPOSTED>20240110<TRNAMT>0.00<FITID>3</STMTTRN>
+</OFX>";
+
+    let statement = parse_statement(text).unwrap();
+    let errors: Vec<RytError> = statement
+        .transactions
+        .iter()
+        .map(|t| to_transaction(t, 0).unwrap_err())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            RytError::ImportOfxMissingField,
+            RytError::ImportParseDate,
+            RytError::ImportParseAmount,
+            RytError::ImportNoAmount,
+        ]
+    );
+    assert_eq!(
+        transaction_text(&statement.transactions[0]),
+        "20240110 -1.00 No id"
+    );
+}
+
+#[test]
+fn parse_ofx_several_statements() {
+    let text = "<OFX><BANKMSGSRSV1>
+<STMTTRNRS><STMTRS><BANKACCTFROM><ACCTID>1</BANKACCTFROM>
+<STMTTRN><DTPOSTED>20240110<TRNAMT>-1.00<FITID>1</STMTTRN>
+</STMTRS></STMTTRNRS>
+<STMTTRNRS><STMTRS><BANKACCTFROM><ACCTID>2</BANKACCTFROM>
+<STMTTRN><DTPOSTED>20240110<TRNAMT>-2.00<FITID>2</STMTTRN>
+</STMTRS></STMTTRNRS>
+</BANKMSGSRSV1></OFX>";
+
+    assert!(matches!(
+        parse_statement(text),
+        Err(RytError::ImportOfxInvalid)
+    ));
+}
+
+#[test]
+fn decode_charset() {
+    let sgml = |charset: &str, payee: &[u8]| -> Vec<u8> {
+        let mut bytes = format!("OFXHEADER:100\nCHARSET:{charset}\n\n<OFX><NAME>").into_bytes();
+        bytes.extend_from_slice(payee);
+        bytes.extend_from_slice(b"</OFX>");
+        return bytes;
+    };
+
+    // Café René’s in windows-1252
+    let cafe = b"Caf\xe9 Ren\xe9\x92s";
+    assert!(decode(&sgml("1252", cafe)).contains("Caf\u{e9} Ren\u{e9}\u{2019}s"));
+    assert!(decode(&sgml("ISO-8859-1", b"Caf\xe9")).contains("Caf\u{e9}"));
+
+    // No usable charset, not utf8 either
+    assert!(decode(&sgml("NONE", cafe)).contains("Caf\u{e9} Ren\u{e9}\u{2019}s"));
+
+    let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<OFX><NAME>Caf\u{e9}</OFX>";
+    assert!(decode(xml.as_bytes()).contains("Caf\u{e9}"));
+}
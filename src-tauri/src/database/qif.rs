@@ -0,0 +1,428 @@
+use chrono::{DateTime, NaiveDate};
+
+use data::account::AccountType;
+use data::RytError;
+
+// Quicken Interchange Format. Records are lines starting with a one letter field code and end
+// with a ^ line. Lines starting with ! switch what kind of records follow.
+
+// One line of a split transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QifSplit {
+    pub category: String,
+    pub memo: String,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QifTransaction {
+    // Line the record starts on
+    pub line: u64,
+
+    // unix timestamp
+    pub date: i64,
+
+    pub amount: i64,
+    pub payee: String,
+    pub memo: String,
+
+    // As written, see category_name
+    pub category: String,
+
+    pub splits: Vec<QifSplit>,
+}
+
+// Transactions from one !Type section
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QifAccount {
+    // From the !Account record before the section. Empty when the file doesn't name accounts.
+    pub name: String,
+
+    pub transactions: Vec<QifTransaction>,
+}
+
+// Sections holding bank style transactions. Investment, memorized and list sections are skipped.
+const TRANSACTION_TYPES: &[&str] = &["bank", "cash", "ccard", "oth a", "oth l"];
+
+enum Section {
+    Accounts,
+    Transactions,
+    Skip,
+}
+
+pub fn parse(text: &str) -> Result<Vec<QifAccount>, RytError> {
+    let mut accounts: Vec<QifAccount> = vec![];
+
+    let mut section = Section::Skip;
+    let mut account_name = String::new();
+    let mut record_name = String::new();
+    let mut current: Option<QifTransaction> = None;
+
+    for (i, raw_line) in text.lines().enumerate() {
+        let line = raw_line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.starts_with('!') {
+            let header = line.to_lowercase();
+            if header == "!account" {
+                section = Section::Accounts;
+            } else if let Some(kind) = header.strip_prefix("!type:") {
+                if TRANSACTION_TYPES.contains(&kind.trim()) {
+                    section = Section::Transactions;
+                    accounts.push(QifAccount {
+                        name: account_name.clone(),
+                        transactions: vec![],
+                    });
+                } else {
+                    section = Section::Skip;
+                }
+            }
+
+            // !Option and !Clear lines don't change the section
+            continue;
+        }
+
+        let (code, value) = line.split_at(line.chars().next().unwrap().len_utf8());
+        let value = value.trim();
+
+        match section {
+            Section::Skip => {}
+            Section::Accounts => match code {
+                "N" => record_name = value.to_string(),
+                "^" => account_name = std::mem::take(&mut record_name),
+                _ => {}
+            },
+            Section::Transactions => {
+                let trans = current.get_or_insert_with(|| QifTransaction {
+                    line: i as u64 + 1,
+                    date: i64::MIN,
+                    amount: i64::MIN,
+                    ..Default::default()
+                });
+
+                match code {
+                    "D" => trans.date = parse_date(value)?,
+                    "T" | "U" => trans.amount = parse_amount(value)?,
+                    "P" => trans.payee = value.to_string(),
+                    "M" => trans.memo = value.to_string(),
+                    "L" => trans.category = value.to_string(),
+                    "S" => trans.splits.push(QifSplit {
+                        category: value.to_string(),
+                        memo: String::new(),
+                        amount: 0,
+                    }),
+                    "E" => {
+                        if let Some(split) = trans.splits.last_mut() {
+                            split.memo = value.to_string();
+                        }
+                    }
+                    "$" => {
+                        if let Some(split) = trans.splits.last_mut() {
+                            split.amount = parse_amount(value)?;
+                        }
+                    }
+                    "^" => {
+                        let trans = current.take().unwrap();
+                        if trans.date == i64::MIN || trans.amount == i64::MIN {
+                            return Err(RytError::ImportQifMissingField);
+                        }
+                        accounts.last_mut().unwrap().transactions.push(trans);
+                    }
+
+                    // Check numbers, cleared status, addresses
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if accounts.is_empty() {
+        return Err(RytError::ImportQifInvalid);
+    }
+
+    return Ok(accounts);
+}
+
+// Category name to use from a QIF category. Subcategories keep their parent, Food:Groceries.
+// A /class suffix is dropped. Transfers to other accounts, written [Account], have no category.
+pub fn category_name(qif_category: &str) -> String {
+    let name = match qif_category.split_once('/') {
+        Some((v, _)) => v,
+        None => qif_category,
+    };
+
+    if name.starts_with('[') {
+        return String::new();
+    }
+
+    return name.trim().to_string();
+}
+
+// US month first dates. Quicken writes years after 1999 with an apostrophe, 1/15'24.
+fn parse_date(value: &str) -> Result<i64, RytError> {
+    let apostrophe_year = value.contains('\'');
+
+    let parts: Vec<&str> = value
+        .split(['/', '-', '\'', '.'])
+        .map(|v| v.trim())
+        .collect();
+    if parts.len() != 3 {
+        return Err(RytError::ImportParseDate);
+    }
+
+    let mut numbers: Vec<i64> = vec![];
+    for part in &parts {
+        numbers.push(part.parse::<i64>().map_err(|_| RytError::ImportParseDate)?);
+    }
+
+    let mut year = numbers[2];
+    if parts[2].len() <= 2 {
+        year += if apostrophe_year { 2000 } else { 1900 };
+    }
+
+    let date = NaiveDate::from_ymd_opt(year as i32, numbers[0] as u32, numbers[1] as u32)
+        .ok_or(RytError::ImportParseDate)?;
+    return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp());
+}
+
+fn parse_amount(value: &str) -> Result<i64, RytError> {
+    let dollars = value
+        .replace(',', "")
+        .parse::<f64>()
+        .map_err(|_| RytError::ImportParseAmount)?;
+    return Ok(data::dollars_to_cents(dollars));
+}
+
+fn format_date(unix_date: i64) -> String {
+    return match DateTime::from_timestamp(unix_date, 0) {
+        Some(v) => v.format("%m/%d/%Y").to_string(),
+        None => String::new(),
+    };
+}
+
+// One transaction to write. category is as written after L, Group:Category or [Account] for
+// transfers, empty when uncategorized. Split lines are written in place of the category.
+pub struct QifExportRow {
+    pub date: i64,
+    pub amount: i64,
+    pub payee: String,
+    pub notes: String,
+    pub category: String,
+    pub splits: Vec<QifSplit>,
+}
+
+// Account type as written after T in an !Account record and after !Type:. Investments are
+// written as other assets, Invst sections hold trades rather than plain transactions.
+fn qif_type(account_type: AccountType) -> &'static str {
+    match account_type {
+        AccountType::Checking | AccountType::Savings => "Bank",
+        AccountType::Cash => "Cash",
+        AccountType::CreditCard => "CCard",
+        AccountType::Investment => "Oth A",
+        AccountType::Loan => "Oth L",
+    }
+}
+
+// Append an !Account record and the transactions for it
+pub fn write_account(
+    out: &mut String,
+    account_name: &str,
+    account_type: AccountType,
+    rows: &[QifExportRow],
+) {
+    let kind = qif_type(account_type);
+    out.push_str("!Account\n");
+    out.push_str(&format!("N{}\n", account_name));
+    out.push_str(&format!("T{}\n^\n", kind));
+
+    out.push_str(&format!("!Type:{}\n", kind));
+    for row in rows {
+        out.push_str(&format!("D{}\n", format_date(row.date)));
+        out.push_str(&format!("T{}\n", data::amount_to_plain(row.amount)));
+        out.push_str(&format!("P{}\n", row.payee));
+        if !row.notes.is_empty() {
+            out.push_str(&format!("M{}\n", row.notes));
+        }
+        if !row.category.is_empty() && row.splits.is_empty() {
+            out.push_str(&format!("L{}\n", row.category));
+        }
+        for split in &row.splits {
+            out.push_str(&format!("S{}\n", split.category));
+            if !split.memo.is_empty() {
+                out.push_str(&format!("E{}\n", split.memo));
+            }
+            out.push_str(&format!("${}\n", data::amount_to_plain(split.amount)));
+        }
+        out.push_str("^\n");
+    }
+}
+
+#[test]
+fn qif_dates() {
+    assert_eq!(parse_date("01/15/2024"), Ok(1705276800));
+    assert_eq!(parse_date("1/15'24"), Ok(1705276800));
+    assert_eq!(parse_date(" 1/15' 24"), Ok(1705276800));
+    assert_eq!(parse_date("1-15-2024"), Ok(1705276800));
+    assert_eq!(parse_date("12/31/98"), Ok(915062400));
+    assert_eq!(parse_date("13/01/2024"), Err(RytError::ImportParseDate));
+    assert_eq!(parse_date("yesterday"), Err(RytError::ImportParseDate));
+}
+
+#[test]
+fn qif_categories() {
+    assert_eq!(category_name("Food:Groceries"), "Food:Groceries");
+    assert_eq!(category_name("Food/Vacation"), "Food");
+    assert_eq!(category_name("[Savings]"), "");
+    assert_eq!(category_name(""), "");
+}
+
+#[test]
+fn qif_parse() {
+    let text = "!Type:Cat
+NFood
+^
+!Account
+NChecking
+TBank
+^
+!Type:Bank
+D1/15'24
+T-1,042.10
+PCostco
+MMonthly trip
+LHousehold
+SFood:Groceries
+EProduce
+$-42.10
+SHousehold
+$-1000.00
+^
+D1/16'24
+U500.00
+PPaycheck
+^
+!Type:Invst
+D1/17'24
+NBuy
+^
+";
+
+    let accounts = parse(text).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].name, "Checking");
+    assert_eq!(accounts[0].transactions.len(), 2);
+
+    let costco = &accounts[0].transactions[0];
+    assert_eq!(costco.line, 9);
+    assert_eq!(costco.amount, -104210);
+    assert_eq!(costco.payee, "Costco");
+    assert_eq!(costco.memo, "Monthly trip");
+    assert_eq!(
+        costco.splits,
+        vec![
+            QifSplit {
+                category: "Food:Groceries".to_string(),
+                memo: "Produce".to_string(),
+                amount: -4210,
+            },
+            QifSplit {
+                category: "Household".to_string(),
+                memo: String::new(),
+                amount: -100000,
+            },
+        ]
+    );
+
+    assert_eq!(accounts[0].transactions[1].amount, 50000);
+
+    assert_eq!(
+        parse("!Type:Bank\nPNo date\nT1.00\n^\n"),
+        Err(RytError::ImportQifMissingField)
+    );
+    assert_eq!(parse("payee,amount\n"), Err(RytError::ImportQifInvalid));
+}
+
+#[test]
+fn qif_write() {
+    let mut out = String::new();
+    write_account(
+        &mut out,
+        "Checking",
+        AccountType::Checking,
+        &[QifExportRow {
+            date: 1705276800,
+            amount: -5,
+            payee: "Arbys".to_string(),
+            notes: String::new(),
+            category: "Food".to_string(),
+            splits: vec![],
+        }],
+    );
+
+    assert_eq!(
+        out,
+        "!Account\nNChecking\nTBank\n^\n!Type:Bank\nD01/15/2024\nT-0.05\nPArbys\nLFood\n^\n"
+    );
+    assert_eq!(parse(&out).unwrap()[0].transactions[0].amount, -5);
+
+    let mut out = String::new();
+    let splits = vec![
+        QifSplit {
+            category: "Food".to_string(),
+            memo: "Produce".to_string(),
+            amount: -300,
+        },
+        QifSplit {
+            category: "Household".to_string(),
+            memo: String::new(),
+            amount: -200,
+        },
+    ];
+    write_account(
+        &mut out,
+        "Checking",
+        AccountType::Checking,
+        &[QifExportRow {
+            date: 1705276800,
+            amount: -500,
+            payee: "Costco".to_string(),
+            notes: String::new(),
+            category: String::new(),
+            splits: splits.clone(),
+        }],
+    );
+    assert_eq!(parse(&out).unwrap()[0].transactions[0].splits, splits);
+}
+
+#[test]
+fn qif_write_credit_card() {
+    let mut out = String::new();
+    write_account(
+        &mut out,
+        "Visa",
+        AccountType::CreditCard,
+        &[QifExportRow {
+            date: 1705276800,
+            amount: -4599,
+            payee: "Streaming Co".to_string(),
+            notes: String::new(),
+            category: "Entertainment".to_string(),
+            splits: vec![],
+        }],
+    );
+
+    assert_eq!(
+        out,
+        "!Account\nNVisa\nTCCard\n^\n!Type:CCard\nD01/15/2024\nT-45.99\nPStreaming Co\nLEntertainment\n^\n"
+    );
+    let accounts = parse(&out).unwrap();
+    assert_eq!(accounts[0].name, "Visa");
+    assert_eq!(accounts[0].transactions[0].amount, -4599);
+
+    // Loans and investments are still plain transactions
+    assert_eq!(qif_type(AccountType::Loan), "Oth L");
+    assert_eq!(qif_type(AccountType::Investment), "Oth A");
+    assert_eq!(qif_type(AccountType::Cash), "Cash");
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Days, Months};
+
+use data::scheduled_transaction::{Frequency, ScheduledTransaction};
+
+// Date of occurrence n, counting the start date as 0. None once past the end date, or for dates
+// chrono can't represent.
+pub fn occurrence_date(scheduled: &ScheduledTransaction, n: i64) -> Option<i64> {
+    let start = DateTime::from_timestamp(scheduled.start_date, 0)?;
+    let n = u32::try_from(n).ok()?;
+
+    // Months are always counted from the start, so the 31st stays the 31st after a short month
+    let date = match scheduled.frequency {
+        Frequency::Daily => start.checked_add_days(Days::new(n as u64))?,
+        Frequency::Weekly => start.checked_add_days(Days::new(7 * n as u64))?,
+        Frequency::Biweekly => start.checked_add_days(Days::new(14 * n as u64))?,
+        Frequency::Monthly => start.checked_add_months(Months::new(n))?,
+        Frequency::Yearly => start.checked_add_months(Months::new(n.checked_mul(12)?))?,
+    };
+
+    let date = date.timestamp();
+    if let Some(end_date) = scheduled.end_date {
+        if date > end_date {
+            return None;
+        }
+    }
+
+    return Some(date);
+}
+
+// Dates of the occurrences not yet made into transactions, up to and including until
+pub fn pending_dates(scheduled: &ScheduledTransaction, until: i64) -> Vec<i64> {
+    let mut ret: Vec<i64> = vec![];
+
+    let mut n = scheduled.occurrence_count;
+    while let Some(date) = occurrence_date(scheduled, n) {
+        if date > until {
+            break;
+        }
+
+        ret.push(date);
+        n += 1;
+    }
+
+    return ret;
+}
+
+#[cfg(test)]
+fn test_schedule(frequency: Frequency, start_date: i64) -> ScheduledTransaction {
+    return ScheduledTransaction::new("Rent".to_string(), -100_000, 1, frequency, start_date);
+}
+
+#[test]
+fn schedule_occurrences() {
+    // 2024-01-31
+    let start = 1706659200;
+    let day = 24 * 60 * 60;
+
+    let daily = test_schedule(Frequency::Daily, start);
+    assert_eq!(occurrence_date(&daily, 0), Some(start));
+    assert_eq!(occurrence_date(&daily, 3), Some(start + 3 * day));
+
+    let biweekly = test_schedule(Frequency::Biweekly, start);
+    assert_eq!(occurrence_date(&biweekly, 2), Some(start + 28 * day));
+
+    // 2024-02-29, 2024-03-31, 2024-04-30
+    let monthly = test_schedule(Frequency::Monthly, start);
+    assert_eq!(occurrence_date(&monthly, 1), Some(1709164800));
+    assert_eq!(occurrence_date(&monthly, 2), Some(1711843200));
+    assert_eq!(occurrence_date(&monthly, 3), Some(1714435200));
+
+    // 2024-02-29 to 2025-02-28
+    let yearly = test_schedule(Frequency::Yearly, 1709164800);
+    assert_eq!(occurrence_date(&yearly, 1), Some(1740700800));
+
+    assert_eq!(occurrence_date(&daily, -1), None);
+}
+
+#[test]
+fn schedule_pending_dates() {
+    let start = 1706659200;
+    let day = 24 * 60 * 60;
+
+    let mut weekly = test_schedule(Frequency::Weekly, start);
+    assert_eq!(
+        pending_dates(&weekly, start + 14 * day),
+        vec![start, start + 7 * day, start + 14 * day]
+    );
+    assert!(pending_dates(&weekly, start - 1).is_empty());
+
+    // Already made ones are skipped, nothing after the end date
+    weekly.occurrence_count = 1;
+    weekly.end_date = Some(start + 10 * day);
+    assert_eq!(
+        pending_dates(&weekly, start + 100 * day),
+        vec![start + 7 * day]
+    );
+}
@@ -1,16 +1,24 @@
-use rusqlite::Row;
+use rusqlite::{Row, ToSql};
 
 use data::account::Account;
 use data::category::*;
+use data::category_assignment::CategoryAssignment;
+use data::category_group::CategoryGroup;
+use data::category_target::CategoryTarget;
 use data::category_transfer::CategoryTransfer;
+use data::import_batch::ImportBatch;
+use data::import_profile::ImportProfile;
+use data::payee_rule::PayeeRule;
+use data::scheduled_transaction::ScheduledTransaction;
 use data::transaction::Transaction;
+use data::transaction_split::TransactionSplit;
 
 pub trait TableActions {
     fn get_table_name() -> String;
-    fn get_table_schema() -> String;
     fn get_insert_schema() -> String;
     fn get_fetch_schema() -> String;
-    fn to_insert_data(&self) -> String;
+    // Values bound to the columns in get_insert_schema, in the same order
+    fn to_insert_params(&self) -> Vec<&dyn ToSql>;
     fn row_to_data(row: &Row) -> Self;
 }
 
@@ -20,6 +28,9 @@ impl TableActions for super::Category {
             display_name: row.get(0).unwrap(),
             balance: row.get(1).unwrap(),
             id: row.get(2).unwrap(),
+            payment_account_id: row.get(3).unwrap(),
+            group_id: row.get(4).unwrap(),
+            sort_order: row.get(5).unwrap(),
         }
     }
 
@@ -27,21 +38,49 @@ impl TableActions for super::Category {
         return "categories".to_string();
     }
 
-    fn get_table_schema() -> String {
-        return "display_name TEXT NOT NULL, balance INTEGER NOT NULL, id INTEGER primary key autoincrement"
-            .to_string();
+    fn get_insert_schema() -> String {
+        return "display_name, balance, payment_account_id, group_id, sort_order".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "display_name, balance, id, payment_account_id, group_id, sort_order".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.display_name,
+            &self.balance,
+            &self.payment_account_id,
+            &self.group_id,
+            &self.sort_order,
+        ];
+    }
+}
+
+impl TableActions for CategoryGroup {
+    fn row_to_data(row: &Row) -> Self {
+        CategoryGroup {
+            display_name: row.get(0).unwrap(),
+            sort_order: row.get(1).unwrap(),
+            collapsed: row.get(2).unwrap(),
+            id: row.get(3).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "category_groups".to_string();
     }
 
     fn get_insert_schema() -> String {
-        return "display_name, balance".to_string();
+        return "display_name, sort_order, collapsed".to_string();
     }
 
     fn get_fetch_schema() -> String {
-        return "display_name, balance, id".to_string();
+        return "display_name, sort_order, collapsed, rowid".to_string();
     }
 
-    fn to_insert_data(&self) -> String {
-        return format!("'{}', '{}'", self.display_name, self.balance);
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![&self.display_name, &self.sort_order, &self.collapsed];
     }
 }
 
@@ -50,6 +89,9 @@ impl TableActions for super::Account {
         Account {
             id: row.get(0).unwrap(),
             display_name: row.get(1).unwrap(),
+            account_type: row.get(2).unwrap(),
+            on_budget: row.get(3).unwrap(),
+            closed: row.get(4).unwrap(),
         }
     }
 
@@ -57,20 +99,21 @@ impl TableActions for super::Account {
         return "accounts".to_string();
     }
 
-    fn get_table_schema() -> String {
-        return "display_name TEXT NOT NULL".to_string();
-    }
-
     fn get_insert_schema() -> String {
-        return "display_name".to_string();
+        return "display_name, account_type, on_budget, closed".to_string();
     }
 
     fn get_fetch_schema() -> String {
-        return "rowid, display_name".to_string();
+        return "rowid, display_name, account_type, on_budget, closed".to_string();
     }
 
-    fn to_insert_data(&self) -> String {
-        return format!("'{}'", self.display_name);
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.display_name,
+            &self.account_type,
+            &self.on_budget,
+            &self.closed,
+        ];
     }
 }
 
@@ -79,7 +122,9 @@ impl TableActions for super::CategoryTransfer {
         CategoryTransfer {
             source: row.get(0).unwrap(),
             dest: row.get(1).unwrap(),
-            amount: row.get(1).unwrap(),
+            amount: row.get(2).unwrap(),
+            month: row.get(3).unwrap(),
+            id: row.get(4).unwrap(),
         }
     }
 
@@ -87,21 +132,93 @@ impl TableActions for super::CategoryTransfer {
         return "category_transfer".to_string();
     }
 
-    fn get_table_schema() -> String {
-        return "source INTEGER NOT NULL, dest INTEGER NOT NULL, amount INTEGER NOT NULL"
-            .to_string();
+    fn get_insert_schema() -> String {
+        return "source, dest, amount, month".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "source, dest, amount, month, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![&self.source, &self.dest, &self.amount, &self.month];
+    }
+}
+
+impl TableActions for CategoryAssignment {
+    fn row_to_data(row: &Row) -> Self {
+        CategoryAssignment {
+            category_id: row.get(0).unwrap(),
+            month: row.get(1).unwrap(),
+            amount: row.get(2).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "category_assignments".to_string();
     }
 
     fn get_insert_schema() -> String {
-        return "source, dest, amount".to_string();
+        return "category_id, month, amount".to_string();
     }
 
     fn get_fetch_schema() -> String {
-        return "source, dest, amount".to_string();
+        return "category_id, month, amount".to_string();
     }
 
-    fn to_insert_data(&self) -> String {
-        return format!("'{}', '{}', '{}'", self.source, self.dest, self.amount);
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![&self.category_id, &self.month, &self.amount];
+    }
+}
+
+impl TableActions for ImportProfile {
+    fn row_to_data(row: &Row) -> Self {
+        ImportProfile {
+            display_name: row.get(0).unwrap(),
+            delimiter: row.get(1).unwrap(),
+            skip_rows: row.get(2).unwrap(),
+            date_column: row.get(3).unwrap(),
+            date_format: row.get(4).unwrap(),
+            payee_column: row.get(5).unwrap(),
+            amount_format: row.get(6).unwrap(),
+            amount_column: row.get(7).unwrap(),
+            negate_amounts: row.get(8).unwrap(),
+            outflow_column: row.get(9).unwrap(),
+            inflow_column: row.get(10).unwrap(),
+            category_column: row.get(11).unwrap(),
+            notes_column: row.get(12).unwrap(),
+            id: row.get(13).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "import_profiles".to_string();
+    }
+
+    fn get_insert_schema() -> String {
+        return "display_name, delimiter, skip_rows, date_column, date_format, payee_column, amount_format, amount_column, negate_amounts, outflow_column, inflow_column, category_column, notes_column".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "display_name, delimiter, skip_rows, date_column, date_format, payee_column, amount_format, amount_column, negate_amounts, outflow_column, inflow_column, category_column, notes_column, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.display_name,
+            &self.delimiter,
+            &self.skip_rows,
+            &self.date_column,
+            &self.date_format,
+            &self.payee_column,
+            &self.amount_format,
+            &self.amount_column,
+            &self.negate_amounts,
+            &self.outflow_column,
+            &self.inflow_column,
+            &self.category_column,
+            &self.notes_column,
+        ];
     }
 }
 
@@ -114,6 +231,11 @@ impl TableActions for Transaction {
             notes: row.get(3).unwrap(),
             account_id: row.get(4).unwrap(),
             category_id: row.get(5).unwrap(),
+            import_batch_id: row.get(6).unwrap(),
+            fitid: row.get(7).unwrap(),
+            transfer_id: row.get(8).unwrap(),
+            cleared: row.get(9).unwrap(),
+            id: row.get(10).unwrap(),
         }
     }
 
@@ -121,22 +243,211 @@ impl TableActions for Transaction {
         return "transactions".to_string();
     }
 
-    fn get_table_schema() -> String {
-        return "payee TEXT NOT NULL, amount INTEGER NOT NULL, date INTEGER NOT NULL, notes TEXT NOT NULL, account_id INTEGER NOT NULL, category_id INTEGER NOT NULL".to_string();
+    fn get_insert_schema() -> String {
+        return "payee, amount, date, notes, account_id, category_id, import_batch_id, fitid, transfer_id, cleared"
+            .to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "payee, amount, date, notes, account_id, category_id, import_batch_id, fitid, transfer_id, cleared, rowid"
+            .to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.payee,
+            &self.amount,
+            &self.date,
+            &self.notes,
+            &self.account_id,
+            &self.category_id,
+            &self.import_batch_id,
+            &self.fitid,
+            &self.transfer_id,
+            &self.cleared,
+        ];
+    }
+}
+
+impl TableActions for ImportBatch {
+    fn row_to_data(row: &Row) -> Self {
+        ImportBatch {
+            account_id: row.get(0).unwrap(),
+            date: row.get(1).unwrap(),
+            file_name: row.get(2).unwrap(),
+            transaction_count: row.get(3).unwrap(),
+            id: row.get(4).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "import_batches".to_string();
+    }
+
+    fn get_insert_schema() -> String {
+        return "account_id, date, file_name, transaction_count".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "account_id, date, file_name, transaction_count, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.account_id,
+            &self.date,
+            &self.file_name,
+            &self.transaction_count,
+        ];
+    }
+}
+
+impl TableActions for TransactionSplit {
+    fn row_to_data(row: &Row) -> Self {
+        TransactionSplit {
+            transaction_id: row.get(0).unwrap(),
+            category_id: row.get(1).unwrap(),
+            amount: row.get(2).unwrap(),
+            notes: row.get(3).unwrap(),
+            id: row.get(4).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "transaction_splits".to_string();
+    }
+
+    fn get_insert_schema() -> String {
+        return "transaction_id, category_id, amount, notes".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "transaction_id, category_id, amount, notes, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.transaction_id,
+            &self.category_id,
+            &self.amount,
+            &self.notes,
+        ];
+    }
+}
+
+impl TableActions for ScheduledTransaction {
+    fn row_to_data(row: &Row) -> Self {
+        ScheduledTransaction {
+            payee: row.get(0).unwrap(),
+            notes: row.get(1).unwrap(),
+            account_id: row.get(2).unwrap(),
+            category_id: row.get(3).unwrap(),
+            amount: row.get(4).unwrap(),
+            frequency: row.get(5).unwrap(),
+            start_date: row.get(6).unwrap(),
+            end_date: row.get(7).unwrap(),
+            occurrence_count: row.get(8).unwrap(),
+            id: row.get(9).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "scheduled_transactions".to_string();
+    }
+
+    fn get_insert_schema() -> String {
+        return "payee, notes, account_id, category_id, amount, frequency, start_date, end_date, occurrence_count".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "payee, notes, account_id, category_id, amount, frequency, start_date, end_date, occurrence_count, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.payee,
+            &self.notes,
+            &self.account_id,
+            &self.category_id,
+            &self.amount,
+            &self.frequency,
+            &self.start_date,
+            &self.end_date,
+            &self.occurrence_count,
+        ];
+    }
+}
+
+impl TableActions for PayeeRule {
+    fn row_to_data(row: &Row) -> Self {
+        PayeeRule {
+            match_kind: row.get(0).unwrap(),
+            pattern: row.get(1).unwrap(),
+            account_id: row.get(2).unwrap(),
+            min_amount: row.get(3).unwrap(),
+            max_amount: row.get(4).unwrap(),
+            category_id: row.get(5).unwrap(),
+            rename_payee: row.get(6).unwrap(),
+            notes: row.get(7).unwrap(),
+            id: row.get(8).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "payee_rules".to_string();
+    }
+
+    fn get_insert_schema() -> String {
+        return "match_kind, pattern, account_id, min_amount, max_amount, category_id, rename_payee, notes".to_string();
+    }
+
+    fn get_fetch_schema() -> String {
+        return "match_kind, pattern, account_id, min_amount, max_amount, category_id, rename_payee, notes, rowid".to_string();
+    }
+
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.match_kind,
+            &self.pattern,
+            &self.account_id,
+            &self.min_amount,
+            &self.max_amount,
+            &self.category_id,
+            &self.rename_payee,
+            &self.notes,
+        ];
+    }
+}
+
+impl TableActions for CategoryTarget {
+    fn row_to_data(row: &Row) -> Self {
+        CategoryTarget {
+            category_id: row.get(0).unwrap(),
+            kind: row.get(1).unwrap(),
+            amount: row.get(2).unwrap(),
+            target_month: row.get(3).unwrap(),
+            id: row.get(4).unwrap(),
+        }
+    }
+
+    fn get_table_name() -> String {
+        return "category_targets".to_string();
     }
 
     fn get_insert_schema() -> String {
-        return "payee, amount, date, notes, account_id, category_id".to_string();
+        return "category_id, kind, amount, target_month".to_string();
     }
 
     fn get_fetch_schema() -> String {
-        return "payee, amount, date, notes, account_id, category_id".to_string();
+        return "category_id, kind, amount, target_month, rowid".to_string();
     }
 
-    fn to_insert_data(&self) -> String {
-        return format!(
-            "'{}', '{}', '{}', '{}', '{}', '{}'",
-            self.payee, self.amount, self.date, self.notes, self.account_id, self.category_id
-        );
+    fn to_insert_params(&self) -> Vec<&dyn ToSql> {
+        return vec![
+            &self.category_id,
+            &self.kind,
+            &self.amount,
+            &self.target_month,
+        ];
     }
 }
@@ -1,5 +1,12 @@
 use super::*;
 
+use data::category_assignment::CategoryAssignment;
+use data::csv_export::ExportColumn;
+use data::import_profile::AmountFormat;
+
+use time::format_description::well_known::Iso8601;
+use time::PrimitiveDateTime;
+
 macro_rules! function {
     () => {{
         fn f() {}
@@ -13,17 +20,22 @@ macro_rules! function {
     }};
 }
 
-fn test_setup_db(name: &str) -> Database {
+fn test_db_path(name: &str) -> PathBuf {
     let db_dir = "C:/Digital Archive";
 
-    // Remove any past existing db files
-    let _ = std::fs::remove_file(db_dir);
-
-    // Create path for new
     let mut path = std::path::PathBuf::from(format!("{db_dir}/"));
     path.push(format!("{name}_db.db3"));
 
-    let db = Database::new(path, &mut PersistentData::new_empty(), false);
+    // Remove any past existing db files
+    let _ = std::fs::remove_file(&path);
+
+    return path;
+}
+
+fn test_setup_db(name: &str) -> Database {
+    let path = test_db_path(name);
+
+    let db = Database::new(path, &mut PersistentData::new_empty(), false).unwrap();
     return db;
 }
 
@@ -34,6 +46,16 @@ fn test_remove_db(name: &str, db: Database) {
     std::fs::remove_file(db_dir).unwrap();
 }
 
+// Preview and commit a file which should have no bad lines
+fn test_import(db: &Database, file_path: &str, account_id: i64, profile_id: i64) {
+    let preview = db
+        .import_preview(file_path, account_id, profile_id)
+        .unwrap();
+    assert_eq!(preview.errors, vec![]);
+
+    db.import_commit(preview).unwrap();
+}
+
 #[test]
 fn database_setup() {
     let db = test_setup_db(function!());
@@ -45,7 +67,7 @@ fn insert_get() {
     let db = test_setup_db(function!());
     db.insert(Category::new("testing here")).unwrap();
 
-    let cat_ret = db.get::<Category>(1);
+    let cat_ret = db.get::<Category>(1).unwrap();
 
     let mut cat_real = Category::new("testing here");
     cat_real.id = 1;
@@ -158,8 +180,7 @@ fn get_account_display_list() {
     let db = test_setup_db(function!());
 
     db.insert(Account::new("first")).unwrap();
-    db.import("test_input/month_daily_transactions.csv", 1)
-        .unwrap();
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
 
     let account_list = db.get_account_display_list().unwrap();
 
@@ -174,8 +195,7 @@ fn get_account_history() {
     let db = test_setup_db(function!());
 
     db.insert(Account::new("first")).unwrap();
-    db.import("test_input/month_daily_transactions.csv", 1)
-        .unwrap();
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
 
     let entries = db.get_account_history(1).unwrap();
 
@@ -218,7 +238,7 @@ fn get_category_display_list() {
     trans.category_id = 2;
     db.insert(trans).unwrap();
 
-    let category_displays = db.get_category_display_list(1, 10_000).unwrap();
+    let category_displays = db.get_category_display_list(1, 10_000, 197001).unwrap();
 
     assert_eq!(category_displays.len(), 3);
 
@@ -238,8 +258,7 @@ fn get_category_display_list() {
 fn import() {
     let db = test_setup_db(function!());
 
-    db.import("test_input/month_daily_transactions.csv", 0)
-        .unwrap();
+    test_import(&db, "test_input/month_daily_transactions.csv", 0, 1);
 
     let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
 
@@ -261,3 +280,3136 @@ fn import() {
 
     test_remove_db(function!(), db);
 }
+
+#[test]
+fn category_names_with_quotes() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("Trader Joe's")).unwrap();
+    db.insert(Category::new("\"quoted\" name")).unwrap();
+
+    assert_eq!(db.category_exists("Trader Joe's"), Ok(true));
+    assert_eq!(db.category_exists("\"quoted\" name"), Ok(true));
+    assert_eq!(db.get_category_id("Trader Joe's"), Ok(1));
+    assert_eq!(db.get::<Category>(1).unwrap().display_name, "Trader Joe's");
+
+    db.rename_category(2, "Joe's 'other' store".to_string())
+        .unwrap();
+    assert_eq!(
+        db.get::<Category>(2).unwrap().display_name,
+        "Joe's 'other' store"
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn category_names_with_semicolons() {
+    let db = test_setup_db(function!());
+
+    let name = "x'); DROP TABLE categories; --";
+    db.insert(Category::new(name)).unwrap();
+    db.insert(Category::new("second")).unwrap();
+
+    assert_eq!(db.category_exists(name), Ok(true));
+    assert_eq!(db.get_category_id(name), Ok(1));
+    assert_eq!(db.category_exists("' OR '1'='1"), Ok(false));
+
+    db.rename_category(2, "'; DELETE FROM categories; --".to_string())
+        .unwrap();
+
+    let categories = db.get_all::<Category>(OrderBy::None).unwrap();
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0].display_name, name);
+    assert_eq!(categories[1].display_name, "'; DELETE FROM categories; --");
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn unicode_names() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Caixa Econômica 🏦")).unwrap();
+    db.insert(Category::new("Café ☕")).unwrap();
+    db.insert(Category::new("食料品")).unwrap();
+
+    assert_eq!(
+        db.get::<Account>(1).unwrap().display_name,
+        "Caixa Econômica 🏦"
+    );
+    assert_eq!(db.get_category_id("食料品"), Ok(2));
+    assert_eq!(db.category_exists("Cafe ☕"), Ok(false));
+
+    let mut trans = Transaction::new_raw("Zürich Bäckerei — 🥐".to_string(), -450, 0, 1);
+    trans.category_id = 1;
+    trans.notes = "crème brûlée; l'addition".to_string();
+    trans.id = db.insert(trans.clone()).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans, vec![trans]);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn transaction_payee_with_quotes() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Joe's Checking")).unwrap();
+    db.insert(Category::new("Groceries")).unwrap();
+
+    let mut trans = Transaction::new_raw("Trader Joe's".to_string(), -2599, 0, 1);
+    trans.category_id = 1;
+    trans.notes = "it's a 'note'; with \"quotes\"".to_string();
+    trans.id = db.insert(trans.clone()).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans, vec![trans]);
+
+    let transaction_displays = db.get_transaction_list_display().unwrap();
+    assert_eq!(
+        transaction_displays.transactions[0].trans_raw.payee,
+        "Trader Joe's"
+    );
+    assert_eq!(
+        transaction_displays.transactions[0].account_display,
+        "Joe's Checking"
+    );
+
+    let entries = db.get_account_history(1).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].display_name, "Joe's Checking");
+    assert_eq!(entries[0].running_balance, -2599);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn migrations_new_database() {
+    let db = test_setup_db(function!());
+
+    assert_eq!(
+        migrations::get_version(&db.connection),
+        Ok(migrations::latest_version())
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn migrations_reopen() {
+    let path = test_db_path(function!());
+
+    let db = Database::new(path.clone(), &mut PersistentData::new_empty(), false).unwrap();
+    db.insert(Category::new("first")).unwrap();
+    db.connection.close().unwrap();
+
+    let db = Database::new(path, &mut PersistentData::new_empty(), false).unwrap();
+    assert_eq!(db.category_exists("first"), Ok(true));
+
+    test_remove_db(function!(), db);
+}
+
+// Files written before schema versioning have the tables but a user_version of 0
+#[test]
+fn migrations_unversioned_file() {
+    let path = test_db_path(function!());
+
+    {
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "
+                CREATE TABLE categories ( display_name TEXT NOT NULL, balance INTEGER NOT NULL, id INTEGER primary key autoincrement );
+                CREATE TABLE accounts ( display_name TEXT NOT NULL );
+                CREATE TABLE transactions ( payee TEXT NOT NULL, amount INTEGER NOT NULL, date INTEGER NOT NULL, notes TEXT NOT NULL, account_id INTEGER NOT NULL, category_id INTEGER NOT NULL );
+                CREATE TABLE category_transfer ( source INTEGER NOT NULL, dest INTEGER NOT NULL, amount INTEGER NOT NULL );
+                INSERT INTO categories (display_name, balance) VALUES ('first', '0');
+                INSERT INTO accounts (display_name) VALUES ('checking');
+                INSERT INTO transactions VALUES ('payee', '-100', '10', '', '1', '1');
+                ",
+            )
+            .unwrap();
+    }
+
+    let db = Database::new(path, &mut PersistentData::new_empty(), false).unwrap();
+
+    assert_eq!(
+        migrations::get_version(&db.connection),
+        Ok(migrations::latest_version())
+    );
+    assert_eq!(db.get_category_id("first"), Ok(1));
+    assert_eq!(db.get::<Account>(1).unwrap().display_name, "checking");
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 1);
+    assert_eq!(all_trans[0].amount, -100);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn migrations_newer_file() {
+    let path = test_db_path(function!());
+
+    {
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .pragma_update(None, "user_version", migrations::latest_version() + 1)
+            .unwrap();
+    }
+
+    let res = Database::new(path.clone(), &mut PersistentData::new_empty(), false);
+    assert!(matches!(res, Err(RytError::DatabaseNewerThanApp)));
+
+    // What's opened instead at launch
+    let empty = Database::new_in_memory().unwrap();
+    assert!(empty.get_account_display_list().unwrap().is_empty());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn migrations_failed_step_rolls_back() {
+    let path = test_db_path(function!());
+
+    // An index named like the last table makes the first step fail part way through
+    {
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "
+                CREATE TABLE other ( value INTEGER );
+                CREATE INDEX category_transfer ON other ( value );
+                ",
+            )
+            .unwrap();
+    }
+
+    let res = Database::new(path.clone(), &mut PersistentData::new_empty(), false);
+    assert!(matches!(res, Err(RytError::Migration)));
+
+    // Nothing from the failed step was kept
+    {
+        let connection = Connection::open(&path).unwrap();
+        assert_eq!(migrations::get_version(&connection), Ok(0));
+        let tables: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 1);
+    }
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn update_transaction() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("first")).unwrap();
+    db.insert(Account::new("second")).unwrap();
+    db.insert(Category::new("food")).unwrap();
+
+    let mut trans = Transaction::new_raw("first payee".to_string(), -100, 10, 1);
+    trans.id = db.insert(trans.clone()).unwrap();
+    db.insert(Transaction::new_raw("untouched".to_string(), -5, 10, 1))
+        .unwrap();
+
+    trans.payee = "Trader Joe's".to_string();
+    trans.amount = 2500;
+    trans.date = 20;
+    trans.account_id = 2;
+    trans.category_id = 1;
+    trans.notes = "edited".to_string();
+    db.update_transaction(trans.clone()).unwrap();
+
+    assert_eq!(db.get::<Transaction>(trans.id).unwrap(), trans);
+    assert_eq!(db.get::<Transaction>(2).unwrap().payee, "untouched");
+
+    let transaction_displays = db.get_transaction_list_display().unwrap();
+    assert_eq!(transaction_displays.transactions[0].trans_raw, trans);
+    assert_eq!(
+        transaction_displays.transactions[0].account_display,
+        "second"
+    );
+    assert_eq!(
+        transaction_displays.transactions[0].category_display,
+        "food"
+    );
+
+    trans.id = 100;
+    assert_eq!(
+        db.update_transaction(trans),
+        Err(RytError::TransactionMissing)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn delete_transaction() {
+    let db = test_setup_db(function!());
+
+    let first = db
+        .insert(Transaction::new_raw("first".to_string(), -100, 10, 1))
+        .unwrap();
+    db.insert(Transaction::new_raw("second".to_string(), -5, 10, 1))
+        .unwrap();
+
+    db.delete_transaction(first).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 1);
+    assert_eq!(all_trans[0].payee, "second");
+
+    assert_eq!(
+        db.delete_transaction(first),
+        Err(RytError::TransactionMissing)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn get_category_display_list_out_of_range() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("first")).unwrap();
+
+    let mut trans = Transaction::new_raw("before".to_string(), -100, 10, 0);
+    trans.category_id = 1;
+    db.insert(trans).unwrap();
+
+    let mut trans = Transaction::new_raw("after".to_string(), -1000, 50_000, 0);
+    trans.category_id = 1;
+    db.insert(trans).unwrap();
+
+    let category_displays = db.get_category_display_list(20, 10_000, 197001).unwrap();
+
+    assert_eq!(category_displays.len(), 1);
+    assert_eq!(category_displays[0].transaction_total, 0);
+    assert_eq!(category_displays[0].transaction_average, 0.0);
+    assert_eq!(category_displays[0].available, -100);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn assign_category() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("first")).unwrap();
+    db.insert(Category::new("second")).unwrap();
+
+    db.assign_category(1, 202401, 500).unwrap();
+    db.assign_category(1, 202401, 700).unwrap();
+    db.assign_category(2, 202401, 100).unwrap();
+
+    let assignments: Vec<CategoryAssignment> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(
+        assignments,
+        vec![
+            CategoryAssignment::new(1, 202401, 700),
+            CategoryAssignment::new(2, 202401, 100)
+        ]
+    );
+
+    test_remove_db(function!(), db);
+}
+
+fn unix_date(date: &str) -> i64 {
+    let date = PrimitiveDateTime::parse(&format!("{date}T00:00:00"), &Iso8601::DEFAULT).unwrap();
+    return date.assume_utc().unix_timestamp();
+}
+
+#[test]
+fn category_available_rolls_forward() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("food")).unwrap();
+
+    db.assign_category(1, 202401, 10_000).unwrap();
+    db.assign_category(1, 202402, 5_000).unwrap();
+
+    let mut trans = Transaction::new_raw("january".to_string(), -3_000, unix_date("2024-01-15"), 0);
+    trans.category_id = 1;
+    db.insert(trans).unwrap();
+
+    let mut trans =
+        Transaction::new_raw("february".to_string(), -1_000, unix_date("2024-02-15"), 0);
+    trans.category_id = 1;
+    db.insert(trans).unwrap();
+
+    let jan = db
+        .get_category_display_list(unix_date("2024-01-01"), unix_date("2024-02-01") - 1, 202401)
+        .unwrap();
+    assert_eq!(jan[0].assigned, 10_000);
+    assert_eq!(jan[0].transaction_total, -3_000);
+    assert_eq!(jan[0].available, 7_000);
+
+    // January's leftover carries into February
+    let feb = db
+        .get_category_display_list(unix_date("2024-02-01"), unix_date("2024-03-01") - 1, 202402)
+        .unwrap();
+    assert_eq!(feb[0].assigned, 5_000);
+    assert_eq!(feb[0].transaction_total, -1_000);
+    assert_eq!(feb[0].available, 11_000);
+
+    // Nothing new in March, still carried
+    let mar = db
+        .get_category_display_list(unix_date("2024-03-01"), unix_date("2024-04-01") - 1, 202403)
+        .unwrap();
+    assert_eq!(mar[0].assigned, 0);
+    assert_eq!(mar[0].transaction_total, 0);
+    assert_eq!(mar[0].available, 11_000);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn get_ready_to_assign() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("food")).unwrap();
+    db.insert(Category::new("rent")).unwrap();
+
+    // Income has no category
+    db.insert(Transaction::new_raw(
+        "paycheck".to_string(),
+        100_000,
+        unix_date("2024-01-01"),
+        0,
+    ))
+    .unwrap();
+    db.insert(Transaction::new_raw(
+        "paycheck".to_string(),
+        100_000,
+        unix_date("2024-02-01"),
+        0,
+    ))
+    .unwrap();
+
+    // Spending from a category doesn't change what is left to assign
+    let mut trans =
+        Transaction::new_raw("groceries".to_string(), -5_000, unix_date("2024-01-10"), 0);
+    trans.category_id = 1;
+    db.insert(trans).unwrap();
+
+    db.assign_category(1, 202401, 20_000).unwrap();
+    db.assign_category(2, 202401, 50_000).unwrap();
+    db.assign_category(2, 202402, 50_000).unwrap();
+
+    let jan_end = unix_date("2024-02-01") - 1;
+    let feb_end = unix_date("2024-03-01") - 1;
+
+    assert_eq!(db.get_ready_to_assign(jan_end, 202401), Ok(30_000));
+    assert_eq!(db.get_ready_to_assign(feb_end, 202402), Ok(80_000));
+
+    // Deleting a category returns its assignments and spending to be assigned again
+    db.delete::<Category>(1).unwrap();
+    assert_eq!(db.get_ready_to_assign(jan_end, 202401), Ok(45_000));
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn category_transfer_round_trip() {
+    let db = test_setup_db(function!());
+
+    let mut transfer = CategoryTransfer::new(1, 2, 1234, 202401);
+    transfer.id = db.insert(transfer.clone()).unwrap();
+
+    assert_eq!(db.get::<CategoryTransfer>(transfer.id).unwrap(), transfer);
+
+    let all: Vec<CategoryTransfer> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all, vec![transfer]);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn transfer_between_categories() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("food")).unwrap();
+    db.insert(Category::new("fun")).unwrap();
+
+    db.insert(Transaction::new_raw(
+        "paycheck".to_string(),
+        100_000,
+        unix_date("2024-01-01"),
+        0,
+    ))
+    .unwrap();
+    db.assign_category(1, 202401, 10_000).unwrap();
+    db.assign_category(2, 202401, 10_000).unwrap();
+
+    db.transfer_between_categories(CategoryTransfer::new(1, 2, 3_000, 202401))
+        .unwrap();
+    db.transfer_between_categories(CategoryTransfer::new(2, 1, 500, 202402))
+        .unwrap();
+
+    let jan_end = unix_date("2024-02-01") - 1;
+    let jan = db
+        .get_category_display_list(unix_date("2024-01-01"), jan_end, 202401)
+        .unwrap();
+    assert_eq!(jan[0].transferred, -3_000);
+    assert_eq!(jan[0].available, 7_000);
+    assert_eq!(jan[1].transferred, 3_000);
+    assert_eq!(jan[1].available, 13_000);
+
+    let feb = db
+        .get_category_display_list(jan_end + 1, unix_date("2024-03-01") - 1, 202402)
+        .unwrap();
+    assert_eq!(feb[0].transferred, 500);
+    assert_eq!(feb[0].available, 7_500);
+    assert_eq!(feb[1].transferred, -500);
+    assert_eq!(feb[1].available, 12_500);
+
+    // Moving money between categories leaves nothing new to assign
+    assert_eq!(db.get_ready_to_assign(jan_end, 202401), Ok(80_000));
+
+    // The money returns to be assigned once a side is deleted
+    db.delete::<Category>(2).unwrap();
+    assert_eq!(db.get_ready_to_assign(jan_end, 202401), Ok(93_000));
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn transfer_between_categories_invalid() {
+    let db = test_setup_db(function!());
+
+    assert_eq!(
+        db.transfer_between_categories(CategoryTransfer::new(1, 1, 100, 202401)),
+        Err(RytError::TransferSameCategory)
+    );
+    assert_eq!(
+        db.transfer_between_categories(CategoryTransfer::new(1, 2, 0, 202401)),
+        Err(RytError::TransferAmountNotPositive)
+    );
+    assert_eq!(
+        db.transfer_between_categories(CategoryTransfer::new(1, 2, -100, 202401)),
+        Err(RytError::TransferAmountNotPositive)
+    );
+
+    let all: Vec<CategoryTransfer> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all.len(), 0);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn get_category_transfers() {
+    let db = test_setup_db(function!());
+
+    let first = db
+        .transfer_between_categories(CategoryTransfer::new(1, 2, 100, 202401))
+        .unwrap();
+    let second = db
+        .transfer_between_categories(CategoryTransfer::new(3, 1, 200, 202402))
+        .unwrap();
+    db.transfer_between_categories(CategoryTransfer::new(2, 3, 300, 202402))
+        .unwrap();
+
+    let history = db.get_category_transfers(1).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].id, second);
+    assert_eq!(history[0].amount, 200);
+    assert_eq!(history[1].id, first);
+
+    assert_eq!(db.get_category_transfers(2).unwrap().len(), 2);
+    assert_eq!(db.get_category_transfers(4).unwrap().len(), 0);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn default_import_profile() {
+    let db = test_setup_db(function!());
+
+    let profiles: Vec<ImportProfile> = db.get_all(OrderBy::None).unwrap();
+
+    let mut default_profile = ImportProfile::new_default();
+    default_profile.id = 1;
+    assert_eq!(profiles, vec![default_profile]);
+
+    test_remove_db(function!(), db);
+}
+
+fn signed_amounts_profile() -> ImportProfile {
+    let mut profile = ImportProfile::new_default();
+    profile.display_name = "Signed bank".to_string();
+    profile.skip_rows = 2;
+    profile.date_column = 0;
+    profile.date_format = "%m/%d/%Y".to_string();
+    profile.payee_column = 1;
+    profile.amount_format = data::import_profile::AmountFormat::Signed;
+    profile.amount_column = 2;
+    profile.category_column = None;
+    profile.notes_column = Some(3);
+    return profile;
+}
+
+#[test]
+fn create_import_profile() {
+    let db = test_setup_db(function!());
+
+    let mut profile = signed_amounts_profile();
+    profile.id = db.create_import_profile(profile.clone()).unwrap();
+    assert_eq!(db.get::<ImportProfile>(profile.id).unwrap(), profile);
+
+    let mut bad_profile = signed_amounts_profile();
+    bad_profile.delimiter = "".to_string();
+    assert_eq!(
+        db.create_import_profile(bad_profile),
+        Err(RytError::ImportProfileDelimiter)
+    );
+
+    // Negative numbers would wrap around as indexes
+    let mut bad_profile = signed_amounts_profile();
+    bad_profile.skip_rows = -1;
+    assert_eq!(
+        db.create_import_profile(bad_profile),
+        Err(RytError::ImportProfileNegative)
+    );
+    let mut bad_profile = signed_amounts_profile();
+    bad_profile.payee_column = -3;
+    assert_eq!(
+        db.create_import_profile(bad_profile),
+        Err(RytError::ImportProfileNegative)
+    );
+    assert_eq!(db.get_all::<ImportProfile>(OrderBy::None).unwrap().len(), 2);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_with_profile() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    let profile_id = db.create_import_profile(signed_amounts_profile()).unwrap();
+
+    test_import(&db, "test_input/signed_amounts.csv", 1, profile_id);
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
+    assert_eq!(all_trans.len(), 3);
+
+    assert_eq!(all_trans[0].payee, "Trader Joe's");
+    assert_eq!(all_trans[0].amount, -5210);
+    assert_eq!(all_trans[0].notes, "weekly, groceries");
+    assert_eq!(all_trans[0].date, unix_date("2024-01-03"));
+    assert_eq!(all_trans[0].category_id, 0);
+
+    assert_eq!(all_trans[1].payee, "ACME PAYROLL");
+    assert_eq!(all_trans[1].amount, 150000);
+
+    assert_eq!(all_trans[2].payee, "Smith, Jones \"and\" Co");
+    assert_eq!(all_trans[2].amount, -1200);
+
+    // No categories in this layout
+    let categories: Vec<Category> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(categories.len(), 0);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_wrong_profile() {
+    let db = test_setup_db(function!());
+
+    // The default layout can't read these dates. Every line is reported and nothing is kept.
+    let preview = db
+        .import_preview("test_input/signed_amounts.csv", 1, 1)
+        .unwrap();
+    assert_eq!(preview.candidates.len(), 0);
+    // Only the first row is skipped, so the column titles are an error too
+    assert_eq!(preview.errors.len(), 4);
+    assert_eq!(preview.errors[0].line, 2);
+    assert_eq!(preview.errors[1].line, 3);
+    assert_eq!(preview.errors[1].error, RytError::ImportParseDate);
+    assert_eq!(
+        preview.errors[1].text,
+        "01/03/2024,Trader Joe's,-52.10,weekly, groceries"
+    );
+
+    assert_eq!(
+        db.import_preview("test_input/missing_file.csv", 1, 1),
+        Err(RytError::ImportReadFile)
+    );
+
+    assert_eq!(
+        db.import_preview("test_input/signed_amounts.csv", 1, 100),
+        Err(RytError::Rusqlite)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_preview_line_errors() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    let preview = db
+        .import_preview("test_input/malformed_lines.csv", 1, 1)
+        .unwrap();
+
+    // Previewing writes nothing
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 0);
+
+    let lines: Vec<u64> = preview.candidates.iter().map(|c| c.line).collect();
+    assert_eq!(lines, vec![2, 5, 8]);
+
+    let errors: Vec<(u64, RytError)> = preview
+        .errors
+        .iter()
+        .map(|e| (e.line, e.error.clone()))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (3, RytError::ImportParseDate),
+            (4, RytError::ImportParseAmount),
+            (6, RytError::ImportMissingColumn),
+            (7, RytError::ImportNoAmount),
+        ]
+    );
+    assert_eq!(preview.errors[1].text, "BOA,2024-01-03,Arbys,twelve,,Food");
+
+    // Inflows don't take a category
+    assert_eq!(preview.candidates[1].category, "");
+
+    db.import_commit(preview).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
+    assert_eq!(all_trans.len(), 3);
+    assert_eq!(all_trans[0].payee, "Arbys");
+    assert_eq!(all_trans[0].category_id, 1);
+    assert_eq!(all_trans[1].category_id, 0);
+    assert_eq!(all_trans[2].category_id, 1);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_commit_atomic() {
+    let db = test_setup_db(function!());
+
+    let preview = db
+        .import_preview("test_input/month_daily_transactions.csv", 1, 1)
+        .unwrap();
+
+    // Fail part way through the commit
+    db.connection
+        .execute_batch(
+            "CREATE TRIGGER fail_insert BEFORE INSERT ON transactions WHEN NEW.payee = 'The City'
+            BEGIN SELECT RAISE(ABORT, 'fail'); END;",
+        )
+        .unwrap();
+
+    assert_eq!(db.import_commit(preview), Err(RytError::Rusqlite));
+
+    // Neither the rows before the failure, their categories, nor the batch were kept
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 0);
+    let categories: Vec<Category> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(categories.len(), 0);
+    let batches: Vec<ImportBatch> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(batches.len(), 0);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_same_file_twice() {
+    let db = test_setup_db(function!());
+
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
+
+    let preview = db
+        .import_preview("test_input/month_daily_transactions.csv", 1, 1)
+        .unwrap();
+    assert_eq!(preview.candidates.len(), 31);
+    assert!(preview.candidates.iter().all(|c| c.skip));
+
+    // Same lines into a different account aren't duplicates
+    let preview_other = db
+        .import_preview("test_input/month_daily_transactions.csv", 2, 1)
+        .unwrap();
+    assert!(preview_other.candidates.iter().all(|c| !c.skip));
+
+    db.import_commit(preview).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 31);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_overlapping_statement() {
+    let db = test_setup_db(function!());
+
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
+
+    let mut preview = db
+        .import_preview("test_input/overlapping_transactions.csv", 1, 1)
+        .unwrap();
+
+    let duplicates: Vec<i64> = preview.candidates.iter().map(|c| c.duplicate_of).collect();
+    assert_eq!(
+        duplicates,
+        vec![
+            // Posted two days later with the store number added
+            1, // Arbys only happened once, so the second one is new
+            0, // Payee too different
+            0, // Outside the date window
+            0, // Amount off by a cent
+            0,
+        ]
+    );
+    assert!(preview.candidates[0].skip);
+
+    // Keep the flagged duplicate anyway
+    preview.candidates[0].skip = false;
+    db.import_commit(preview).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 36);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_rollback() {
+    let db = test_setup_db(function!());
+
+    db.insert(Transaction::new_raw("by hand".to_string(), -100, 0, 1))
+        .unwrap();
+
+    let preview = db
+        .import_preview("test_input/month_daily_transactions.csv", 1, 1)
+        .unwrap();
+    let batch_id = db.import_commit(preview).unwrap();
+
+    let batches = db.get_import_batches(1).unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].id, batch_id);
+    assert_eq!(batches[0].file_name, "month_daily_transactions.csv");
+    assert_eq!(batches[0].transaction_count, 31);
+    assert_eq!(db.get_import_batches(2).unwrap().len(), 0);
+
+    // Editing doesn't lose where the transaction came from
+    let mut trans: Transaction = db.get(2).unwrap();
+    assert_eq!(trans.import_batch_id, batch_id);
+    trans.import_batch_id = 0;
+    trans.payee = "edited".to_string();
+    db.update_transaction(trans).unwrap();
+    assert_eq!(db.get::<Transaction>(2).unwrap().import_batch_id, batch_id);
+
+    db.import_rollback(batch_id).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 1);
+    assert_eq!(all_trans[0].payee, "by hand");
+    assert_eq!(db.get_import_batches(1).unwrap().len(), 0);
+
+    assert_eq!(
+        db.import_rollback(batch_id),
+        Err(RytError::ImportBatchMissing)
+    );
+    assert_eq!(db.import_rollback(0), Err(RytError::ImportBatchMissing));
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_rollback_reconciled() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    let preview = db
+        .import_preview("test_input/month_daily_transactions.csv", 1, 1)
+        .unwrap();
+    let batch_id = db.import_commit(preview).unwrap();
+
+    let trans: Transaction = db.get(2).unwrap();
+    db.set_transaction_cleared(trans.id, ClearedStatus::Cleared)
+        .unwrap();
+    db.reconcile_account(1, trans.date, trans.amount).unwrap();
+    assert!(db.get::<Transaction>(2).unwrap().is_reconciled());
+
+    // Nothing is undone, not even the unreconciled ones
+    assert_eq!(
+        db.import_rollback(batch_id),
+        Err(RytError::TransactionReconciled)
+    );
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 31);
+    assert_eq!(db.get_import_batches(1).unwrap().len(), 1);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn get_account_display_list_empty_accounts() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("first")).unwrap();
+    db.insert(Account::new("second")).unwrap();
+    db.insert(Transaction::new_raw("payee".to_string(), 100, 0, 2))
+        .unwrap();
+
+    let account_list = db.get_account_display_list().unwrap();
+    assert_eq!(account_list.len(), 2);
+    assert_eq!(account_list[0].balance, 0);
+    assert_eq!(account_list[1].balance, 100);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_ofx() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Transaction::new_raw(
+        "Starting Balance".to_string(),
+        10_000,
+        unix_date("2024-01-01"),
+        1,
+    ))
+    .unwrap();
+
+    let preview = db
+        .import_ofx_preview("test_input/statement.ofx", 1)
+        .unwrap();
+    assert_eq!(preview.file_name, "statement.ofx");
+    assert_eq!(preview.candidates.len(), 4);
+    assert_eq!(preview.candidates[0].line, 23);
+    assert_eq!(preview.candidates[0].trans.payee, "SMITH & SONS");
+    assert_eq!(preview.candidates[0].trans.fitid, "2024010501");
+
+    // Identical purchases with their own bank ids aren't duplicates of each other
+    assert!(preview.candidates.iter().all(|c| !c.skip));
+
+    assert_eq!(preview.errors.len(), 1);
+    assert_eq!(preview.errors[0].error, RytError::ImportOfxMissingField);
+
+    // The missing line is why the account doesn't match the bank
+    assert_eq!(preview.account_balance, 10_000);
+    assert_eq!(
+        preview.ledger_balance,
+        Some(LedgerBalance {
+            amount: 154790,
+            date: unix_date("2024-01-31"),
+        })
+    );
+    assert_eq!(preview.ledger_difference(), Some(-100));
+
+    db.import_commit(preview).unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
+    assert_eq!(all_trans.len(), 5);
+    assert_eq!(all_trans[1].fitid, "2024010501");
+
+    // Bank ids catch a re-import even after the payee is edited
+    let mut trans = all_trans[1].clone();
+    trans.payee = "Smith and Sons hardware".to_string();
+    db.update_transaction(trans).unwrap();
+
+    let preview = db
+        .import_ofx_preview("test_input/statement.ofx", 1)
+        .unwrap();
+    let duplicates: Vec<i64> = preview.candidates.iter().map(|c| c.duplicate_of).collect();
+    assert_eq!(duplicates, vec![2, 3, 4, 5]);
+    assert_eq!(preview.account_balance, 154890);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_ofx_matches_hand_entered() {
+    let db = test_setup_db(function!());
+
+    // Entered by hand before the statement arrived, so no bank id
+    db.insert(Transaction::new_raw(
+        "Smith and Sons".to_string(),
+        -4210,
+        unix_date("2024-01-04"),
+        1,
+    ))
+    .unwrap();
+
+    let preview = db
+        .import_ofx_preview("test_input/statement.ofx", 1)
+        .unwrap();
+    assert_eq!(preview.candidates[0].duplicate_of, 1);
+    assert!(preview.candidates[0].skip);
+
+    assert_eq!(
+        db.import_ofx_preview("test_input/month_daily_transactions.csv", 1),
+        Err(RytError::ImportOfxInvalid)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_qif() {
+    let db = test_setup_db(function!());
+
+    // Checking already exists, Visa is created
+    db.insert(Account::new("Checking")).unwrap();
+
+    db.import_qif("test_input/history.qif", 1).unwrap();
+
+    let accounts: Vec<Account> = db.get_all(OrderBy::None).unwrap();
+    let account_names: Vec<&str> = accounts.iter().map(|a| a.display_name.as_str()).collect();
+    assert_eq!(account_names, vec!["Checking", "Visa"]);
+
+    let categories: Vec<Category> = db.get_all(OrderBy::None).unwrap();
+    let category_names: Vec<&str> = categories.iter().map(|c| c.display_name.as_str()).collect();
+    assert_eq!(
+        category_names,
+        vec!["Groceries", "Household", "Entertainment"]
+    );
+
+    // Food:Groceries is Groceries in the group Food
+    let groups = db.get_category_groups().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].display_name, "Food");
+    assert_eq!(categories[0].group_id, groups[0].id);
+    assert_eq!(categories[1].group_id, UNGROUPED_ID);
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
+    let summary: Vec<(String, i64, i64, i64)> = all_trans
+        .iter()
+        .map(|t| (t.payee.clone(), t.amount, t.account_id, t.category_id))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Opening Balance".to_string(), 100_000, 1, 0),
+            // The split lines are in the splits
+            ("Costco".to_string(), -15_000, 1, 0),
+            // Income stays uncategorized
+            ("Acme Payroll".to_string(), 250_000, 1, 0),
+            ("Streaming Co".to_string(), -4_599, 2, 3),
+        ]
+    );
+    assert_eq!(all_trans[1].notes, "Monthly trip");
+    assert_eq!(all_trans[0].date, unix_date("1998-12-30"));
+
+    let splits: Vec<(i64, i64, String)> = db
+        .get_transaction_splits(all_trans[1].id)
+        .unwrap()
+        .into_iter()
+        .map(|s| (s.category_id, s.amount, s.notes))
+        .collect();
+    assert_eq!(
+        splits,
+        vec![
+            (1, -10_000, "Produce".to_string()),
+            (2, -3_000, String::new()),
+            // The part of the total the splits don't cover
+            (0, -2_000, String::new()),
+        ]
+    );
+
+    // One batch per account, so the whole import can be undone
+    let batches = db.get_import_batches(1).unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].transaction_count, 3);
+    db.import_rollback(batches[0].id).unwrap();
+    db.import_rollback(db.get_import_batches(2).unwrap()[0].id)
+        .unwrap();
+
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 0);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn import_qif_bad_record() {
+    let db = test_setup_db(function!());
+
+    std::fs::write(
+        "C:/Digital Archive/import_qif_bad_record.qif",
+        "!Type:Bank\nD1/15'24\nT-1.00\nPFine\nLFood\n^\nDnot a date\nT-1.00\n^\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        db.import_qif("C:/Digital Archive/import_qif_bad_record.qif", 1),
+        Err(RytError::ImportParseDate)
+    );
+
+    // Nothing from before the bad record was kept
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 0);
+    let categories: Vec<Category> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(categories.len(), 0);
+
+    std::fs::remove_file("C:/Digital Archive/import_qif_bad_record.qif").unwrap();
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn export_qif_round_trip() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Checking")).unwrap();
+    db.import_qif("test_input/history.qif", 1).unwrap();
+
+    let mut trans: Transaction = db.get(3).unwrap();
+    trans.notes = "bonus".to_string();
+    db.update_transaction(trans).unwrap();
+
+    let path = PathBuf::from("C:/Digital Archive/export_qif_round_trip.qif");
+    db.export_qif(path.clone()).unwrap();
+
+    let other_db = test_setup_db("export_qif_round_trip_other");
+    other_db.insert(Account::new("Checking")).unwrap();
+    other_db
+        .import_qif("C:/Digital Archive/export_qif_round_trip.qif", 1)
+        .unwrap();
+
+    let summary = |db: &Database| -> Vec<(String, i64, i64, i64, String, i64)> {
+        let all_trans: Vec<Transaction> = db.get_all(OrderBy::Date).unwrap();
+        all_trans
+            .iter()
+            .map(|t| {
+                (
+                    t.payee.clone(),
+                    t.amount,
+                    t.date,
+                    t.account_id,
+                    t.notes.clone(),
+                    t.category_id,
+                )
+            })
+            .collect()
+    };
+    assert_eq!(summary(&db), summary(&other_db));
+
+    std::fs::remove_file(path).unwrap();
+    test_remove_db("export_qif_round_trip_other", other_db);
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn export_qif_split_round_trip() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Checking")).unwrap();
+    db.insert(Category::new("groceries")).unwrap();
+    db.insert(Category::new("household")).unwrap();
+
+    let trans = Transaction::new_raw("Costco".to_string(), -15_000, unix_date("2024-01-15"), 1);
+    let mut produce = split(1, -10_000);
+    produce.notes = "Produce".to_string();
+    db.insert_split_transaction(trans, vec![produce, split(2, -5_000)])
+        .unwrap();
+
+    let path = PathBuf::from("C:/Digital Archive/export_qif_split_round_trip.qif");
+    db.export_qif(path.clone()).unwrap();
+
+    let other_db = test_setup_db("export_qif_split_round_trip_other");
+    other_db.insert(Account::new("Checking")).unwrap();
+    other_db
+        .import_qif("C:/Digital Archive/export_qif_split_round_trip.qif", 1)
+        .unwrap();
+
+    // Still one transaction, with the same lines
+    let all_trans: Vec<Transaction> = other_db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 1);
+    assert_eq!(all_trans[0].amount, -15_000);
+
+    let splits = |db: &Database, transaction_id: i64| -> Vec<(i64, String)> {
+        db.get_transaction_splits(transaction_id)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.amount, s.notes))
+            .collect()
+    };
+    assert_eq!(splits(&other_db, all_trans[0].id), splits(&db, 1));
+    assert_eq!(splits(&other_db, all_trans[0].id).len(), 2);
+
+    let categories: Vec<Category> = other_db.get_all(OrderBy::None).unwrap();
+    let category_names: Vec<&str> = categories.iter().map(|c| c.display_name.as_str()).collect();
+    assert_eq!(category_names, vec!["groceries", "household"]);
+
+    std::fs::remove_file(path).unwrap();
+    test_remove_db("export_qif_split_round_trip_other", other_db);
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn export_qif_groups_and_transfers() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Checking")).unwrap();
+    db.insert(Account::new("Savings")).unwrap();
+    let rent = db.get_or_create_category("Bills:Rent").unwrap();
+    let gifts = db.get_or_create_category("Gifts").unwrap();
+
+    let mut trans =
+        Transaction::new_raw("Landlord".to_string(), -100_000, unix_date("2024-01-01"), 1);
+    trans.category_id = rent;
+    db.insert(trans).unwrap();
+    let trans = Transaction::new_raw("Target".to_string(), -3_000, unix_date("2024-01-02"), 1);
+    db.insert_split_transaction(trans, vec![split(rent, -1_000), split(gifts, -2_000)])
+        .unwrap();
+    db.create_account_transfer(1, 2, 5_000, unix_date("2024-01-03"), String::new())
+        .unwrap();
+
+    let path = PathBuf::from("C:/Digital Archive/export_qif_groups_and_transfers.qif");
+    db.export_qif(path.clone()).unwrap();
+    let accounts = qif::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+    let categories = |account: &qif::QifAccount| -> Vec<(String, Vec<String>)> {
+        account
+            .transactions
+            .iter()
+            .map(|t| {
+                (
+                    t.category.clone(),
+                    t.splits.iter().map(|s| s.category.clone()).collect(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(accounts[0].name, "Checking");
+    assert_eq!(
+        categories(&accounts[0]),
+        vec![
+            ("Bills:Rent".to_string(), vec![]),
+            (
+                String::new(),
+                vec!["Bills:Rent".to_string(), "Gifts".to_string()]
+            ),
+            ("[Savings]".to_string(), vec![]),
+        ]
+    );
+    assert_eq!(accounts[1].name, "Savings");
+    assert_eq!(
+        categories(&accounts[1]),
+        vec![("[Checking]".to_string(), vec![])]
+    );
+
+    // The group is found again on import, transfers stay uncategorized
+    let other_db = test_setup_db("export_qif_groups_and_transfers_other");
+    other_db.insert(Account::new("Checking")).unwrap();
+    other_db
+        .import_qif("C:/Digital Archive/export_qif_groups_and_transfers.qif", 1)
+        .unwrap();
+    let groups = other_db.get_category_groups().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].display_name, "Bills");
+    let all_trans: Vec<Transaction> = other_db.get_all(OrderBy::Date).unwrap();
+    assert_eq!(
+        all_trans[0].category_id,
+        other_db.get_or_create_category("Bills:Rent").unwrap()
+    );
+    assert_eq!(all_trans[2].category_id, UNCATEGORIZED_ID);
+
+    std::fs::remove_file(path).unwrap();
+    test_remove_db("export_qif_groups_and_transfers_other", other_db);
+    test_remove_db(function!(), db);
+}
+
+// Fields of each line after the header
+fn read_exported_csv(path: &str) -> Vec<Vec<String>> {
+    let mut reader = csv::Reader::from_path(path).unwrap();
+    return reader
+        .records()
+        .map(|r| r.unwrap().iter().map(|v| v.to_string()).collect())
+        .collect();
+}
+
+#[test]
+fn export_csv_round_trip() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("BOA")).unwrap();
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
+
+    // Characters the old exporter wrote unescaped
+    let mut trans: Transaction = db.get(1).unwrap();
+    trans.payee = "Smith, \"Bob\"\nand Sons".to_string();
+    db.update_transaction(trans).unwrap();
+
+    let path = PathBuf::from("C:/Digital Archive/export_csv_round_trip.csv");
+    db.export_csv(path.clone(), &CsvExportOptions::new_default())
+        .unwrap();
+
+    // The default layout reads it back
+    let other_db = test_setup_db("export_csv_round_trip_other");
+    other_db.insert(Account::new("BOA")).unwrap();
+    test_import(
+        &other_db,
+        "C:/Digital Archive/export_csv_round_trip.csv",
+        1,
+        1,
+    );
+
+    let summary = |db: &Database| -> Vec<(String, i64, i64, String)> {
+        db.get_transaction_list_display()
+            .unwrap()
+            .transactions
+            .into_iter()
+            .map(|t| {
+                (
+                    t.trans_raw.payee,
+                    t.trans_raw.amount,
+                    t.trans_raw.date,
+                    t.category_display,
+                )
+            })
+            .collect()
+    };
+    assert_eq!(summary(&db).len(), 31);
+    assert_eq!(summary(&db), summary(&other_db));
+
+    std::fs::remove_file(path).unwrap();
+    test_remove_db("export_csv_round_trip_other", other_db);
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn export_csv_filters() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Checking")).unwrap();
+    db.import_qif("test_input/history.qif", 1).unwrap();
+    db.update_transaction({
+        let mut trans: Transaction = db.get(2).unwrap();
+        trans.notes = "with notes".to_string();
+        trans
+    })
+    .unwrap();
+
+    let path = "C:/Digital Archive/export_csv_filters.csv";
+    let export = |options: CsvExportOptions| -> Vec<Vec<String>> {
+        db.export_csv(PathBuf::from(path), &options).unwrap();
+        return read_exported_csv(path);
+    };
+
+    let columns = vec![
+        ExportColumn::Payee,
+        ExportColumn::Amount,
+        ExportColumn::Notes,
+    ];
+    let signed = CsvExportOptions {
+        columns,
+        amount_format: AmountFormat::Signed,
+        ..CsvExportOptions::new_default()
+    };
+
+    // Everything, oldest first
+    assert_eq!(export(signed.clone()).len(), 6);
+
+    assert_eq!(
+        export(CsvExportOptions {
+            account_id: Some(2),
+            ..signed.clone()
+        }),
+        vec![vec!["Streaming Co", "-45.99", ""]]
+    );
+
+    assert_eq!(
+        export(CsvExportOptions {
+            category_id: Some(1),
+            ..signed.clone()
+        }),
+        vec![vec!["Costco", "-100.00", "Produce"]]
+    );
+
+    // Both ends of the range are included
+    let in_range = export(CsvExportOptions {
+        start_date: Some(unix_date("2024-01-15")),
+        end_date: Some(unix_date("2024-01-16")),
+        category_id: Some(UNCATEGORIZED_ID),
+        ..signed.clone()
+    });
+    assert_eq!(
+        in_range,
+        vec![
+            // Split lines without notes of their own have the transaction's
+            vec!["Costco", "-20.00", "with notes"],
+            vec!["Acme Payroll", "2500.00", ""],
+        ]
+    );
+
+    let split = export(CsvExportOptions {
+        account_id: Some(1),
+        amount_format: AmountFormat::OutflowInflow,
+        ..signed.clone()
+    });
+    assert_eq!(split[0], vec!["Opening Balance", "", "1000.00", ""]);
+    assert_eq!(split[1], vec!["Costco", "100.00", "", "Produce"]);
+
+    // The header matches the columns
+    let mut reader = csv::Reader::from_path(path).unwrap();
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec!["Payee", "Outflow", "Inflow", "Notes"]
+    );
+
+    assert_eq!(
+        db.export_csv(
+            PathBuf::from("C:/No Such Folder/export.csv"),
+            &CsvExportOptions::new_default()
+        ),
+        Err(RytError::ExportWriteFile)
+    );
+
+    std::fs::remove_file(path).unwrap();
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn backup_restore_round_trip() {
+    let db = test_setup_db(function!());
+
+    // Something in every table, with gaps in the ids
+    db.insert(Category::new("deleted")).unwrap();
+    db.insert(Category::new("food")).unwrap();
+    db.insert(Category::new("rent")).unwrap();
+    db.insert(Category::new("deleted last")).unwrap();
+    db.delete::<Category>(1).unwrap();
+    db.delete::<Category>(4).unwrap();
+
+    db.insert(Account::new("checking")).unwrap();
+    test_import(&db, "test_input/month_daily_transactions.csv", 1, 1);
+    db.import_ofx_preview("test_input/statement.ofx", 1)
+        .and_then(|p| db.import_commit(p))
+        .unwrap();
+
+    let mut trans: Transaction = db.get(3).unwrap();
+    trans.notes = "quotes \" and, commas\nand lines".to_string();
+    db.update_transaction(trans).unwrap();
+    db.delete_transaction(4).unwrap();
+
+    db.assign_category(2, 202401, 50_000).unwrap();
+    db.transfer_between_categories(CategoryTransfer::new(2, 3, 1_000, 202401))
+        .unwrap();
+    db.create_import_profile(signed_amounts_profile()).unwrap();
+
+    let backup_path = PathBuf::from("C:/Digital Archive/backup_restore_round_trip.json");
+    db.backup(backup_path.clone()).unwrap();
+
+    let restored = Database::restore(
+        backup_path.clone(),
+        test_db_path("backup_restore_round_trip_restored"),
+        &mut PersistentData::new_empty(),
+        false,
+    )
+    .unwrap();
+
+    let original = backup::dump(&db.connection).unwrap();
+    assert_eq!(backup::dump(&restored.connection).unwrap(), original);
+
+    // Ids still line up, so new rows don't reuse deleted ids
+    let categories: Vec<Category> = restored.get_all(OrderBy::None).unwrap();
+    assert_eq!(categories[0].id, 2);
+    assert_eq!(
+        restored.insert(Category::new("new")).unwrap(),
+        db.insert(Category::new("new")).unwrap()
+    );
+    assert_eq!(
+        restored.get_ready_to_assign(unix_date("2024-02-01"), 202401),
+        db.get_ready_to_assign(unix_date("2024-02-01"), 202401)
+    );
+
+    std::fs::remove_file(backup_path).unwrap();
+    test_remove_db("backup_restore_round_trip_restored", restored);
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn restore_old_snapshot() {
+    // Made by a version with only the original tables
+    let snapshot = r#"{
+        "format": 1,
+        "schema_version": 1,
+        "tables": [
+            { "name": "accounts", "columns": ["rowid", "display_name"], "rows": [[1, "checking"]] },
+            { "name": "categories", "columns": ["display_name", "balance", "id"], "rows": [["food", 0, 7]] },
+            { "name": "category_transfer", "columns": ["rowid", "source", "dest", "amount"], "rows": [] },
+            {
+                "name": "transactions",
+                "columns": ["rowid", "payee", "amount", "date", "notes", "account_id", "category_id"],
+                "rows": [[3, "Arbys", -500, 0, "", 1, 7]]
+            }
+        ]
+    }"#;
+
+    let snapshot_path = PathBuf::from("C:/Digital Archive/restore_old_snapshot.json");
+    std::fs::write(&snapshot_path, snapshot).unwrap();
+
+    let db = Database::restore(
+        snapshot_path.clone(),
+        test_db_path(function!()),
+        &mut PersistentData::new_empty(),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(
+        migrations::get_version(&db.connection).unwrap(),
+        migrations::latest_version()
+    );
+
+    let trans: Transaction = db.get(3).unwrap();
+    assert_eq!(trans.payee, "Arbys");
+    assert_eq!(trans.category_id, 7);
+    assert_eq!(trans.import_batch_id, 0);
+    assert_eq!(db.get::<Category>(7).unwrap().display_name, "food");
+
+    // Later migrations still add their own rows
+    let profiles: Vec<ImportProfile> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(profiles.len(), 1);
+
+    std::fs::remove_file(snapshot_path).unwrap();
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn restore_bad_snapshot() {
+    let snapshot_path = PathBuf::from("C:/Digital Archive/restore_bad_snapshot.json");
+    let restore = || {
+        Database::restore(
+            snapshot_path.clone(),
+            test_db_path("restore_bad_snapshot"),
+            &mut PersistentData::new_empty(),
+            false,
+        )
+        .map(|_| ())
+    };
+
+    std::fs::write(
+        &snapshot_path,
+        r#"{ "format": 1, "schema_version": 1000, "tables": [] }"#,
+    )
+    .unwrap();
+    assert_eq!(restore(), Err(RytError::DatabaseNewerThanApp));
+
+    std::fs::write(
+        &snapshot_path,
+        r#"{ "format": 1, "schema_version": 1, "tables": [ { "name": "missing", "columns": [], "rows": [] } ] }"#,
+    )
+    .unwrap();
+    assert_eq!(restore(), Err(RytError::RestoreInvalidSnapshot));
+
+    std::fs::write(&snapshot_path, "not json").unwrap();
+    assert_eq!(restore(), Err(RytError::RestoreInvalidSnapshot));
+
+    // Nothing is left behind
+    assert!(!test_db_path("restore_bad_snapshot").exists());
+    assert!(!test_db_path("restore_bad_snapshot")
+        .with_extension("restoring")
+        .exists());
+
+    std::fs::remove_file(&snapshot_path).unwrap();
+    assert_eq!(restore(), Err(RytError::RestoreReadFile));
+}
+
+fn split(category_id: i64, amount: i64) -> TransactionSplit {
+    return TransactionSplit::new(category_id, amount);
+}
+
+#[test]
+fn split_transaction_categories() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Category::new("groceries")).unwrap();
+    db.insert(Category::new("household")).unwrap();
+    db.insert(Category::new("pharmacy")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        100_000,
+        date,
+        1,
+    ))
+    .unwrap();
+
+    // The parent's own category is replaced by the lines
+    let mut costco = Transaction::new_raw("Costco".to_string(), -15_000, date, 1);
+    costco.category_id = 2;
+    let mut pharmacy = split(3, -2_000);
+    pharmacy.notes = "Prescription".to_string();
+    let costco_id = db
+        .insert_split_transaction(costco, vec![split(1, -10_000), split(2, -3_000), pharmacy])
+        .unwrap();
+
+    assert_eq!(db.get::<Transaction>(costco_id).unwrap().category_id, 0);
+    assert_eq!(db.get_transaction_splits(costco_id).unwrap().len(), 3);
+
+    let category_displays = db
+        .get_category_display_list(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401)
+        .unwrap();
+    let totals: Vec<i64> = category_displays
+        .iter()
+        .map(|c| c.transaction_total)
+        .collect();
+    assert_eq!(totals, vec![-10_000, -3_000, -2_000]);
+    assert_eq!(category_displays[0].available, -10_000);
+
+    // Only the paycheck is income, the split parent isn't counted again
+    assert_eq!(
+        db.get_ready_to_assign(unix_date("2024-01-31"), 202401),
+        Ok(100_000)
+    );
+
+    let display = db.get_transaction_list_display().unwrap();
+    let costco_display = &display.transactions[1];
+    assert_eq!(costco_display.category_display, "");
+    let split_names: Vec<&str> = costco_display
+        .splits
+        .iter()
+        .map(|s| s.category_display.as_str())
+        .collect();
+    assert_eq!(split_names, vec!["groceries", "household", "pharmacy"]);
+    assert_eq!(costco_display.splits[2].split_raw.notes, "Prescription");
+    assert!(display.transactions[0].splits.is_empty());
+
+    // A deleted category's line counts as unassigned money again
+    db.delete::<Category>(3).unwrap();
+    assert_eq!(
+        db.get_ready_to_assign(unix_date("2024-01-31"), 202401),
+        Ok(98_000)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn split_transaction_invalid() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("groceries")).unwrap();
+    db.insert(Category::new("household")).unwrap();
+
+    let trans = Transaction::new_raw("Costco".to_string(), -15_000, 10, 1);
+    assert_eq!(
+        db.insert_split_transaction(trans.clone(), vec![split(1, -15_000)]),
+        Err(RytError::SplitTooFewLines)
+    );
+    assert_eq!(
+        db.insert_split_transaction(trans.clone(), vec![split(1, -10_000), split(2, -3_000)]),
+        Err(RytError::SplitAmountMismatch)
+    );
+
+    // Nothing was written by either
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 0);
+
+    let id = db
+        .insert_split_transaction(trans.clone(), vec![split(1, -10_000), split(2, -5_000)])
+        .unwrap();
+
+    let mut edited = trans.clone();
+    edited.id = id;
+    edited.amount = -20_000;
+    assert_eq!(
+        db.update_split_transaction(edited.clone(), vec![split(1, -10_000), split(2, -5_000)]),
+        Err(RytError::SplitAmountMismatch)
+    );
+
+    // The failed edit left everything as it was
+    assert_eq!(db.get::<Transaction>(id).unwrap().amount, -15_000);
+    assert_eq!(db.get_transaction_splits(id).unwrap().len(), 2);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn split_transaction_edit_delete() {
+    let db = test_setup_db(function!());
+
+    db.insert(Category::new("groceries")).unwrap();
+    db.insert(Category::new("household")).unwrap();
+
+    let trans = Transaction::new_raw("Costco".to_string(), -15_000, 10, 1);
+    let id = db
+        .insert_split_transaction(trans.clone(), vec![split(1, -10_000), split(2, -5_000)])
+        .unwrap();
+
+    // New amount and lines together
+    let mut edited = trans.clone();
+    edited.id = id;
+    edited.amount = -20_000;
+    db.update_split_transaction(edited.clone(), vec![split(1, -12_000), split(2, -8_000)])
+        .unwrap();
+    let amounts: Vec<i64> = db
+        .get_transaction_splits(id)
+        .unwrap()
+        .iter()
+        .map(|s| s.amount)
+        .collect();
+    assert_eq!(amounts, vec![-12_000, -8_000]);
+
+    // Editing without touching the amount keeps the lines
+    edited.payee = "Costco Wholesale".to_string();
+    db.update_transaction(edited.clone()).unwrap();
+    assert_eq!(db.get_transaction_splits(id).unwrap().len(), 2);
+
+    // A new amount doesn't match the lines any more
+    edited.amount = -1_000;
+    edited.category_id = 2;
+    db.update_transaction(edited.clone()).unwrap();
+    assert_eq!(db.get_transaction_splits(id).unwrap(), vec![]);
+    assert_eq!(db.get::<Transaction>(id).unwrap().category_id, 2);
+
+    // No lines makes it an ordinary transaction
+    let id = db
+        .insert_split_transaction(trans.clone(), vec![split(1, -10_000), split(2, -5_000)])
+        .unwrap();
+    let mut edited = trans.clone();
+    edited.id = id;
+    edited.category_id = 1;
+    db.update_split_transaction(edited, vec![]).unwrap();
+    assert_eq!(db.get_transaction_splits(id).unwrap(), vec![]);
+    assert_eq!(db.get::<Transaction>(id).unwrap().category_id, 1);
+
+    // Lines go with their transaction
+    db.update_split_transaction(
+        db.get::<Transaction>(id).unwrap(),
+        vec![split(1, -10_000), split(2, -5_000)],
+    )
+    .unwrap();
+    db.delete_transaction(id).unwrap();
+    let all_splits: Vec<TransactionSplit> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_splits, vec![]);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn split_transaction_exports() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("Checking")).unwrap();
+    db.insert(Category::new("groceries")).unwrap();
+    db.insert(Category::new("household")).unwrap();
+
+    let mut trans = Transaction::new_raw("Costco".to_string(), -15_000, unix_date("2024-01-15"), 1);
+    trans.notes = "Monthly trip".to_string();
+    let mut produce = split(1, -10_000);
+    produce.notes = "Produce".to_string();
+    db.insert_split_transaction(trans, vec![produce, split(2, -5_000)])
+        .unwrap();
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        50_000,
+        unix_date("2024-01-16"),
+        1,
+    ))
+    .unwrap();
+
+    // One line per split, notes fall back to the transaction's
+    let path = "C:/Digital Archive/split_transaction_exports.csv";
+    let options = CsvExportOptions {
+        columns: vec![
+            ExportColumn::Payee,
+            ExportColumn::Category,
+            ExportColumn::Notes,
+            ExportColumn::Amount,
+        ],
+        amount_format: AmountFormat::Signed,
+        ..CsvExportOptions::new_default()
+    };
+    db.export_csv(PathBuf::from(path), &options).unwrap();
+    assert_eq!(
+        read_exported_csv(path),
+        vec![
+            vec!["Costco", "groceries", "Produce", "-100.00"],
+            vec!["Costco", "household", "Monthly trip", "-50.00"],
+            vec!["Paycheck", "", "", "500.00"],
+        ]
+    );
+
+    // Filtering by category picks out the line
+    db.export_csv(
+        PathBuf::from(path),
+        &CsvExportOptions {
+            category_id: Some(2),
+            ..options.clone()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        read_exported_csv(path),
+        vec![vec!["Costco", "household", "Monthly trip", "-50.00"]]
+    );
+    std::fs::remove_file(path).unwrap();
+
+    // QIF keeps the transaction whole, with its splits
+    let qif_path = PathBuf::from("C:/Digital Archive/split_transaction_exports.qif");
+    db.export_qif(qif_path.clone()).unwrap();
+    let text = std::fs::read_to_string(&qif_path).unwrap();
+    let accounts = qif::parse(&text).unwrap();
+    assert_eq!(accounts[0].transactions[0].amount, -15_000);
+    assert_eq!(
+        accounts[0].transactions[0].splits,
+        vec![
+            qif::QifSplit {
+                category: "groceries".to_string(),
+                memo: "Produce".to_string(),
+                amount: -10_000,
+            },
+            qif::QifSplit {
+                category: "household".to_string(),
+                memo: String::new(),
+                amount: -5_000,
+            },
+        ]
+    );
+    std::fs::remove_file(qif_path).unwrap();
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn account_transfer() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Account::new("savings")).unwrap();
+    db.insert(Category::new("food")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        100_000,
+        date,
+        1,
+    ))
+    .unwrap();
+
+    let (from_id, to_id) = db
+        .create_account_transfer(1, 2, 25_000, date, "rainy day".to_string())
+        .unwrap();
+
+    let from_side: Transaction = db.get(from_id).unwrap();
+    let to_side: Transaction = db.get(to_id).unwrap();
+    assert_eq!(from_side.payee, "Transfer to savings");
+    assert_eq!(from_side.amount, -25_000);
+    assert_eq!(from_side.transfer_id, to_id);
+    assert_eq!(to_side.payee, "Transfer from checking");
+    assert_eq!(to_side.amount, 25_000);
+    assert_eq!(to_side.transfer_id, from_id);
+    assert_eq!(to_side.notes, "rainy day");
+
+    let balances: Vec<i64> = db
+        .get_account_display_list()
+        .unwrap()
+        .iter()
+        .map(|a| a.balance)
+        .collect();
+    assert_eq!(balances, vec![75_000, 25_000]);
+
+    // Neither side is spending or income
+    assert_eq!(
+        db.get_ready_to_assign(unix_date("2024-01-31"), 202401),
+        Ok(100_000)
+    );
+
+    assert_eq!(
+        db.create_account_transfer(1, 1, 100, date, String::new()),
+        Err(RytError::TransferSameAccount)
+    );
+    assert_eq!(
+        db.create_account_transfer(1, 2, -100, date, String::new()),
+        Err(RytError::TransferAmountNotPositive)
+    );
+    assert_eq!(
+        db.create_account_transfer(1, 5, 100, date, String::new()),
+        Err(RytError::Rusqlite)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn account_transfer_sync() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Account::new("savings")).unwrap();
+    db.insert(Category::new("food")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    let (from_id, to_id) = db
+        .create_account_transfer(1, 2, 25_000, date, String::new())
+        .unwrap();
+
+    // Edited from the UI, which rebuilds the transaction without the link
+    let mut edited = Transaction::new_raw(
+        "Transfer to savings".to_string(),
+        30_000,
+        unix_date("2024-01-20"),
+        1,
+    );
+    edited.id = to_id;
+    edited.account_id = 2;
+    edited.category_id = 1;
+    db.update_transaction(edited).unwrap();
+
+    let from_side: Transaction = db.get(from_id).unwrap();
+    let to_side: Transaction = db.get(to_id).unwrap();
+    assert_eq!(from_side.amount, -30_000);
+    assert_eq!(from_side.date, unix_date("2024-01-20"));
+    assert_eq!(to_side.transfer_id, from_id);
+
+    // Transfers never take a category
+    assert_eq!(to_side.category_id, 0);
+    let category_displays = db
+        .get_category_display_list(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401)
+        .unwrap();
+    assert_eq!(category_displays[0].transaction_total, 0);
+
+    // Moving a side renames both, but never onto the other side's account
+    db.insert(Account::new("brokerage")).unwrap();
+    let mut moved = to_side.clone();
+    moved.account_id = 3;
+    db.update_transaction(moved.clone()).unwrap();
+    let from_side: Transaction = db.get(from_id).unwrap();
+    let to_side: Transaction = db.get(to_id).unwrap();
+    assert_eq!(to_side.account_id, 3);
+    assert_eq!(to_side.payee, "Transfer from checking");
+    assert_eq!(from_side.payee, "Transfer to brokerage");
+
+    moved.account_id = 1;
+    assert_eq!(
+        db.update_transaction(moved),
+        Err(RytError::TransferSameAccount)
+    );
+    let to_side: Transaction = db.get(to_id).unwrap();
+    assert_eq!(to_side.account_id, 3);
+
+    // Either side takes the other with it
+    db.insert(Transaction::new_raw("Arbys".to_string(), -500, date, 1))
+        .unwrap();
+    db.delete_transaction(from_id).unwrap();
+    let all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(all_trans.len(), 1);
+    assert_eq!(all_trans[0].payee, "Arbys");
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn scheduled_transactions_create_due() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Category::new("rent")).unwrap();
+
+    let mut rent = ScheduledTransaction::new(
+        "Landlord".to_string(),
+        -120_000,
+        1,
+        Frequency::Monthly,
+        unix_date("2024-01-01"),
+    );
+    rent.category_id = 1;
+    rent.notes = "flat".to_string();
+    rent.end_date = Some(unix_date("2024-03-15"));
+    let id = db.create_scheduled_transaction(rent).unwrap();
+
+    assert_eq!(db.create_due_scheduled(unix_date("2024-02-10")), Ok(2));
+
+    // Running again only makes the new ones
+    assert_eq!(db.create_due_scheduled(unix_date("2024-02-10")), Ok(0));
+    assert_eq!(db.create_due_scheduled(unix_date("2024-12-31")), Ok(1));
+
+    let mut transactions: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    transactions.sort_by_key(|t| t.date);
+    let dates: Vec<i64> = transactions.iter().map(|t| t.date).collect();
+    assert_eq!(
+        dates,
+        vec![
+            unix_date("2024-01-01"),
+            unix_date("2024-02-01"),
+            unix_date("2024-03-01")
+        ]
+    );
+    assert!(transactions
+        .iter()
+        .all(|t| t.payee == "Landlord" && t.category_id == 1 && t.notes == "flat"));
+
+    let scheduled: ScheduledTransaction = db.get(id).unwrap();
+    assert_eq!(scheduled.occurrence_count, 3);
+
+    // Deleting keeps what was already made
+    db.delete_scheduled_transaction(id).unwrap();
+    assert_eq!(db.get_all::<Transaction>(OrderBy::None).unwrap().len(), 3);
+    assert_eq!(
+        db.delete_scheduled_transaction(id),
+        Err(rusqlite::Error::QueryReturnedNoRows)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn scheduled_transactions_upcoming() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Account::new("savings")).unwrap();
+
+    let start = unix_date("2024-01-01");
+    db.create_scheduled_transaction(ScheduledTransaction::new(
+        "Paycheck".to_string(),
+        200_000,
+        1,
+        Frequency::Biweekly,
+        start,
+    ))
+    .unwrap();
+    db.create_scheduled_transaction(ScheduledTransaction::new(
+        "Interest".to_string(),
+        500,
+        2,
+        Frequency::Monthly,
+        start,
+    ))
+    .unwrap();
+    db.create_due_scheduled(start).unwrap();
+
+    let upcoming = db
+        .get_upcoming_transactions(1, unix_date("2024-01-31"))
+        .unwrap();
+    let dates: Vec<i64> = upcoming.iter().map(|u| u.date).collect();
+    assert_eq!(
+        dates,
+        vec![unix_date("2024-01-15"), unix_date("2024-01-29")]
+    );
+    assert!(upcoming
+        .iter()
+        .all(|u| u.payee == "Paycheck" && u.amount == 200_000 && u.scheduled_id == 1));
+
+    assert!(db
+        .get_upcoming_transactions(2, unix_date("2024-01-31"))
+        .unwrap()
+        .is_empty());
+
+    let mut bad = ScheduledTransaction::new("x".to_string(), 0, 1, Frequency::Daily, start);
+    assert_eq!(
+        db.create_scheduled_transaction(bad.clone()),
+        Err(RytError::ScheduleNoAmount)
+    );
+    bad.amount = 100;
+    bad.end_date = Some(start - 1);
+    assert_eq!(
+        db.create_scheduled_transaction(bad),
+        Err(RytError::ScheduleEndBeforeStart)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn payee_rules_on_import() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    let groceries = db.insert(Category::new("groceries")).unwrap();
+
+    let mut rename = PayeeRule::new(PayeeMatch::Contains, "arbys".to_string());
+    rename.rename_payee = "Arby's".to_string();
+    rename.category_id = groceries;
+    db.create_payee_rule(rename).unwrap();
+
+    let mut grocer = PayeeRule::new(PayeeMatch::Equals, "grocery store".to_string());
+    grocer.category_id = groceries;
+    db.create_payee_rule(grocer).unwrap();
+
+    let mut city = PayeeRule::new(PayeeMatch::Regex, "^city of".to_string());
+    city.notes = "water".to_string();
+    city.max_amount = Some(0);
+    db.create_payee_rule(city).unwrap();
+
+    test_import(&db, "test_input/overlapping_transactions.csv", 1, 1);
+
+    let mut all_trans: Vec<Transaction> = db.get_all(OrderBy::None).unwrap();
+    all_trans.sort_by_key(|t| t.id);
+    let payees: Vec<&str> = all_trans.iter().map(|t| t.payee.as_str()).collect();
+    assert_eq!(
+        payees,
+        vec![
+            "Arby's",
+            "Arby's",
+            "City of Springfield",
+            "Grocery Store",
+            "McDonalds"
+        ]
+    );
+
+    // The file's own category wins
+    let extra = db.get_category_id("Extra").unwrap();
+    assert_eq!(all_trans[0].category_id, extra);
+    assert_eq!(all_trans[3].category_id, groceries);
+
+    // Inflow is outside the amount range
+    assert_eq!(all_trans[2].notes, "");
+    assert_eq!(
+        all_trans[4].category_id,
+        db.get_category_id("Bills").unwrap()
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn payee_rules_on_existing() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Account::new("savings")).unwrap();
+    let coffee = db.insert(Category::new("coffee")).unwrap();
+    let other = db.insert(Category::new("other")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    let mut categorized = Transaction::new_raw("Starbucks".to_string(), -500, date, 1);
+    categorized.category_id = other;
+    db.insert(categorized).unwrap();
+    let plain_id = db
+        .insert(Transaction::new_raw(
+            "STARBUCKS 0042".to_string(),
+            -450,
+            date,
+            1,
+        ))
+        .unwrap();
+    let split_id = db
+        .insert_split_transaction(
+            Transaction::new_raw("Starbucks".to_string(), -900, date, 1),
+            vec![split(coffee, -400), split(other, -500)],
+        )
+        .unwrap();
+    db.create_account_transfer(1, 2, 1_000, date, String::new())
+        .unwrap();
+
+    assert_eq!(db.apply_payee_rules(), Ok(0));
+
+    let mut rule = PayeeRule::new(PayeeMatch::Contains, "starbucks".to_string());
+    rule.category_id = coffee;
+    rule.rename_payee = "Starbucks".to_string();
+    db.create_payee_rule(rule).unwrap();
+
+    let mut transfers = PayeeRule::new(PayeeMatch::Contains, "transfer".to_string());
+    transfers.category_id = other;
+    db.create_payee_rule(transfers).unwrap();
+
+    assert_eq!(db.apply_payee_rules(), Ok(1));
+    let plain: Transaction = db.get(plain_id).unwrap();
+    assert_eq!(plain.payee, "Starbucks");
+    assert_eq!(plain.category_id, coffee);
+
+    let split_parent: Transaction = db.get(split_id).unwrap();
+    assert_eq!(split_parent.category_id, 0);
+
+    // Nothing left to do
+    assert_eq!(db.apply_payee_rules(), Ok(0));
+
+    assert_eq!(
+        db.create_payee_rule(PayeeRule::new(PayeeMatch::Contains, "x".to_string())),
+        Err(RytError::RuleNoAction)
+    );
+    let rules = db.get_payee_rules().unwrap();
+    assert_eq!(rules.len(), 2);
+    db.delete_payee_rule(rules[0].id).unwrap();
+    assert_eq!(db.get_payee_rules().unwrap().len(), 1);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn cleared_balances() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    let paycheck = db
+        .insert(Transaction::new_raw(
+            "Paycheck".to_string(),
+            100_000,
+            date,
+            1,
+        ))
+        .unwrap();
+    let rent = db
+        .insert(Transaction::new_raw("Rent".to_string(), -60_000, date, 1))
+        .unwrap();
+
+    db.set_transaction_cleared(paycheck, ClearedStatus::Cleared)
+        .unwrap();
+
+    let accounts = db.get_account_display_list().unwrap();
+    assert_eq!(accounts[0].balance, 40_000);
+    assert_eq!(accounts[0].cleared_balance, 100_000);
+
+    // Edits don't change the status
+    let mut edited: Transaction = db.get(rent).unwrap();
+    edited.cleared = ClearedStatus::Reconciled;
+    edited.amount = -55_000;
+    db.update_transaction(edited).unwrap();
+    let edited: Transaction = db.get(rent).unwrap();
+    assert_eq!(edited.cleared, ClearedStatus::Uncleared);
+    assert_eq!(edited.amount, -55_000);
+
+    assert_eq!(
+        db.set_transaction_cleared(100, ClearedStatus::Cleared),
+        Err(RytError::TransactionMissing)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn reconcile_account() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    db.insert(Account::new("savings")).unwrap();
+
+    let paycheck = db
+        .insert(Transaction::new_raw(
+            "Paycheck".to_string(),
+            100_000,
+            unix_date("2024-01-01"),
+            1,
+        ))
+        .unwrap();
+    // Entered with a time of day, still on the statement date
+    let groceries = db
+        .insert(Transaction::new_raw(
+            "Groceries".to_string(),
+            -5_000,
+            unix_date("2024-01-31") + 15 * 60 * 60,
+            1,
+        ))
+        .unwrap();
+    let later = db
+        .insert(Transaction::new_raw(
+            "Later".to_string(),
+            -1_000,
+            unix_date("2024-02-02"),
+            1,
+        ))
+        .unwrap();
+    let pending = db
+        .insert(Transaction::new_raw(
+            "Pending".to_string(),
+            -2_000,
+            unix_date("2024-01-20"),
+            1,
+        ))
+        .unwrap();
+    let (savings_side, checking_side) = db
+        .create_account_transfer(2, 1, 3_000, unix_date("2024-01-10"), String::new())
+        .unwrap();
+
+    for id in [paycheck, groceries, later] {
+        db.set_transaction_cleared(id, ClearedStatus::Cleared)
+            .unwrap();
+    }
+
+    // The transfer in hasn't cleared yet
+    let statement_date = unix_date("2024-01-31");
+    let summary = db.reconcile_account(1, statement_date, 98_000).unwrap();
+    assert_eq!(
+        summary,
+        ReconcileSummary {
+            account_id: 1,
+            statement_date,
+            statement_balance: 98_000,
+            cleared_balance: 95_000,
+            difference: 3_000,
+            reconciled_count: 0,
+        }
+    );
+    assert_eq!(
+        db.get::<Transaction>(paycheck).unwrap().cleared,
+        ClearedStatus::Cleared
+    );
+
+    db.set_transaction_cleared(checking_side, ClearedStatus::Cleared)
+        .unwrap();
+    let summary = db.reconcile_account(1, statement_date, 98_000).unwrap();
+    assert_eq!(summary.difference, 0);
+    assert_eq!(summary.reconciled_count, 3);
+
+    let status = |id: i64| db.get::<Transaction>(id).unwrap().cleared;
+    assert_eq!(status(paycheck), ClearedStatus::Reconciled);
+    assert_eq!(status(groceries), ClearedStatus::Reconciled);
+    assert_eq!(status(later), ClearedStatus::Cleared);
+    assert_eq!(status(pending), ClearedStatus::Uncleared);
+
+    // Reconciled transactions are locked, including through the other side of a transfer
+    let mut edited: Transaction = db.get(paycheck).unwrap();
+    edited.amount = 1;
+    assert_eq!(
+        db.update_transaction(edited),
+        Err(RytError::TransactionReconciled)
+    );
+    assert_eq!(
+        db.delete_transaction(groceries),
+        Err(RytError::TransactionReconciled)
+    );
+    assert_eq!(
+        db.delete_transaction(savings_side),
+        Err(RytError::TransactionReconciled)
+    );
+    assert_eq!(
+        db.update_split_transaction(
+            db.get(paycheck).unwrap(),
+            vec![split(0, 50_000), split(0, 50_000)]
+        ),
+        Err(RytError::TransactionReconciled)
+    );
+
+    // Unlocking allows edits again
+    db.set_transaction_cleared(groceries, ClearedStatus::Cleared)
+        .unwrap();
+    db.delete_transaction(groceries).unwrap();
+
+    // Only open accounts that exist can be reconciled
+    assert_eq!(
+        db.reconcile_account(5, statement_date, 0),
+        Err(RytError::AccountMissing)
+    );
+    let closed = db.insert(Account::new("old")).unwrap();
+    db.close_account(closed).unwrap();
+    assert_eq!(
+        db.reconcile_account(closed, statement_date, 0),
+        Err(RytError::AccountClosed)
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn tracking_accounts() {
+    let db = test_setup_db(function!());
+
+    db.insert(Account::new("checking")).unwrap();
+    let brokerage = db
+        .insert(Account::new_typed("brokerage", AccountType::Investment))
+        .unwrap();
+    let food = db.insert(Category::new("food")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        100_000,
+        date,
+        1,
+    ))
+    .unwrap();
+
+    // Nothing in a tracking account touches the budget
+    db.insert(Transaction::new_raw(
+        "Dividend".to_string(),
+        5_000,
+        date,
+        brokerage,
+    ))
+    .unwrap();
+    let mut tracked_spend = Transaction::new_raw("Fees".to_string(), -700, date, brokerage);
+    tracked_spend.category_id = food;
+    db.insert(tracked_spend).unwrap();
+
+    // Money sent to a tracking account leaves the budget
+    db.create_account_transfer(1, brokerage, 20_000, date, String::new())
+        .unwrap();
+
+    let month_end = unix_date("2024-01-31");
+    assert_eq!(db.get_ready_to_assign(month_end, 202401), Ok(80_000));
+    let categories = db
+        .get_category_display_list(unix_date("2024-01-01"), month_end, 202401)
+        .unwrap();
+    assert_eq!(categories[0].transaction_total, 0);
+
+    let accounts = db.get_account_display_list().unwrap();
+    assert_eq!(accounts[1].account_type, AccountType::Investment);
+    assert!(!accounts[1].on_budget);
+    assert_eq!(accounts[1].balance, 24_300);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn close_and_delete_accounts() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let card = db
+        .insert(Account::new_typed("card", AccountType::CreditCard))
+        .unwrap();
+    let empty = db.insert(Account::new("empty")).unwrap();
+
+    let date = unix_date("2024-01-15");
+    let charge = db
+        .insert(Transaction::new_raw("Shop".to_string(), -3_000, date, card))
+        .unwrap();
+
+    db.rename_account(card, "visa".to_string()).unwrap();
+    assert_eq!(db.get::<Account>(card).unwrap().display_name, "visa");
+    assert_eq!(
+        db.rename_account(100, "x".to_string()),
+        Err(RytError::AccountMissing)
+    );
+
+    assert_eq!(db.close_account(card), Err(RytError::AccountBalanceNotZero));
+    assert_eq!(
+        db.delete_account(card),
+        Err(RytError::AccountHasTransactions)
+    );
+
+    // Paid off, it can be closed but still not deleted
+    db.create_account_transfer(checking, card, 3_000, date, String::new())
+        .unwrap();
+    db.close_account(card).unwrap();
+    assert_eq!(
+        db.delete_account(card),
+        Err(RytError::AccountHasTransactions)
+    );
+
+    let open: Vec<i64> = db
+        .get_account_display_list()
+        .unwrap()
+        .iter()
+        .map(|a| a.account_id)
+        .collect();
+    assert_eq!(open, vec![checking, empty]);
+    let closed = db.get_closed_account_display_list().unwrap();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].account_id, card);
+    assert_eq!(closed[0].account_type, AccountType::CreditCard);
+
+    db.reopen_account(card).unwrap();
+    assert_eq!(db.get_account_display_list().unwrap().len(), 3);
+    db.delete_transaction(charge).unwrap();
+
+    db.create_scheduled_transaction(ScheduledTransaction::new(
+        "Gym".to_string(),
+        -1_000,
+        empty,
+        Frequency::Monthly,
+        unix_date("2030-01-01"),
+    ))
+    .unwrap();
+    assert_eq!(db.close_account(empty), Err(RytError::AccountHasSchedules));
+    assert_eq!(db.delete_account(empty), Err(RytError::AccountHasSchedules));
+    db.delete_scheduled_transaction(1).unwrap();
+
+    db.delete_account(empty).unwrap();
+    assert_eq!(db.delete_account(empty), Err(RytError::AccountMissing));
+    assert_eq!(db.get_account_display_list().unwrap().len(), 2);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn credit_card_payment_category() {
+    let db = test_setup_db(function!());
+
+    let checking = db.create_account(Account::new("checking")).unwrap();
+    let card = db
+        .create_account(Account::new_typed("visa", AccountType::CreditCard))
+        .unwrap();
+    let groceries = db.insert(Category::new("groceries")).unwrap();
+
+    let categories: Vec<Category> = db.get_all(OrderBy::None).unwrap();
+    assert_eq!(categories.len(), 2);
+    let payment = categories
+        .iter()
+        .find(|c| c.payment_account_id == card)
+        .unwrap();
+    assert_eq!(payment.display_name, "visa Payment");
+    let payment_id = payment.id;
+
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        100_000,
+        unix_date("2024-01-01"),
+        checking,
+    ))
+    .unwrap();
+    db.assign_category(groceries, 202401, 20_000).unwrap();
+
+    // Spending on the card moves the money over to the payment
+    let mut shop =
+        Transaction::new_raw("Grocer".to_string(), -15_000, unix_date("2024-01-05"), card);
+    shop.category_id = groceries;
+    db.insert(shop).unwrap();
+    let mut refund =
+        Transaction::new_raw("Grocer".to_string(), 1_000, unix_date("2024-01-06"), card);
+    refund.category_id = groceries;
+    db.insert(refund).unwrap();
+
+    let available = |month_end: &str, month: i64| -> (i64, i64) {
+        let list = db
+            .get_category_display_list(unix_date("2024-01-01"), unix_date(month_end), month)
+            .unwrap();
+        let get = |id: i64| list.iter().find(|c| c.category_id == id).unwrap().available;
+        (get(groceries), get(payment_id))
+    };
+    assert_eq!(available("2024-01-31", 202401), (6_000, 14_000));
+
+    // Paying the card draws the payment category down, not ready to assign
+    db.create_account_transfer(
+        checking,
+        card,
+        10_000,
+        unix_date("2024-01-20"),
+        String::new(),
+    )
+    .unwrap();
+    assert_eq!(available("2024-01-31", 202401), (6_000, 4_000));
+    assert_eq!(
+        db.get_ready_to_assign(unix_date("2024-01-31"), 202401),
+        Ok(80_000)
+    );
+
+    // Cards off the budget get no payment category
+    db.create_account(Account {
+        on_budget: false,
+        ..Account::new_typed("store card", AccountType::CreditCard)
+    })
+    .unwrap();
+    assert_eq!(db.get_all::<Category>(OrderBy::None).unwrap().len(), 2);
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn category_groups() {
+    let db = test_setup_db(function!());
+
+    let bills = db.create_category_group("Bills").unwrap();
+    let everyday = db.create_category_group("Everyday").unwrap();
+
+    let rent = db.get_or_create_category("Bills:Rent").unwrap();
+    let power = db.get_or_create_category("Bills:Power").unwrap();
+    let food = db.get_or_create_category("Everyday:Food").unwrap();
+    let gifts = db.get_or_create_category("Gifts").unwrap();
+
+    // Parent:Child finds the same category again, and makes missing groups
+    assert_eq!(db.get_or_create_category("Bills:Rent"), Ok(rent));
+    let fun = db.get_or_create_category("Fun:Games").unwrap();
+    let fun_group = db.get_category_groups().unwrap()[2].id;
+
+    db.assign_category(rent, 202401, 100_000).unwrap();
+    db.assign_category(power, 202401, 8_000).unwrap();
+    db.assign_category(food, 202401, 40_000).unwrap();
+
+    let list_ids = |db: &Database| -> Vec<(i64, Vec<i64>)> {
+        db.get_category_group_display_list(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401)
+            .unwrap()
+            .iter()
+            .map(|g| {
+                (
+                    g.group.id,
+                    g.categories.iter().map(|c| c.category_id).collect(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(
+        list_ids(&db),
+        vec![
+            (UNGROUPED_ID, vec![gifts]),
+            (bills, vec![rent, power]),
+            (everyday, vec![food]),
+            (fun_group, vec![fun]),
+        ]
+    );
+
+    let groups = db
+        .get_category_group_display_list(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401)
+        .unwrap();
+    assert_eq!(groups[1].assigned, 108_000);
+    assert_eq!(groups[1].available, 108_000);
+
+    // Reordering groups and categories
+    db.move_category_group(everyday, true).unwrap();
+    db.move_category(power, true).unwrap();
+    db.move_category(power, true).unwrap();
+    assert_eq!(
+        list_ids(&db),
+        vec![
+            (UNGROUPED_ID, vec![gifts]),
+            (everyday, vec![food]),
+            (bills, vec![power, rent]),
+            (fun_group, vec![fun]),
+        ]
+    );
+
+    // Moving between groups puts the category last
+    db.set_category_group(gifts, everyday).unwrap();
+    db.set_category_group(food, UNGROUPED_ID).unwrap();
+    assert_eq!(
+        db.set_category_group(food, 99),
+        Err(RytError::CategoryGroupMissing)
+    );
+    assert_eq!(
+        db.set_category_group(99, everyday),
+        Err(RytError::CategoryMissing)
+    );
+
+    db.rename_category_group(everyday, "Gifts").unwrap();
+    db.set_category_group_collapsed(everyday, true).unwrap();
+    let groups = db.get_category_groups().unwrap();
+    assert_eq!(groups[0].display_name, "Gifts");
+    assert!(groups[0].collapsed);
+
+    // Deleting a group keeps its categories
+    db.delete_category_group(bills).unwrap();
+    assert_eq!(
+        db.delete_category_group(bills),
+        Err(RytError::CategoryGroupMissing)
+    );
+    assert_eq!(
+        list_ids(&db),
+        vec![
+            (UNGROUPED_ID, vec![food, power, rent]),
+            (everyday, vec![gifts]),
+            (fun_group, vec![fun]),
+        ]
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn category_targets() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let rent = db.insert(Category::new("rent")).unwrap();
+    let vacation = db.insert(Category::new("vacation")).unwrap();
+    let repairs = db.insert(Category::new("repairs")).unwrap();
+    let gifts = db.insert(Category::new("gifts")).unwrap();
+
+    db.insert(Transaction::new_raw(
+        "Paycheck".to_string(),
+        500_000,
+        unix_date("2024-01-01"),
+        checking,
+    ))
+    .unwrap();
+
+    db.set_category_target(CategoryTarget::new(
+        rent,
+        TargetKind::MonthlyAmount,
+        150_000,
+    ))
+    .unwrap();
+
+    // 1200 by June, 6 months from January
+    let mut by_june = CategoryTarget::new(vacation, TargetKind::BalanceByDate, 120_000);
+    assert_eq!(
+        db.set_category_target(by_june.clone()),
+        Err(RytError::TargetMissingMonth)
+    );
+    by_june.target_month = Some(202406);
+    db.set_category_target(by_june).unwrap();
+
+    db.set_category_target(CategoryTarget::new(repairs, TargetKind::RefillUpTo, 30_000))
+        .unwrap();
+    assert_eq!(
+        db.set_category_target(CategoryTarget::new(99, TargetKind::RefillUpTo, 30_000)),
+        Err(RytError::CategoryMissing)
+    );
+
+    // Spending in repairs doesn't change the refill goal, it is what's missing that counts
+    let mut fix = Transaction::new_raw(
+        "Plumber".to_string(),
+        -5_000,
+        unix_date("2024-01-10"),
+        checking,
+    );
+    fix.category_id = repairs;
+    db.insert(fix).unwrap();
+    db.assign_category(rent, 202401, 50_000).unwrap();
+
+    let needed = |db: &Database| -> Vec<(i64, i64)> {
+        db.get_category_display_list(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401)
+            .unwrap()
+            .iter()
+            .map(|c| (c.category_id, c.target_needed))
+            .collect()
+    };
+    assert_eq!(
+        needed(&db),
+        vec![
+            (rent, 100_000),
+            (vacation, 20_000),
+            (repairs, 35_000),
+            (gifts, 0)
+        ]
+    );
+
+    assert_eq!(
+        db.assign_to_targets(unix_date("2024-01-01"), unix_date("2024-01-31"), 202401),
+        Ok(155_000)
+    );
+    assert_eq!(
+        needed(&db),
+        vec![(rent, 0), (vacation, 0), (repairs, 0), (gifts, 0)]
+    );
+    assert_eq!(
+        db.get_ready_to_assign(unix_date("2024-01-31"), 202401),
+        Ok(500_000 - 150_000 - 20_000 - 35_000)
+    );
+
+    // Replacing and removing targets
+    db.set_category_target(CategoryTarget::new(
+        rent,
+        TargetKind::MonthlyAmount,
+        200_000,
+    ))
+    .unwrap();
+    assert_eq!(db.get_category_targets().unwrap().len(), 3);
+    assert_eq!(needed(&db)[0], (rent, 50_000));
+
+    db.delete_category_target(rent).unwrap();
+    assert!(db.delete_category_target(rent).is_err());
+    assert_eq!(needed(&db)[0], (rent, 0));
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn query_transactions() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let savings = db.insert(Account::new("savings")).unwrap();
+    let groceries = db.insert(Category::new("groceries")).unwrap();
+    let household = db.insert(Category::new("household")).unwrap();
+
+    let add = |payee: &str, amount: i64, date: &str, account_id: i64, category_id: i64| -> i64 {
+        let mut trans =
+            Transaction::new_raw(payee.to_string(), amount, unix_date(date), account_id);
+        trans.category_id = category_id;
+        return db.insert(trans).unwrap();
+    };
+    let paycheck = add("Paycheck", 200_000, "2024-01-01", checking, 0);
+    let grocer = add("Corner Grocer", -4_000, "2024-01-05", checking, groceries);
+    let mystery = add("Mystery Shop", -1_500, "2024-01-07", checking, 0);
+    let interest = add("Interest", 300, "2024-01-31", savings, 0);
+    let hardware = add("Hardware Store", -8_000, "2024-02-02", checking, household);
+
+    let costco = db
+        .insert_split_transaction(
+            Transaction::new_raw(
+                "COSTCO".to_string(),
+                -6_000,
+                unix_date("2024-02-10"),
+                checking,
+            ),
+            vec![split(groceries, -5_000), split(household, -1_000)],
+        )
+        .unwrap();
+    let (to_savings, _) = db
+        .create_account_transfer(
+            checking,
+            savings,
+            10_000,
+            unix_date("2024-02-15"),
+            String::new(),
+        )
+        .unwrap();
+    db.set_transaction_cleared(grocer, ClearedStatus::Cleared)
+        .unwrap();
+
+    let ids = |query: &TransactionQuery| -> Vec<i64> {
+        db.query_transactions(query)
+            .unwrap()
+            .transactions
+            .iter()
+            .map(|t| t.trans_raw.id)
+            .collect()
+    };
+
+    // Newest first by default
+    let mut query = TransactionQuery::new();
+    query.filter.account_id = Some(checking);
+    assert_eq!(
+        ids(&query),
+        vec![to_savings, costco, hardware, mystery, grocer, paycheck]
+    );
+
+    // Split transactions match on their lines
+    query.filter.category_id = Some(groceries);
+    assert_eq!(ids(&query), vec![costco, grocer]);
+    let page = db.query_transactions(&query).unwrap();
+    assert_eq!(page.transactions[0].splits.len(), 2);
+
+    // Uncategorized leaves out transfers and splits
+    query.filter = TransactionFilter::default();
+    query.filter.uncategorized_only = true;
+    query.sort = TransactionSort::DateOldest;
+    assert_eq!(ids(&query), vec![paycheck, mystery, interest]);
+
+    // Payee text ignores case, amounts are signed
+    query.filter = TransactionFilter::default();
+    query.filter.payee = "costco".to_string();
+    assert_eq!(ids(&query), vec![costco]);
+
+    query.filter = TransactionFilter::default();
+    query.filter.min_amount = Some(-6_000);
+    query.filter.max_amount = Some(-1_000);
+    query.sort = TransactionSort::AmountSmallest;
+    assert_eq!(ids(&query), vec![costco, grocer, mystery]);
+
+    query.filter = TransactionFilter::default();
+    query.filter.start_date = Some(unix_date("2024-01-05"));
+    query.filter.end_date = Some(unix_date("2024-01-31"));
+    query.filter.cleared = Some(ClearedStatus::Uncleared);
+    query.sort = TransactionSort::Payee;
+    assert_eq!(ids(&query), vec![interest, mystery]);
+
+    // Pages of two, counting every match
+    query = TransactionQuery::new();
+    query.limit = 2;
+    query.offset = 6;
+    let page = db.query_transactions(&query).unwrap();
+    assert_eq!(page.total_count, 8);
+    assert_eq!(page.page_number(), 4);
+    assert_eq!(ids(&query), vec![grocer, paycheck]);
+
+    query.offset = 8;
+    assert!(ids(&query).is_empty());
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn query_transactions_uses_indexes() {
+    let db = test_setup_db(function!());
+
+    let plan = |filter: &TransactionFilter| -> String {
+        let (where_clause, filter_params) = transaction_filter_sql(filter);
+        let mut stmt = db
+            .connection
+            .prepare(&format!(
+                "explain query plan select count(*) from transactions where {}",
+                where_clause
+            ))
+            .unwrap();
+        let rows = stmt
+            .query_map(filter_params.as_slice(), |row| row.get::<usize, String>(3))
+            .unwrap();
+        return rows.map(|r| r.unwrap()).collect::<Vec<String>>().join("\n");
+    };
+
+    let filter = TransactionFilter {
+        start_date: Some(unix_date("2024-01-01")),
+        account_id: Some(1),
+        ..Default::default()
+    };
+    assert!(plan(&filter).contains("INDEX transactions_account_date"));
+
+    let filter = TransactionFilter {
+        end_date: Some(unix_date("2024-01-31")),
+        ..Default::default()
+    };
+    assert!(plan(&filter).contains("INDEX transactions_date"));
+
+    let filter = TransactionFilter {
+        category_id: Some(1),
+        ..Default::default()
+    };
+    assert!(plan(&filter).contains("INDEX transactions_category"));
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn spending_reports() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let brokerage = db
+        .insert(Account::new_typed("brokerage", AccountType::Investment))
+        .unwrap();
+    let groceries = db.insert(Category::new("groceries")).unwrap();
+    let dining = db.insert(Category::new("dining")).unwrap();
+
+    let add = |payee: &str, amount: i64, date: &str, account_id: i64, category_id: i64| {
+        let mut trans =
+            Transaction::new_raw(payee.to_string(), amount, unix_date(date), account_id);
+        trans.category_id = category_id;
+        db.insert(trans).unwrap();
+    };
+    add("Paycheck", 300_000, "2024-01-01", checking, 0);
+    add("Grocer", -20_000, "2024-01-05", checking, groceries);
+    add("Grocer", -10_000, "2024-01-20", checking, groceries);
+    add("Cafe", -5_000, "2024-01-21", checking, dining);
+    add("Cash", -1_000, "2024-01-22", checking, 0);
+    add("Paycheck", 300_000, "2024-03-01", checking, 0);
+    add("Grocer", -25_000, "2024-03-05", checking, groceries);
+    add("Grocer", 5_000, "2024-03-06", checking, groceries);
+    add("Cafe", -9_000, "2024-03-09", checking, dining);
+
+    // Neither tracking accounts nor transfers count
+    add("Dividend", 7_000, "2024-01-15", brokerage, 0);
+    db.create_account_transfer(
+        checking,
+        brokerage,
+        50_000,
+        unix_date("2024-01-25"),
+        String::new(),
+    )
+    .unwrap();
+
+    let start = unix_date("2024-01-01");
+    let end = unix_date("2024-03-31");
+
+    let months: Vec<(i64, i64, i64)> = db
+        .get_income_expense_by_month(start, end)
+        .unwrap()
+        .iter()
+        .map(|m| (m.month, m.income, m.expense))
+        .collect();
+    assert_eq!(
+        months,
+        vec![
+            (202401, 300_000, 36_000),
+            (202402, 0, 0),
+            (202403, 300_000, 29_000)
+        ]
+    );
+
+    // Refunds come off, uncategorized outflows are their own line
+    assert_eq!(
+        db.get_spending_by_category(start, end).unwrap(),
+        vec![
+            CategorySpending {
+                category_id: groceries,
+                display_name: "groceries".to_string(),
+                amount: 50_000
+            },
+            CategorySpending {
+                category_id: dining,
+                display_name: "dining".to_string(),
+                amount: 14_000
+            },
+            CategorySpending {
+                category_id: UNCATEGORIZED_ID,
+                display_name: String::new(),
+                amount: 1_000
+            },
+        ]
+    );
+
+    let payees: Vec<(String, i64, i64)> = db
+        .get_top_payees(start, end, 2)
+        .unwrap()
+        .into_iter()
+        .map(|p| (p.payee, p.amount, p.transaction_count))
+        .collect();
+    assert_eq!(
+        payees,
+        vec![
+            ("Grocer".to_string(), 55_000, 3),
+            ("Cafe".to_string(), 14_000, 2)
+        ]
+    );
+
+    // February had nothing, so March is all new
+    let comparison = db.get_category_comparison(202403).unwrap();
+    assert_eq!(comparison.len(), 2);
+    assert_eq!((comparison[0].previous, comparison[0].current), (0, 20_000));
+
+    let comparison = db.get_category_comparison(202402).unwrap();
+    let changes: Vec<(i64, i64, i64)> = comparison
+        .iter()
+        .map(|c| (c.category_id, c.previous, c.current))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (groceries, 30_000, 0),
+            (dining, 5_000, 0),
+            (UNCATEGORIZED_ID, 1_000, 0)
+        ]
+    );
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn net_worth_history() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let card = db
+        .insert(Account::new_typed("visa", AccountType::CreditCard))
+        .unwrap();
+    let mortgage = db
+        .insert(Account::new_typed("mortgage", AccountType::Loan))
+        .unwrap();
+    let house = db
+        .insert(Account::new_typed("house", AccountType::Investment))
+        .unwrap();
+
+    let add = |amount: i64, date: &str, account_id: i64| {
+        db.insert(Transaction::new_raw(
+            "Balance".to_string(),
+            amount,
+            unix_date(date),
+            account_id,
+        ))
+        .unwrap();
+    };
+    add(500_000, "2024-01-01", checking);
+    add(-20_000, "2024-01-10", card);
+    add(30_000_000, "2024-01-01", house);
+    add(-25_000_000, "2024-01-01", mortgage);
+
+    // Nothing happens in February, March pays the card and some of the mortgage
+    db.create_account_transfer(
+        checking,
+        card,
+        20_000,
+        unix_date("2024-03-05"),
+        String::new(),
+    )
+    .unwrap();
+    db.create_account_transfer(
+        checking,
+        mortgage,
+        100_000,
+        unix_date("2024-03-06"),
+        String::new(),
+    )
+    .unwrap();
+    add(-5_000, "2024-03-07", card);
+
+    let history: Vec<(i64, i64, i64, i64)> = db
+        .get_net_worth_history(unix_date("2024-04-15"))
+        .unwrap()
+        .iter()
+        .map(|s| (s.month, s.assets, s.liabilities, s.net_worth()))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            (202401, 30_500_000, 25_020_000, 5_480_000),
+            (202402, 30_500_000, 25_020_000, 5_480_000),
+            (202403, 30_380_000, 24_905_000, 5_475_000),
+            (202404, 30_380_000, 24_905_000, 5_475_000),
+        ]
+    );
+
+    // Later transactions are left out
+    assert_eq!(
+        db.get_net_worth_history(unix_date("2024-01-31"))
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(db
+        .get_net_worth_history(unix_date("2023-12-31"))
+        .unwrap()
+        .is_empty());
+
+    test_remove_db(function!(), db);
+}
+
+#[test]
+fn account_forecasts() {
+    let db = test_setup_db(function!());
+
+    let checking = db.insert(Account::new("checking")).unwrap();
+    let groceries = db.get_or_create_category("Groceries").unwrap();
+    let rent = db.get_or_create_category("Rent").unwrap();
+
+    let add = |amount: i64, date: &str, category_id: i64| {
+        let mut trans =
+            Transaction::new_raw("Store".to_string(), amount, unix_date(date), checking);
+        trans.category_id = category_id;
+        db.insert(trans).unwrap();
+    };
+    add(300_000, "2024-01-01", 0);
+    add(-4_000, "2024-01-15", groceries);
+    add(-5_000, "2024-02-28", groceries);
+    add(-150_000, "2024-02-20", rent);
+
+    // Already entered, but not until after today
+    db.insert(Transaction::new_raw(
+        "Car Payment".to_string(),
+        -20_000,
+        unix_date("2024-03-18"),
+        checking,
+    ))
+    .unwrap();
+
+    let mut scheduled_rent = ScheduledTransaction::new(
+        "Landlord".to_string(),
+        -150_000,
+        checking,
+        Frequency::Monthly,
+        unix_date("2024-03-20"),
+    );
+    scheduled_rent.category_id = rent;
+    db.create_scheduled_transaction(scheduled_rent).unwrap();
+    db.create_scheduled_transaction(ScheduledTransaction::new(
+        "Paycheck".to_string(),
+        200_000,
+        checking,
+        Frequency::Biweekly,
+        unix_date("2024-03-29"),
+    ))
+    .unwrap();
+
+    let now = unix_date("2024-03-15");
+    let forecasts = db.get_account_forecasts(now, 30, false).unwrap();
+    assert_eq!(forecasts.len(), 1);
+    let forecast = &forecasts[0];
+    assert_eq!(forecast.starting_balance, 141_000);
+    let payees: Vec<&str> = forecast.entries.iter().map(|e| e.payee.as_str()).collect();
+    assert_eq!(
+        payees,
+        vec!["Car Payment", "Landlord", "Paycheck", "Paycheck"]
+    );
+    assert_eq!(
+        (forecast.low_balance, forecast.low_date),
+        (-29_000, unix_date("2024-03-20"))
+    );
+    assert_eq!(forecast.ending_balance, 371_000);
+
+    // 90 dollars of groceries over 90 days, rent is already scheduled
+    let forecast = &db.get_account_forecasts(now, 30, true).unwrap()[0];
+    assert_eq!(forecast.entries.len(), 34);
+    assert_eq!(
+        (forecast.low_balance, forecast.low_date),
+        (-30_300, unix_date("2024-03-28"))
+    );
+    assert_eq!(forecast.ending_balance, 368_000);
+
+    test_remove_db(function!(), db);
+}
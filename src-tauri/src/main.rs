@@ -13,7 +13,20 @@ use rusqlite::Result;
 
 use data::account::*;
 use data::category::*;
+use data::category_group::*;
+use data::category_target::CategoryTarget;
+use data::category_transfer::CategoryTransfer;
+use data::csv_export::CsvExportOptions;
+use data::forecast::AccountForecast;
+use data::import_batch::ImportBatch;
+use data::import_preview::*;
+use data::import_profile::ImportProfile;
+use data::payee_rule::PayeeRule;
+use data::report::*;
+use data::scheduled_transaction::*;
 use data::transaction::*;
+use data::transaction_query::*;
+use data::transaction_split::TransactionSplit;
 use data::{DatabaseInfo, RytError};
 
 use database::{Database, OrderBy};
@@ -24,6 +37,9 @@ use tauri::api::dialog;
 struct State {
     db: Database,
     persist_data: PersistentData,
+
+    // Why the last database couldn't be opened at launch, given to the frontend once
+    startup_error: Option<RytError>,
 }
 
 struct GuardedState {
@@ -49,8 +65,10 @@ fn build_ok() -> String {
 fn create_category(name: &str, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
     let state = ts.state.lock()?;
 
-    let cat = Category::new(name);
-    return state.db.insert(cat).map_err(rusqlite_to_ryt);
+    return state
+        .db
+        .get_or_create_category(name)
+        .map_err(rusqlite_to_ryt);
 }
 
 #[tauri::command]
@@ -68,17 +86,20 @@ fn get_category_id(name: &str, ts: tauri::State<GuardedState>) -> Result<i64, Ry
 
 #[tauri::command]
 // sb -> starting balance
-fn create_account(name: &str, sb: i64, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
+fn create_account(
+    name: &str,
+    sb: i64,
+    kind: AccountType,
+    budget: bool,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
     let state = ts.state.lock()?;
 
-    let mut account_id: i64;
-    let account = Account::new(name);
-    match state.db.insert(account) {
-        Err(v) => return Err(rusqlite_to_ryt(v)),
-        Ok(v) => account_id = v,
-    };
+    let mut account = Account::new_typed(name, kind);
+    account.on_budget = budget;
+    let account_id = state.db.create_account(account)?;
 
-    let starting_trans = Transaction::new(
+    let mut starting_trans = Transaction::new(
         "Starting Balance".to_string(),
         sb,
         0,
@@ -86,15 +107,178 @@ fn create_account(name: &str, sb: i64, ts: tauri::State<GuardedState>) -> Result
         account_id,
     )?;
 
+    // Already on the bank's books
+    starting_trans.cleared = ClearedStatus::Cleared;
+
     return state.db.insert(starting_trans).map_err(rusqlite_to_ryt);
 }
 
+#[tauri::command]
+fn rename_account(name: String, acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.rename_account(acc, name);
+}
+
+#[tauri::command]
+fn close_account(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.close_account(acc);
+}
+
+#[tauri::command]
+fn reopen_account(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.reopen_account(acc);
+}
+
+#[tauri::command]
+fn delete_account(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.delete_account(acc);
+}
+
+#[tauri::command]
+fn get_closed_account_display_list(
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<AccountDisplay>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_closed_account_display_list()
+        .map_err(rusqlite_to_ryt);
+}
+
 #[tauri::command]
 fn create_transaction(trans: Transaction, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
     let state = ts.state.lock()?;
     return state.db.insert(trans).map_err(rusqlite_to_ryt);
 }
 
+#[tauri::command]
+fn update_transaction(trans: Transaction, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.update_transaction(trans);
+}
+
+#[tauri::command]
+fn create_split_transaction(
+    trans: Transaction,
+    splits: Vec<TransactionSplit>,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.insert_split_transaction(trans, splits);
+}
+
+#[tauri::command]
+fn update_split_transaction(
+    trans: Transaction,
+    splits: Vec<TransactionSplit>,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.update_split_transaction(trans, splits);
+}
+
+#[tauri::command]
+fn create_account_transfer(
+    from: i64,
+    to: i64,
+    amount: i64,
+    date: i64,
+    notes: String,
+    ts: tauri::State<GuardedState>,
+) -> Result<(i64, i64), RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .create_account_transfer(from, to, amount, date, notes);
+}
+
+#[tauri::command]
+fn get_scheduled_transactions(
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<ScheduledTransaction>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_all(OrderBy::None).map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+// Occurrences already due are made into transactions straight away
+fn create_scheduled_transaction(
+    scheduled: ScheduledTransaction,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    let id = state.db.create_scheduled_transaction(scheduled)?;
+    state.db.create_due_scheduled(Utc::now().timestamp())?;
+    return Ok(id);
+}
+
+#[tauri::command]
+fn delete_scheduled_transaction(sid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .delete_scheduled_transaction(sid)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_upcoming_transactions(
+    acc: i64,
+    until: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<UpcomingTransaction>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_upcoming_transactions(acc, until)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_account_forecasts(
+    now: i64,
+    days: i64,
+    spending: bool,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<AccountForecast>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_account_forecasts(now, days, spending)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn set_transaction_cleared(
+    tid: i64,
+    cleared: ClearedStatus,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.set_transaction_cleared(tid, cleared);
+}
+
+#[tauri::command]
+// date -> statement date, balance -> statement ending balance
+fn reconcile_account(
+    acc: i64,
+    date: i64,
+    balance: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<ReconcileSummary, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.reconcile_account(acc, date, balance);
+}
+
+#[tauri::command]
+fn delete_transaction(tid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.delete_transaction(tid);
+}
+
 #[tauri::command]
 fn rename_category(name: String, cid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
     let state = ts.state.lock()?;
@@ -112,19 +296,251 @@ fn get_all_transactions_display(
         .map_err(rusqlite_to_ryt);
 }
 
+#[tauri::command]
+fn query_transactions(
+    query: TransactionQuery,
+    ts: tauri::State<GuardedState>,
+) -> Result<TransactionPage, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.query_transactions(&query).map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_categories(ts: tauri::State<GuardedState>) -> Result<Vec<Category>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_all(OrderBy::None).map_err(rusqlite_to_ryt);
+}
+
 #[tauri::command]
 fn get_category_display_list(
     start: i64,
     end: i64,
+    month: i64,
     ts: tauri::State<GuardedState>,
 ) -> Result<Vec<CategoryDisplay>, RytError> {
     let state = ts.state.lock()?;
     return state
         .db
-        .get_category_display_list(start, end)
+        .get_category_display_list(start, end, month)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_category_groups(ts: tauri::State<GuardedState>) -> Result<Vec<CategoryGroup>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_category_groups().map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_category_group_display_list(
+    start: i64,
+    end: i64,
+    month: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<CategoryGroupDisplay>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_category_group_display_list(start, end, month)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn create_category_group(name: &str, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .create_category_group(name)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn rename_category_group(
+    name: &str,
+    gid: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.rename_category_group(gid, name);
+}
+
+#[tauri::command]
+fn delete_category_group(gid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.delete_category_group(gid);
+}
+
+#[tauri::command]
+fn set_category_group_collapsed(
+    gid: i64,
+    collapsed: bool,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.set_category_group_collapsed(gid, collapsed);
+}
+
+#[tauri::command]
+fn set_category_group(cid: i64, gid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.set_category_group(cid, gid);
+}
+
+#[tauri::command]
+fn move_category_group(gid: i64, up: bool, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.move_category_group(gid, up);
+}
+
+#[tauri::command]
+fn move_category(cid: i64, up: bool, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.move_category(cid, up);
+}
+
+#[tauri::command]
+fn set_category_target(
+    target: CategoryTarget,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.set_category_target(target);
+}
+
+#[tauri::command]
+fn delete_category_target(cid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .delete_category_target(cid)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn assign_to_targets(
+    start: i64,
+    end: i64,
+    month: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.assign_to_targets(start, end, month);
+}
+
+#[tauri::command]
+fn assign_category(
+    cid: i64,
+    month: i64,
+    amount: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .assign_category(cid, month, amount)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn transfer_between_categories(
+    source: i64,
+    dest: i64,
+    amount: i64,
+    month: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .transfer_between_categories(CategoryTransfer::new(source, dest, amount, month));
+}
+
+#[tauri::command]
+fn get_category_transfers(
+    cid: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<CategoryTransfer>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_category_transfers(cid)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_ready_to_assign(
+    end: i64,
+    month: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_ready_to_assign(end, month)
         .map_err(rusqlite_to_ryt);
 }
 
+#[tauri::command]
+fn get_income_expense_by_month(
+    start: i64,
+    end: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<MonthlyIncomeExpense>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_income_expense_by_month(start, end)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_spending_by_category(
+    start: i64,
+    end: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<CategorySpending>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_spending_by_category(start, end)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_top_payees(
+    start: i64,
+    end: i64,
+    limit: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<PayeeSpending>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_top_payees(start, end, limit)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_category_comparison(
+    month: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<CategoryComparison>, RytError> {
+    let state = ts.state.lock()?;
+    return state
+        .db
+        .get_category_comparison(month)
+        .map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn get_net_worth_history(
+    end: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<NetWorthSnapshot>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_net_worth_history(end).map_err(rusqlite_to_ryt);
+}
+
 #[tauri::command]
 fn get_account_display_list(
     ts: tauri::State<GuardedState>,
@@ -143,7 +559,62 @@ fn get_account_history(
 }
 
 #[tauri::command]
-fn import(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+fn get_import_profiles(ts: tauri::State<GuardedState>) -> Result<Vec<ImportProfile>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_all(OrderBy::None).map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn create_import_profile(
+    profile: ImportProfile,
+    ts: tauri::State<GuardedState>,
+) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.create_import_profile(profile);
+}
+
+#[tauri::command]
+fn delete_import_profile(pid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    state
+        .db
+        .delete::<ImportProfile>(pid)
+        .map_err(rusqlite_to_ryt)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_payee_rules(ts: tauri::State<GuardedState>) -> Result<Vec<PayeeRule>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_payee_rules().map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+fn create_payee_rule(rule: PayeeRule, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.create_payee_rule(rule);
+}
+
+#[tauri::command]
+fn delete_payee_rule(rid: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.delete_payee_rule(rid).map_err(rusqlite_to_ryt);
+}
+
+#[tauri::command]
+// Returns how many transactions changed
+fn apply_payee_rules(ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.apply_payee_rules();
+}
+
+#[tauri::command]
+// profile -> import profile id describing the file layout
+fn import_preview(
+    acc: i64,
+    profile: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<ImportPreview, RytError> {
     // Show dialog
     let file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
         .add_filter("CSV", &["csv"])
@@ -157,7 +628,67 @@ fn import(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
         .ok_or(RytError::PathBufToStringFail)?;
 
     let state = ts.state.lock()?;
-    return state.db.import(selected_file_path, acc);
+    return state.db.import_preview(selected_file_path, acc, profile);
+}
+
+#[tauri::command]
+fn import_ofx_preview(acc: i64, ts: tauri::State<GuardedState>) -> Result<ImportPreview, RytError> {
+    // Show dialog
+    let file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .add_filter("OFX", &["ofx", "qfx"])
+        .pick_file()
+        .ok_or(RytError::PickFileNone)?;
+
+    // path buf to string
+    let selected_file_path = file_path_buf
+        .as_path()
+        .to_str()
+        .ok_or(RytError::PathBufToStringFail)?;
+
+    let state = ts.state.lock()?;
+    return state.db.import_ofx_preview(selected_file_path, acc);
+}
+
+#[tauri::command]
+// Writes straight to the database, QIF files name their own accounts.
+// acc -> account for sections which don't
+fn import_qif(acc: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    // Show dialog
+    let file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .add_filter("QIF", &["qif"])
+        .pick_file()
+        .ok_or(RytError::PickFileNone)?;
+
+    // path buf to string
+    let selected_file_path = file_path_buf
+        .as_path()
+        .to_str()
+        .ok_or(RytError::PathBufToStringFail)?;
+
+    let state = ts.state.lock()?;
+    return state.db.import_qif(selected_file_path, acc);
+}
+
+#[tauri::command]
+// Returns the import batch id
+fn import_commit(preview: ImportPreview, ts: tauri::State<GuardedState>) -> Result<i64, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.import_commit(preview);
+}
+
+#[tauri::command]
+fn import_rollback(batch: i64, ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+    return state.db.import_rollback(batch);
+}
+
+#[tauri::command]
+fn get_import_batches(
+    acc: i64,
+    ts: tauri::State<GuardedState>,
+) -> Result<Vec<ImportBatch>, RytError> {
+    let state = ts.state.lock()?;
+    return state.db.get_import_batches(acc).map_err(rusqlite_to_ryt);
 }
 
 #[tauri::command]
@@ -170,6 +701,16 @@ fn get_db_info(ts: tauri::State<GuardedState>) -> Result<DatabaseInfo, RytError>
     });
 }
 
+// Err with why the last database didn't open at launch, only the first time it's asked
+#[tauri::command]
+fn take_startup_error(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let mut state = ts.state.lock()?;
+    return match state.startup_error.take() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    };
+}
+
 #[tauri::command]
 fn create_db(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
     let mut state = ts.state.lock()?;
@@ -179,7 +720,7 @@ fn create_db(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
         .ok_or(RytError::PickFileNone)?;
 
     file_path_buf.set_extension("db3");
-    state.db = Database::new(file_path_buf, &mut state.persist_data, true);
+    state.db = Database::new(file_path_buf, &mut state.persist_data, true)?;
 
     Ok(())
 }
@@ -193,21 +734,75 @@ fn open_db(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
         .pick_file()
         .ok_or(RytError::PickFileNone)?;
 
-    state.db = Database::new(file_path_buf, &mut state.persist_data, true);
+    state.db = Database::new(file_path_buf, &mut state.persist_data, true)?;
 
     Ok(())
 }
 
 #[tauri::command]
-fn export_to_csv(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
-    let mut state = ts.state.lock()?;
+fn export_to_csv(
+    options: CsvExportOptions,
+    ts: tauri::State<GuardedState>,
+) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
 
     let mut file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
         .save_file()
         .ok_or(RytError::PickFileNone)?;
 
     file_path_buf.set_extension("csv");
-    state.db.export_csv(file_path_buf).unwrap();
+    return state.db.export_csv(file_path_buf, &options);
+}
+
+#[tauri::command]
+fn export_to_qif(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+
+    let mut file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .save_file()
+        .ok_or(RytError::PickFileNone)?;
+
+    file_path_buf.set_extension("qif");
+    return state.db.export_qif(file_path_buf);
+}
+
+#[tauri::command]
+fn backup_db(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let state = ts.state.lock()?;
+
+    let mut file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .save_file()
+        .ok_or(RytError::PickFileNone)?;
+
+    file_path_buf.set_extension("json");
+    return state.db.backup(file_path_buf);
+}
+
+#[tauri::command]
+fn restore_db(ts: tauri::State<GuardedState>) -> Result<(), RytError> {
+    let mut state = ts.state.lock()?;
+
+    let backup_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .add_filter("JSON", &["json"])
+        .pick_file()
+        .ok_or(RytError::PickFileNone)?;
+
+    // The restore goes into a new file, the open one is left alone
+    let mut file_path_buf: PathBuf = dialog::blocking::FileDialogBuilder::new()
+        .save_file()
+        .ok_or(RytError::PickFileNone)?;
+    file_path_buf.set_extension("db3");
+
+    if file_path_buf == PathBuf::from(&state.db.folder_dir).join(&state.db.file_name) {
+        return Err(RytError::RestoreOverOpenDatabase);
+    }
+
+    state.db = Database::restore(
+        backup_path_buf,
+        file_path_buf,
+        &mut state.persist_data,
+        true,
+    )?;
 
     Ok(())
 }
@@ -220,16 +815,25 @@ fn main() {
         persist_data.last_db_path = "C:/Digital Archive/db.db3".to_string();
     }
 
-    let db = Database::new(
+    // A file which won't open, like one from a newer version, leaves an empty database open
+    // until the user picks another file
+    let (db, startup_error) = match Database::new(
         PathBuf::from(&persist_data.last_db_path),
         &mut persist_data,
         true,
-    );
+    ) {
+        Ok(v) => (v, None),
+        Err(e) => {
+            println!("Opening the last db failed: {e}");
+            (Database::new_in_memory().unwrap(), Some(e))
+        }
+    };
 
     let guarded_state = GuardedState {
         state: Mutex::new(State {
             persist_data: persist_data,
             db: db,
+            startup_error,
         }),
     };
 
@@ -238,17 +842,73 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             create_category,
             create_account,
+            rename_account,
+            close_account,
+            reopen_account,
+            delete_account,
+            get_closed_account_display_list,
             create_transaction,
+            update_transaction,
+            create_split_transaction,
+            update_split_transaction,
+            create_account_transfer,
+            get_scheduled_transactions,
+            create_scheduled_transaction,
+            delete_scheduled_transaction,
+            get_upcoming_transactions,
+            get_account_forecasts,
+            set_transaction_cleared,
+            reconcile_account,
+            delete_transaction,
             get_all_transactions_display,
+            query_transactions,
             get_category_id,
+            get_categories,
             get_category_display_list,
+            get_category_groups,
+            get_category_group_display_list,
+            create_category_group,
+            rename_category_group,
+            delete_category_group,
+            set_category_group_collapsed,
+            set_category_group,
+            move_category_group,
+            move_category,
+            set_category_target,
+            delete_category_target,
+            assign_to_targets,
+            assign_category,
+            get_ready_to_assign,
+            transfer_between_categories,
+            get_category_transfers,
+            get_income_expense_by_month,
+            get_spending_by_category,
+            get_top_payees,
+            get_category_comparison,
+            get_net_worth_history,
             get_account_display_list,
             get_account_history,
-            import,
+            import_preview,
+            import_ofx_preview,
+            import_commit,
+            import_qif,
+            import_rollback,
+            get_import_batches,
+            get_import_profiles,
+            create_import_profile,
+            delete_import_profile,
+            get_payee_rules,
+            create_payee_rule,
+            delete_payee_rule,
+            apply_payee_rules,
             get_db_info,
+            take_startup_error,
             create_db,
             open_db,
             export_to_csv,
+            export_to_qif,
+            backup_db,
+            restore_db,
             delete_category,
             rename_category,
         ])
@@ -2,8 +2,11 @@
 
 mod categories;
 mod error_modal;
+mod export_modal;
 mod home;
 mod nav;
+mod reports;
+mod rules;
 mod transactions;
 
 use leptos::leptos_dom::ev::SubmitEvent;
@@ -35,6 +38,7 @@ mod js {
     extern "C" {
         pub fn show_error() -> JsValue;
         pub fn build_graph(element_id: String, data: Vec<f64>);
+        pub fn build_chart(element_id: String, option: String);
         pub fn reload_page();
     }
 }
@@ -78,6 +82,8 @@ pub fn App() -> impl IntoView {
                             <Route path="/" view=home::Home/>
                             <Route path="/transactions" view=transactions::Transactions/>
                             <Route path="/categories" view=categories::Categories/>
+                            <Route path="/rules" view=rules::Rules/>
+                            <Route path="/reports" view=reports::Reports/>
                         </Routes>
 
                        <error_modal::ErrorModal/>
@@ -12,12 +12,16 @@ use serde_wasm_bindgen::{from_value, to_value};
 
 use crate::app::error_modal;
 use data::category::*;
+use data::category_group::*;
+use data::category_target::*;
+use data::category_transfer::CategoryTransfer;
 use data::transaction::*;
 use data::RytError;
 
 use chrono::prelude::*;
 
-async fn get_category_list(year: i32, month: u32) -> Vec<CategoryDisplay> {
+// Unix start and end of a month
+fn month_range(year: i32, month: u32) -> (i64, i64) {
     let mut date_start = chrono::Utc
         .with_ymd_and_hms(year, month, 1, 0, 0, 0)
         .unwrap();
@@ -37,35 +41,114 @@ async fn get_category_list(year: i32, month: u32) -> Vec<CategoryDisplay> {
         .checked_sub_signed(chrono::TimeDelta::seconds(1))
         .unwrap();
 
+    return (date_start.timestamp(), date_end.timestamp());
+}
+
+async fn get_category_list(year: i32, month: u32) -> Vec<CategoryGroupDisplay> {
     #[derive(Serialize, Deserialize)]
     struct Args {
         start: i64,
         end: i64,
+        month: i64,
     }
 
-    let start = date_start.timestamp();
-    let end = date_end.timestamp();
-
-    let args = to_value(&Args {
-        start: start,
-        end: end,
-    })
-    .unwrap();
+    let (start, end) = month_range(year, month);
 
     let res = tauri::invoke(
-        "get_category_display_list",
+        "get_category_group_display_list",
         &Args {
             start: start,
             end: end,
+            month: data::month_key(year, month),
         },
     )
     .await;
-    let ret: Result<Vec<CategoryDisplay>, RytError> = super::convert_invoke(res);
+    let ret: Result<Vec<CategoryGroupDisplay>, RytError> = super::convert_invoke(res);
 
     // TODO handle error
     return ret.unwrap();
 }
 
+async fn get_ready_to_assign(year: i32, month: u32) -> i64 {
+    #[derive(Serialize, Deserialize)]
+    struct Args {
+        end: i64,
+        month: i64,
+    }
+
+    let (start, end) = month_range(year, month);
+
+    let res = tauri::invoke(
+        "get_ready_to_assign",
+        &Args {
+            end: end,
+            month: data::month_key(year, month),
+        },
+    )
+    .await;
+    let ret: Result<i64, RytError> = super::convert_invoke(res);
+
+    // TODO handle error
+    return ret.unwrap();
+}
+
+async fn get_category_transfers(category_id: i64) -> Vec<CategoryTransfer> {
+    #[derive(Serialize, Deserialize)]
+    struct Args {
+        cid: i64,
+    }
+
+    let res = tauri::invoke("get_category_transfers", &Args { cid: category_id }).await;
+    let ret: Result<Vec<CategoryTransfer>, RytError> = super::convert_invoke(res);
+
+    // TODO handle error
+    return ret.unwrap();
+}
+
+// YYYYMM month key to "Jan 2024"
+pub fn month_key_to_display(key: i64) -> String {
+    match chrono::Utc
+        .with_ymd_and_hms((key / 100) as i32, (key % 100) as u32, 1, 0, 0, 0)
+        .single()
+    {
+        Some(date) => date.format("%b %Y").to_string(),
+        None => String::new(),
+    }
+}
+
+// Arguments of the category group commands, each takes the ones it needs
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct GroupArgs {
+    gid: i64,
+    cid: i64,
+    up: bool,
+    collapsed: bool,
+    name: String,
+}
+
+// "2024-03" from a month input to 202403
+fn month_input_to_key(value: &str) -> Option<i64> {
+    let (year, month) = value.split_once('-')?;
+    return Some(data::month_key(
+        year.parse::<i32>().ok()?,
+        month.parse::<u32>().ok()?,
+    ));
+}
+
+// Refresh everything shown for the month
+async fn load_month(
+    year: i32,
+    month: u32,
+    categories_set: WriteSignal<Vec<CategoryGroupDisplay>>,
+    ready_to_assign_set: WriteSignal<i64>,
+) {
+    let lst = get_category_list(year, month).await;
+    categories_set.set(lst);
+
+    let ready = get_ready_to_assign(year, month).await;
+    ready_to_assign_set.set(ready);
+}
+
 #[component]
 pub fn Categories() -> impl IntoView {
     let global_state = expect_context::<RwSignal<super::GlobalState>>();
@@ -77,6 +160,23 @@ pub fn Categories() -> impl IntoView {
 
     let (category_id_selected, category_id_selected_set) = create_signal(0);
 
+    let transfers = create_signal::<Vec<CategoryTransfer>>(vec![]);
+    create_resource(
+        move || category_id_selected.get(),
+        move |cat_id| async move {
+            if cat_id == 0 {
+                transfers.1.set(vec![]);
+                return;
+            }
+
+            let lst = get_category_transfers(cat_id).await;
+            transfers.1.set(lst);
+        },
+    );
+
+    let transfer_amount_input: NodeRef<html::Input> = create_node_ref();
+    let transfer_dest_input: NodeRef<html::Select> = create_node_ref();
+
     let handle = window_event_listener(ev::keydown, move |ev| {
         let code = ev.key_code();
         if code == 27 {
@@ -84,21 +184,21 @@ pub fn Categories() -> impl IntoView {
         }
     });
 
-    let categories = create_signal::<Vec<CategoryDisplay>>(vec![]);
+    let categories = create_signal::<Vec<CategoryGroupDisplay>>(vec![]);
+    let (ready_to_assign, ready_to_assign_set) = create_signal::<i64>(0);
     create_resource(
         || (),
         move |_| async move {
-            let lst = get_category_list(
+            load_month(
                 year_selected.get_untracked(),
                 month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
             )
             .await;
-            categories.1.set(lst);
         },
     );
 
-    let (unassigned, unassigned_set) = create_signal(100.0);
-
     let (name, set_name) = create_signal(String::new());
     let update_name = move |ev| {
         let v = event_target_value(&ev);
@@ -123,15 +223,223 @@ pub fn Categories() -> impl IntoView {
             // TODO handle error
             let ret: Result<i64, RytError> = super::convert_invoke(res);
 
-            let lst = get_category_list(
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let (group_name, set_group_name) = create_signal(String::new());
+    let update_group_name = move |ev| {
+        let v = event_target_value(&ev);
+        set_group_name.set(v);
+    };
+
+    // Creating, renaming, collapsing and reordering groups and categories
+    let change_groups = move |command: &'static str, args: GroupArgs| {
+        spawn_local(async move {
+            let res = tauri::invoke(command, &args).await;
+            let ret: Result<(), RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let create_group = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        spawn_local(async move {
+            let name = group_name.get_untracked();
+            if name.is_empty() {
+                return;
+            }
+
+            let res = tauri::invoke(
+                "create_category_group",
+                &GroupArgs {
+                    name: name,
+                    ..GroupArgs::default()
+                },
+            )
+            .await;
+            let ret: Result<i64, RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let (group_id_selected, group_id_selected_set) = create_signal(0);
+    let rename_group_input: NodeRef<html::Input> = create_node_ref();
+
+    let target_kind_input: NodeRef<html::Select> = create_node_ref();
+    let target_amount_input: NodeRef<html::Input> = create_node_ref();
+    let target_month_input: NodeRef<html::Input> = create_node_ref();
+
+    let save_target = move |cat_id: i64| {
+        spawn_local(async move {
+            let kind = match target_kind_input
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<i64>()
+                .ok()
+                .and_then(TargetKind::from_i64)
+            {
+                Some(v) => v,
+                None => return,
+            };
+
+            // Entered in dollars
+            let dollars: f64 = match target_amount_input
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<f64>()
+            {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(
+                        "Error parsing target amount".to_string(),
+                        &global_state,
+                    );
+                    return;
+                }
+            };
+
+            let mut target = CategoryTarget::new(cat_id, kind, data::dollars_to_cents(dollars));
+            if kind == TargetKind::BalanceByDate {
+                target.target_month =
+                    month_input_to_key(&target_month_input.get_untracked().unwrap().value());
+            }
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                target: CategoryTarget,
+            }
+
+            let res = tauri::invoke("set_category_target", &Args { target }).await;
+            let ret: Result<(), RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let remove_target = move |cat_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                cid: i64,
+            }
+
+            let res = tauri::invoke("delete_category_target", &Args { cid: cat_id }).await;
+            let ret: Result<(), RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
                 year_selected.get_untracked(),
                 month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
             )
             .await;
-            categories.1.set(lst);
         });
     };
 
+    // Assign every underfunded category what its target needs this month
+    let assign_targets = move |_| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                start: i64,
+                end: i64,
+                month: i64,
+            }
+
+            let year = year_selected.get_untracked();
+            let month = month_selected.get_untracked();
+            let (start, end) = month_range(year, month);
+
+            let res = tauri::invoke(
+                "assign_to_targets",
+                &Args {
+                    start: start,
+                    end: end,
+                    month: data::month_key(year, month),
+                },
+            )
+            .await;
+            let ret: Result<i64, RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(year, month, categories.1, ready_to_assign_set).await;
+        });
+    };
+
+    // Total still needed by targets this month
+    let targets_needed = move || -> i64 {
+        return categories
+            .0
+            .get()
+            .iter()
+            .flat_map(|g| g.categories.iter())
+            .map(|c| c.target_needed)
+            .sum();
+    };
+
     let delete_category = move |cat_id: i64| {
         spawn_local(async move {
             log!("deleting category {cat_id}");
@@ -145,12 +453,13 @@ pub fn Categories() -> impl IntoView {
             // TODO handle error
             let ret: Result<i64, RytError> = super::convert_invoke(res);
 
-            let lst = get_category_list(
+            load_month(
                 year_selected.get_untracked(),
                 month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
             )
             .await;
-            categories.1.set(lst);
 
             category_id_selected_set.set(0);
         });
@@ -189,12 +498,140 @@ pub fn Categories() -> impl IntoView {
                 _ => {}
             }
 
-            let lst = get_category_list(
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let assign_category = move |cat_id: i64, value: String| {
+        spawn_local(async move {
+            // Entered in dollars, empty clears the assignment
+            let dollars: f64 = match value.trim().parse::<f64>() {
+                Ok(v) => v,
+                Err(v) => {
+                    if !value.trim().is_empty() {
+                        error_modal::show_error(
+                            "Error parsing assigned amount".to_string(),
+                            &global_state,
+                        );
+                        return;
+                    }
+                    0.0
+                }
+            };
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                cid: i64,
+                month: i64,
+                amount: i64,
+            }
+
+            let res = tauri::invoke(
+                "assign_category",
+                &Args {
+                    cid: cat_id,
+                    month: data::month_key(
+                        year_selected.get_untracked(),
+                        month_selected.get_untracked(),
+                    ),
+                    amount: data::dollars_to_cents(dollars),
+                },
+            )
+            .await;
+            let ret: Result<(), RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
+                year_selected.get_untracked(),
+                month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
+            )
+            .await;
+        });
+    };
+
+    let move_money = move |source_id: i64| {
+        spawn_local(async move {
+            let dest_id: i64 = match transfer_dest_input
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<i64>()
+            {
+                Ok(v) => v,
+                Err(v) => return,
+            };
+
+            // Entered in dollars
+            let dollars: f64 = match transfer_amount_input
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<f64>()
+            {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(
+                        "Error parsing transfer amount".to_string(),
+                        &global_state,
+                    );
+                    return;
+                }
+            };
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                source: i64,
+                dest: i64,
+                amount: i64,
+                month: i64,
+            }
+
+            let res = tauri::invoke(
+                "transfer_between_categories",
+                &Args {
+                    source: source_id,
+                    dest: dest_id,
+                    amount: data::dollars_to_cents(dollars),
+                    month: data::month_key(
+                        year_selected.get_untracked(),
+                        month_selected.get_untracked(),
+                    ),
+                },
+            )
+            .await;
+            let ret: Result<i64, RytError> = super::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            load_month(
                 year_selected.get_untracked(),
                 month_selected.get_untracked(),
+                categories.1,
+                ready_to_assign_set,
             )
             .await;
-            categories.1.set(lst);
+
+            let lst = get_category_transfers(source_id).await;
+            transfers.1.set(lst);
         });
     };
 
@@ -213,8 +650,7 @@ pub fn Categories() -> impl IntoView {
                     });
 
                     spawn_local(async move {
-                        let lst = get_category_list(year_selected.get_untracked(), month_selected.get_untracked()).await;
-                        categories.1.set(lst);
+                        load_month(year_selected.get_untracked(), month_selected.get_untracked(), categories.1, ready_to_assign_set).await;
                     });
                 }
             >
@@ -249,8 +685,7 @@ pub fn Categories() -> impl IntoView {
                     });
 
                     spawn_local(async move {
-                        let lst = get_category_list(year_selected.get_untracked(), month_selected.get_untracked()).await;
-                        categories.1.set(lst);
+                        load_month(year_selected.get_untracked(), month_selected.get_untracked(), categories.1, ready_to_assign_set).await;
                     });
                 }
             >
@@ -260,7 +695,20 @@ pub fn Categories() -> impl IntoView {
             </button>
         </div>
 
-
+        <div class="rounded-3 p-3 px-4 mb-3"
+            class:bg-success-subtle = move || ready_to_assign.get() >= 0
+            class:bg-danger-subtle = move || ready_to_assign.get() < 0
+        >
+            <h2>{move || data::amount_to_display(ready_to_assign.get())}</h2>
+            <p class="text-secondary mb-0">"Ready to Assign"</p>
+            {
+                move || (targets_needed() > 0).then(|| view! {
+                    <button type="button" class="btn btn-outline-primary btn-sm mt-2" on:click=assign_targets>
+                        "Assign " {data::amount_to_display(targets_needed())} " to Meet All Targets"
+                    </button>
+                })
+            }
+        </div>
 
             <div class="row">
             <div class="col-8">
@@ -269,13 +717,68 @@ pub fn Categories() -> impl IntoView {
                     <thead>
                         <tr>
                             <th scope="col">Category</th>
+                            <th scope="col">Assigned</th>
                             <th scope="col">Spending</th>
+                            <th scope="col">Available</th>
                         </tr>
                     </thead>
                     <tbody>
                     {
                         move || {
                             categories.0.get().into_iter().map(
+                            |group| {
+                            let group_id = group.group.id;
+                            let collapsed = group.group.collapsed;
+
+                            // Ungrouped categories are listed first, without a heading
+                            let heading = (group_id != UNGROUPED_ID).then(|| view! {
+                                <tr class="table-secondary">
+                                    <th scope="row">
+                                        <button type="button" class="btn btn-link btn-sm p-0 me-2"
+                                            on:click = move |_| {
+                                                change_groups("set_category_group_collapsed", GroupArgs { gid: group_id, collapsed: !collapsed, ..GroupArgs::default() });
+                                            }
+                                        >
+                                            {if collapsed { "+" } else { "-" }}
+                                        </button>
+                                        {group.group.display_name.clone()}
+                                        <span class="float-end">
+                                            <button type="button" class="btn btn-link btn-sm p-0 me-1"
+                                                on:click = move |_| {
+                                                    change_groups("move_category_group", GroupArgs { gid: group_id, up: true, ..GroupArgs::default() });
+                                                }
+                                            >
+                                                "Up"
+                                            </button>
+                                            <button type="button" class="btn btn-link btn-sm p-0 me-1"
+                                                on:click = move |_| {
+                                                    change_groups("move_category_group", GroupArgs { gid: group_id, up: false, ..GroupArgs::default() });
+                                                }
+                                            >
+                                                "Down"
+                                            </button>
+                                            <button type="button" class="btn btn-link btn-sm p-0" data-bs-toggle="modal" data-bs-target="#category_group_modal"
+                                                on:click = move |_| {
+                                                    group_id_selected_set.set(group_id);
+                                                }
+                                            >
+                                                "Edit"
+                                            </button>
+                                        </span>
+                                    </th>
+                                    <th>{data::amount_to_display(group.assigned)}</th>
+                                    <th>{data::amount_to_display(group.transaction_total * -1)}</th>
+                                    <th class:text-danger = group.available < 0>{data::amount_to_display(group.available)}</th>
+                                </tr>
+                            });
+
+                            // A collapsed group only shows its subtotals
+                            let rows = match collapsed {
+                                true => vec![],
+                                false => group.categories,
+                            };
+
+                            let rows = rows.into_iter().map(
                             |val| {
                                 view!{
                                     <tr on:click = move |ev| {
@@ -285,6 +788,59 @@ pub fn Categories() -> impl IntoView {
                                         class:highlight = move || category_id_selected.get() == val.category_id
                                         >
                                             {val.display_name}
+                                            {
+                                                // Filled by spending on the card rather than by assigning
+                                                (val.payment_account_id != 0).then(|| view! {
+                                                    <span class="badge text-bg-secondary ms-2">"Card Payment"</span>
+                                                })
+                                            }
+                                            {
+                                                (val.target_needed > 0).then(|| view! {
+                                                    <span class="badge text-bg-warning ms-2">"Underfunded " {data::amount_to_display(val.target_needed)}</span>
+                                                })
+                                            }
+                                            {
+                                                val.target.clone().map(|t| {
+                                                    let percent = t.progress_percent(data::month_key(year_selected.get_untracked(), month_selected.get_untracked()), val.assigned, val.available);
+                                                    view! {
+                                                        <div class="progress mt-1" style="height: 4px;">
+                                                            <div class="progress-bar"
+                                                                class:bg-success = percent >= 100
+                                                                style={format!("width: {}%", percent)}
+                                                            ></div>
+                                                        </div>
+                                                    }
+                                                })
+                                            }
+                                            <span class="float-end">
+                                                <button type="button" class="btn btn-link btn-sm p-0 me-1"
+                                                    on:click = move |ev| {
+                                                        ev.stop_propagation();
+                                                        change_groups("move_category", GroupArgs { cid: val.category_id, up: true, ..GroupArgs::default() });
+                                                    }
+                                                >
+                                                    "Up"
+                                                </button>
+                                                <button type="button" class="btn btn-link btn-sm p-0"
+                                                    on:click = move |ev| {
+                                                        ev.stop_propagation();
+                                                        change_groups("move_category", GroupArgs { cid: val.category_id, up: false, ..GroupArgs::default() });
+                                                    }
+                                                >
+                                                    "Down"
+                                                </button>
+                                            </span>
+                                        </td>
+
+                                        <td
+                                        class:highlight = move || category_id_selected.get() == val.category_id
+                                        >
+                                            <input class="form-control form-control-sm" type="number"
+                                                value={format!("{:.2}", data::cents_to_dollars(val.assigned))}
+                                                on:change = move |ev| {
+                                                    assign_category(val.category_id, event_target_value(&ev));
+                                                }
+                                            />
                                         </td>
 
                                         <td
@@ -292,9 +848,23 @@ pub fn Categories() -> impl IntoView {
                                         >
                                             {data::amount_to_display(val.transaction_total * -1)}
                                         </td>
+
+                                        <td
+                                        class:highlight = move || category_id_selected.get() == val.category_id
+                                        class:text-danger = val.available < 0
+                                        >
+                                            {data::amount_to_display(val.available)}
+                                        </td>
                                     </tr>
                                 }
 
+                            }
+                            ).collect_view();
+
+                            view! {
+                                {heading}
+                                {rows}
+                            }
                             }
                             ).collect_view()
                         }
@@ -317,6 +887,60 @@ pub fn Categories() -> impl IntoView {
 
                 </form>
 
+                <form class="row row-cols-lg-auto mt-2" on:submit=create_group>
+                    <div class="col-12">
+                    <input
+                        class="form-control"
+                        placeholder="Enter a group name..."
+                        on:input=update_group_name
+                    />
+                    </div>
+
+                    <div class="col-12">
+                    <button class="btn btn-outline-primary" type="submit">"Add Group"</button>
+                    </div>
+
+                </form>
+
+                <div class="modal fade" id="category_group_modal" tabindex="-1" aria-labelledby="category_group_modal" aria-hidden="true">
+                  <div class="modal-dialog modal-dialog-centered">
+                    <div class="modal-content">
+                      <div class="modal-header">
+                        <h1 class="modal-title fs-5">"Category Group"</h1>
+                        <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+                      </div>
+
+                      <div class="modal-body">
+                        <label for="category_group_name" class="col-form-label">"Group Name"</label>
+                        <input type="text" class="form-control" id="category_group_name" node_ref=rename_group_input/>
+                        <p class="text-secondary mt-2 mb-0">"Deleting a group keeps its categories."</p>
+                      </div>
+
+                      <div class="modal-footer">
+                        <button type="button" class="btn btn-outline-danger me-auto" data-bs-dismiss="modal"
+                            on:click = move |_| {
+                                change_groups("delete_category_group", GroupArgs { gid: group_id_selected.get_untracked(), ..GroupArgs::default() });
+                            }
+                        >
+                            "Delete Group"
+                        </button>
+                        <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">"Cancel"</button>
+                        <button type="button" class="btn btn-primary" data-bs-dismiss="modal"
+                            on:click = move |_| {
+                                let name = rename_group_input.get_untracked().unwrap().value();
+                                if name.is_empty() {
+                                    return;
+                                }
+                                change_groups("rename_category_group", GroupArgs { gid: group_id_selected.get_untracked(), name: name, ..GroupArgs::default() });
+                            }
+                        >
+                            "Rename"
+                        </button>
+                      </div>
+                    </div>
+                  </div>
+                </div>
+
             </div>
             <div class="col-4 bg-secondary-subtle rounded-3 p-3 px-4">
                 <h5 class="text-secondary">"Categories Info"</h5>
@@ -328,23 +952,90 @@ pub fn Categories() -> impl IntoView {
                                 <p class="text-secondary">"Select category to view detailed info."</p>
                             }
                         } else {
-                            let cats: Vec<CategoryDisplay> = categories.0.get();
+                            let groups: Vec<CategoryGroupDisplay> = categories.0.get();
+                            let cats: Vec<CategoryDisplay> = groups.iter().flat_map(|g| g.categories.clone()).collect();
 
                             // Get index from id
                             let mut cat_info: CategoryDisplay = cats.get(0).unwrap().clone();
-                            for c in cats {
+                            for c in &cats {
                                 if c.category_id == category_id_selected.get() {
                                     cat_info = c.clone();
                                 }
                             }
 
+                            let category_name = |cat_id: i64| -> String {
+                                for c in &cats {
+                                    if c.category_id == cat_id {
+                                        return c.display_name.clone();
+                                    }
+                                }
+                                return "Deleted category".to_string();
+                            };
+
+                            let dest_options = cats.iter()
+                                .filter(|c| c.category_id != cat_info.category_id)
+                                .map(|c| view! {
+                                    <option value={c.category_id}>{c.display_name.clone()}</option>
+                                })
+                                .collect_view();
+
+                            let transfer_rows = transfers.0.get().into_iter().map(
+                            |t| {
+                                let (other, amount) = if t.dest == cat_info.category_id {
+                                    (format!("From {}", category_name(t.source)), t.amount)
+                                } else {
+                                    (format!("To {}", category_name(t.dest)), -t.amount)
+                                };
+
+                                view! {
+                                    <tr>
+                                        <td>{month_key_to_display(t.month)}</td>
+                                        <td>{other}</td>
+                                        <td class="text-end">{data::amount_to_display(amount)}</td>
+                                    </tr>
+                                }
+                            }
+                            ).collect_view();
+
+                            let source_id = cat_info.category_id;
+
+                            // Filled in from the current target
+                            let target_kind = cat_info.target.as_ref().map(|t| t.kind);
+                            let target_amount = cat_info.target.as_ref().map_or(String::new(), |t| format!("{:.2}", data::cents_to_dollars(t.amount)));
+                            let target_month = cat_info.target.as_ref().and_then(|t| t.target_month).map_or(String::new(), |m| format!("{:04}-{:02}", m / 100, m % 100));
+
+                            let group_options = groups.iter()
+                                .filter(|g| g.group.id != UNGROUPED_ID)
+                                .map(|g| view! {
+                                    <option value={g.group.id} selected = g.group.id == cat_info.group_id>{g.group.display_name.clone()}</option>
+                                })
+                                .collect_view();
+
                             view! {
                                 <h2>{&cat_info.display_name}</h2>
+                                <p>"Assigned " {data::amount_to_display(cat_info.assigned)}</p>
+                                <p>"Transferred " {data::amount_to_display(cat_info.transferred)}</p>
+                                <p>"Available " {data::amount_to_display(cat_info.available)}</p>
                                 <p>"Spending Total " {data::amount_to_display(cat_info.transaction_total * -1)}</p>
                                 <p>"Average (per transaction) " {data::amount_to_display((cat_info.transaction_average * -1.0) as i64)}</p>
+                                {
+                                    (cat_info.target_needed > 0).then(|| view! {
+                                        <p class="text-warning-emphasis">"Needs " {data::amount_to_display(cat_info.target_needed)} " more this month"</p>
+                                    })
+                                }
 
                                 <div class="card card-body d-grid gap-2">
 
+                                    <select class="form-select form-select-sm"
+                                        on:change = move |ev| {
+                                            let gid = event_target_value(&ev).parse::<i64>().unwrap_or(UNGROUPED_ID);
+                                            change_groups("set_category_group", GroupArgs { cid: source_id, gid: gid, ..GroupArgs::default() });
+                                        }
+                                    >
+                                        <option value={UNGROUPED_ID} selected = cat_info.group_id == UNGROUPED_ID>"No Group"</option>
+                                        {group_options}
+                                    </select>
+
                                     <button class="btn btn-outline-primary btn-sm" data-bs-toggle="modal" data-bs-target="#rename_category">
                                         "Rename Category"
                                     </button>
@@ -358,6 +1049,71 @@ pub fn Categories() -> impl IntoView {
                                     </button>
                                 </div>
 
+                                <div class="card card-body mt-3">
+                                    <h6>"Move Money"</h6>
+                                    <div class="input-group input-group-sm">
+                                        <input class="form-control" type="number" placeholder="Amount" node_ref=transfer_amount_input/>
+                                        <select class="form-select" node_ref=transfer_dest_input>
+                                            {dest_options}
+                                        </select>
+                                        <button class="btn btn-outline-primary"
+                                            on:click = move |_| {
+                                                move_money(source_id);
+                                            }
+                                        >
+                                            "Move"
+                                        </button>
+                                    </div>
+                                </div>
+
+                                <div class="card card-body mt-3">
+                                    <h6>"Target"</h6>
+                                    <select class="form-select form-select-sm mb-2" node_ref=target_kind_input>
+                                        {
+                                            [TargetKind::MonthlyAmount, TargetKind::BalanceByDate, TargetKind::RefillUpTo].into_iter().map(
+                                            |k| view! {
+                                                <option value={k.to_i64()} selected = target_kind == Some(k)>{k.display_name()}</option>
+                                            }
+                                            ).collect_view()
+                                        }
+                                    </select>
+                                    <div class="input-group input-group-sm mb-2">
+                                        <input class="form-control" type="number" placeholder="Amount" node_ref=target_amount_input
+                                            value={target_amount}
+                                        />
+                                        <input class="form-control" type="month" title="Needed by, for a balance by date" node_ref=target_month_input
+                                            value={target_month}
+                                        />
+                                    </div>
+                                    <div class="d-flex gap-2">
+                                        <button class="btn btn-outline-primary btn-sm"
+                                            on:click = move |_| {
+                                                save_target(source_id);
+                                            }
+                                        >
+                                            "Save Target"
+                                        </button>
+                                        {
+                                            target_kind.is_some().then(|| view! {
+                                                <button class="btn btn-outline-danger btn-sm"
+                                                    on:click = move |_| {
+                                                        remove_target(source_id);
+                                                    }
+                                                >
+                                                    "Remove Target"
+                                                </button>
+                                            })
+                                        }
+                                    </div>
+                                </div>
+
+                                <h6 class="mt-3">"Transfers"</h6>
+                                <table class="table table-sm">
+                                    <tbody>
+                                        {transfer_rows}
+                                    </tbody>
+                                </table>
+
 
                                  <div class="modal fade" id="rename_category" tabindex="-1" aria-labelledby="rename_category" aria-hidden="true">
                                    <div class="modal-dialog modal-dialog-centered">
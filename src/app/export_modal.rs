@@ -0,0 +1,213 @@
+use time::format_description::well_known::Iso8601;
+use time::PrimitiveDateTime;
+
+use leptos::html::*;
+use leptos::leptos_dom::ev::MouseEvent;
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::error_modal;
+use data::account::AccountDisplay;
+use data::category::{Category, UNCATEGORIZED_ID};
+use data::csv_export::*;
+use data::import_profile::AmountFormat;
+use data::RytError;
+
+use crate::app::GlobalState;
+
+// Unix timestamp of a date input, None when it's empty
+fn parse_date_input(input: NodeRef<html::Input>) -> Option<i64> {
+    let mut date_val: String = input.get_untracked().unwrap().value();
+    if date_val.is_empty() {
+        return None;
+    }
+
+    date_val.push_str("T00:00:00");
+    return match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+        Ok(v) => Some(v.assume_utc().unix_timestamp()),
+        Err(_) => None,
+    };
+}
+
+// "" is every row, otherwise an id
+fn parse_id_select(select: NodeRef<html::Select>) -> Option<i64> {
+    return select
+        .get_untracked()
+        .unwrap()
+        .value()
+        .parse::<i64>()
+        .ok();
+}
+
+#[component]
+pub fn ExportModal() -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let accounts = create_signal::<Vec<AccountDisplay>>(vec![]);
+    let categories = create_signal::<Vec<Category>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let res = tauri::invoke("get_account_display_list", &crate::app::NoArgs {}).await;
+            let ret: Result<Vec<AccountDisplay>, RytError> = crate::app::convert_invoke(res);
+            // TODO handle error
+            accounts.1.set(ret.unwrap());
+
+            let res = tauri::invoke("get_categories", &crate::app::NoArgs {}).await;
+            let ret: Result<Vec<Category>, RytError> = crate::app::convert_invoke(res);
+            // TODO handle error
+            categories.1.set(ret.unwrap());
+        },
+    );
+
+    // Checked columns, kept in the default order
+    let (columns, columns_set) =
+        create_signal::<Vec<ExportColumn>>(CsvExportOptions::new_default().columns);
+
+    let set_column = move |column: ExportColumn, checked: bool| {
+        columns_set.update(|c: &mut Vec<ExportColumn>| {
+            c.retain(|v| *v != column);
+            if checked {
+                c.push(column);
+            }
+
+            let order = CsvExportOptions::new_default().columns;
+            c.sort_by_key(|v| order.iter().position(|o| o == v));
+        });
+    };
+
+    let amount_format_select: NodeRef<html::Select> = create_node_ref();
+    let account_select: NodeRef<html::Select> = create_node_ref();
+    let category_select: NodeRef<html::Select> = create_node_ref();
+    let start_date_input: NodeRef<html::Input> = create_node_ref();
+    let end_date_input: NodeRef<html::Input> = create_node_ref();
+
+    let run_export = move |ev: MouseEvent| {
+        let amount_format = match amount_format_select.get_untracked().unwrap().value().as_str() {
+            "signed" => AmountFormat::Signed,
+            _ => AmountFormat::OutflowInflow,
+        };
+
+        let options = CsvExportOptions {
+            columns: columns.get_untracked(),
+            amount_format,
+            account_id: parse_id_select(account_select),
+            category_id: parse_id_select(category_select),
+            start_date: parse_date_input(start_date_input),
+            end_date: parse_date_input(end_date_input),
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                options: CsvExportOptions,
+            }
+
+            let res = tauri::invoke("export_to_csv", &Args { options }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(()) | Err(RytError::PickFileNone) => {}
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
+
+    view! {
+        <div class="modal fade" id="export_csv_modal" tabindex="-1" aria-labelledby="export_csv_modal" aria-hidden="true">
+           <div class="modal-dialog modal-dialog-centered">
+             <div class="modal-content">
+               <div class="modal-header">
+                 <h1 class="modal-title fs-5">"Export to CSV"</h1>
+                 <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+               </div>
+
+               <div class="modal-body">
+                    <h6>"Columns"</h6>
+                    <div class="mb-3">
+                    {
+                        CsvExportOptions::new_default().columns.into_iter().map(
+                        |c| {
+                            let id = format!("export_column_{}", c.display_name());
+                            view! {
+                                <div class="form-check form-check-inline">
+                                    <input type="checkbox" class="form-check-input" id={id.clone()} checked
+                                        on:change = move |ev| {
+                                            set_column(c, event_target_checked(&ev));
+                                        }
+                                    />
+                                    <label class="form-check-label" for={id}>{c.display_name()}</label>
+                                </div>
+                            }
+                        }
+                        ).collect_view()
+                    }
+                    </div>
+
+                    <label class="col-form-label">"Amounts"</label>
+                    <select class="form-select mb-3" node_ref=amount_format_select>
+                        <option value="outflow_inflow">"Outflow and Inflow Columns"</option>
+                        <option value="signed">"Single Signed Amount"</option>
+                    </select>
+
+                    <label class="col-form-label">"Account"</label>
+                    <select class="form-select mb-3" node_ref=account_select>
+                        <option value="">"All Accounts"</option>
+                        {
+                            move || {
+                                accounts.0.get().into_iter().map(
+                                |a| {
+                                    view! {
+                                        <option value={a.account_id}>{a.display_name}</option>
+                                    }
+                                }
+                                ).collect_view()
+                            }
+                        }
+                    </select>
+
+                    <label class="col-form-label">"Category"</label>
+                    <select class="form-select mb-3" node_ref=category_select>
+                        <option value="">"All Categories"</option>
+                        <option value={UNCATEGORIZED_ID}>"Uncategorized"</option>
+                        {
+                            move || {
+                                categories.0.get().into_iter().map(
+                                |c| {
+                                    view! {
+                                        <option value={c.id}>{c.display_name}</option>
+                                    }
+                                }
+                                ).collect_view()
+                            }
+                        }
+                    </select>
+
+                    <div class="row">
+                        <div class="col">
+                            <label class="col-form-label">"From"</label>
+                            <input class="form-control" type="date" node_ref=start_date_input/>
+                        </div>
+                        <div class="col">
+                            <label class="col-form-label">"To"</label>
+                            <input class="form-control" type="date" node_ref=end_date_input/>
+                        </div>
+                    </div>
+               </div>
+
+               <div class="modal-footer">
+                 <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">"Cancel"</button>
+                 <button type="button" class="btn btn-primary" data-bs-dismiss="modal" on:click=run_export
+                    disabled = move || columns.get().is_empty()
+                 >
+                    "Export"
+                 </button>
+               </div>
+             </div>
+           </div>
+         </div>
+    }
+}
@@ -13,9 +13,14 @@ use serde_wasm_bindgen::{from_value, to_value};
 
 use crate::app::error_modal;
 use data::account::*;
+use data::forecast::*;
 use data::RytError;
 
 mod account_box;
+mod import_modal;
+mod net_worth;
+mod reconcile_modal;
+mod upcoming;
 
 async fn get_account_list() -> Vec<AccountDisplay> {
     let res = tauri::invoke("get_account_display_list", &crate::app::NoArgs {}).await;
@@ -41,8 +46,65 @@ pub fn Home() -> impl IntoView {
         },
     );
 
+    // Projected balances for the account boxes, optionally with average spending taken off
+    let include_spending = create_signal(false);
+    let forecasts = create_signal::<Vec<AccountForecast>>(vec![]);
+    create_resource(
+        move || include_spending.0.get(),
+        move |spending| async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                now: i64,
+                days: i64,
+                spending: bool,
+            }
+
+            let now = (js_sys::Date::now() / 1000.0) as i64;
+            let args = Args {
+                now,
+                days: DEFAULT_FORECAST_DAYS,
+                spending,
+            };
+            let res = tauri::invoke("get_account_forecasts", &args).await;
+            let ret: Result<Vec<AccountForecast>, RytError> = super::convert_invoke(res);
+            match ret {
+                Ok(v) => forecasts.1.set(v),
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+            }
+        },
+    );
+
+    let closed_accounts = create_signal::<Vec<AccountDisplay>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let res = tauri::invoke("get_closed_account_display_list", &crate::app::NoArgs {}).await;
+            let ret: Result<Vec<AccountDisplay>, RytError> = super::convert_invoke(res);
+            // TODO handle error
+            closed_accounts.1.set(ret.unwrap());
+        },
+    );
+
+    let reopen_account = move |account_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                acc: i64,
+            }
+
+            let res = tauri::invoke("reopen_account", &Args { acc: account_id }).await;
+            let ret: Result<(), RytError> = super::convert_invoke(res);
+            match ret {
+                Ok(()) => super::js::reload_page(),
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
+
     let account_name_input: NodeRef<html::Input> = create_node_ref();
     let starting_balance_input: NodeRef<html::Input> = create_node_ref();
+    let account_type_select: NodeRef<html::Select> = create_node_ref();
+    let (on_budget, on_budget_set) = create_signal(true);
 
     let create_account = move |ev: MouseEvent| {
         ev.prevent_default();
@@ -51,6 +113,8 @@ pub fn Home() -> impl IntoView {
             struct Args<'a> {
                 name: &'a str,
                 sb: i64,
+                kind: AccountType,
+                budget: bool,
             }
 
             let name: &str = &account_name_input.get().unwrap().value();
@@ -68,12 +132,23 @@ pub fn Home() -> impl IntoView {
                 }
             };
 
+            let kind = account_type_select
+                .get()
+                .unwrap()
+                .value()
+                .parse::<i64>()
+                .ok()
+                .and_then(AccountType::from_i64)
+                .unwrap_or(AccountType::Checking);
+
             // invoke
             let res = tauri::invoke(
                 "create_account",
                 &Args {
                     name: name,
                     sb: data::dollars_to_cents(starting_balance),
+                    kind,
+                    budget: on_budget.get_untracked(),
                 },
             )
             .await;
@@ -139,6 +214,47 @@ pub fn Home() -> impl IntoView {
                         <input type="number" class="form-control" id="starting_amount" node_ref=starting_balance_input/>
                     </div>
 
+                    <div class="mb-3">
+                        <label for="account_type" class="col-form-label">Account Type</label>
+                        <select class="form-select" id="account_type" node_ref=account_type_select
+                            on:change = move |ev| {
+                                let kind = event_target_value(&ev)
+                                    .parse::<i64>()
+                                    .ok()
+                                    .and_then(AccountType::from_i64)
+                                    .unwrap_or(AccountType::Checking);
+                                on_budget_set.set(kind.default_on_budget());
+                            }
+                        >
+                        {
+                            [
+                                AccountType::Checking,
+                                AccountType::Savings,
+                                AccountType::Cash,
+                                AccountType::CreditCard,
+                                AccountType::Loan,
+                                AccountType::Investment,
+                            ].into_iter().map(
+                            |t| {
+                                view! {
+                                    <option value={t.to_i64()}>{t.display_name()}</option>
+                                }
+                            }
+                            ).collect_view()
+                        }
+                        </select>
+                    </div>
+
+                    <div class="form-check mb-3">
+                        <input class="form-check-input" type="checkbox" id="account_on_budget"
+                            prop:checked = on_budget
+                            on:change = move |ev| on_budget_set.set(event_target_checked(&ev))
+                        />
+                        <label class="form-check-label" for="account_on_budget">
+                            "On budget. Tracking accounts only follow a balance, like loans or investments."
+                        </label>
+                    </div>
+
                 </form>
 
                </div>
@@ -155,6 +271,18 @@ pub fn Home() -> impl IntoView {
 
 
          <div class="container-fluid">
+         <net_worth::NetWorth/>
+
+         <div class="form-check form-switch">
+             <input class="form-check-input" type="checkbox" id="forecast_spending"
+                 prop:checked = move || include_spending.0.get()
+                 on:change = move |ev| include_spending.1.set(event_target_checked(&ev))
+             />
+             <label class="form-check-label" for="forecast_spending">
+                 "Include average spending in balance forecasts"
+             </label>
+         </div>
+
          <div class="row">
 
          {
@@ -173,8 +301,12 @@ pub fn Home() -> impl IntoView {
          move || {
              accounts.0.get().into_iter().map(
              |val| {
+                 let account_id = val.account_id;
+                 let forecast = Signal::derive(move || {
+                     forecasts.0.with(|f| f.iter().find(|v| v.account_id == account_id).cloned())
+                 });
                  view!{
-                     <account_box::AccountBox account=val/>
+                     <account_box::AccountBox account=val forecast=forecast/>
                 }
              }
              ).collect_view()
@@ -182,6 +314,43 @@ pub fn Home() -> impl IntoView {
          }
 
         </div>
+
+        {
+            move || {
+                let closed = closed_accounts.0.get();
+                if closed.is_empty() {
+                    return ().into_view();
+                }
+
+                view! {
+                    <h4 class="mt-4">"Closed Accounts"</h4>
+                    <table class="table table-sm">
+                        <tbody>
+                        {
+                            closed.into_iter().map(
+                            |a| {
+                                let account_id = a.account_id;
+                                view! {
+                                    <tr>
+                                        <td>{a.display_name}</td>
+                                        <td>{a.account_type.display_name()}</td>
+                                        <td class="text-end">
+                                            <button class="btn btn-outline-secondary btn-sm" type="button"
+                                                on:click = move |_| reopen_account(account_id)
+                                            >
+                                                "Reopen"
+                                            </button>
+                                        </td>
+                                    </tr>
+                                }
+                            }
+                            ).collect_view()
+                        }
+                        </tbody>
+                    </table>
+                }.into_view()
+            }
+        }
         </div>
 
     }
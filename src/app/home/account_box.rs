@@ -14,6 +14,7 @@ use serde_wasm_bindgen::{from_value, to_value};
 
 use crate::app::error_modal;
 use data::account::*;
+use data::forecast::AccountForecast;
 use data::RytError;
 
 use crate::app::invoke;
@@ -35,7 +36,10 @@ async fn get_account_history(account_id: i64) -> Vec<AccountHistoryEntry> {
 }
 
 #[component]
-pub fn AccountBox(account: AccountDisplay) -> impl IntoView {
+pub fn AccountBox(
+    account: AccountDisplay,
+    forecast: Signal<Option<AccountForecast>>,
+) -> impl IntoView {
     let global_state = expect_context::<RwSignal<GlobalState>>();
 
     let account_history = create_signal::<Vec<AccountHistoryEntry>>(vec![]);
@@ -68,31 +72,42 @@ pub fn AccountBox(account: AccountDisplay) -> impl IntoView {
         },
     );
 
-    let run_import = move || {
+    let graph_div_id: String = format!("graph_{0}", account.account_id);
+
+    let account_id = account.account_id;
+    let rename_input: NodeRef<html::Input> = create_node_ref();
+
+    // Rename, close or delete, then show the new account list
+    let account_action = move |command: &'static str| {
+        let name = rename_input.get_untracked().unwrap().value();
         spawn_local(async move {
             #[derive(Serialize, Deserialize)]
             struct Args {
                 acc: i64,
+                name: String,
             }
 
             let res = tauri::invoke(
-                "import",
+                command,
                 &Args {
-                    acc: account.account_id,
+                    acc: account_id,
+                    name,
                 },
             )
             .await;
             let ret: Result<(), RytError> = crate::app::convert_invoke(res);
             match ret {
-                Err(v) => {
-                    error_modal::show_error(v.to_string(), &global_state);
-                }
-                Ok(()) => {}
+                Ok(()) => crate::app::js::reload_page(),
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
             }
         });
     };
 
-    let graph_div_id: String = format!("graph_{0}", account.account_id);
+    let settings_modal_id = format!("account_settings_{0}", account.account_id);
+    let account_kind = match account.on_budget {
+        true => account.account_type.display_name().to_string(),
+        false => format!("{}, Tracking", account.account_type.display_name()),
+    };
 
     view! {
         <div class="col-md-6">
@@ -103,11 +118,15 @@ pub fn AccountBox(account: AccountDisplay) -> impl IntoView {
           <div class="row">
 
             <div class="col">
-                <h4>{account.display_name}</h4>
+                <h4>{account.display_name.clone()}</h4>
+                <p class="text-body-secondary">{account_kind}</p>
             </div>
 
             <div class="col text-end">
                 <h1>{data::amount_to_display(account.balance)}</h1>
+                <p class="text-body-secondary mb-0">
+                    {format!("Cleared {}", data::amount_to_display(account.cleared_balance))}
+                </p>
             </div>
 
           </div>
@@ -125,12 +144,90 @@ pub fn AccountBox(account: AccountDisplay) -> impl IntoView {
         <div id={graph_div_id.clone()} style="width: max-width; height:400px;">
         </div>
 
-         <button class="btn btn-outline-secondary btn-sm" type="submit"
-         on:click = move |ev| { run_import(); }
+         <super::upcoming::Upcoming account_id=account.account_id/>
+
+         {
+             move || {
+                 let Some(f) = forecast.get() else {
+                     return ().into_view();
+                 };
+
+                 let days = (f.end_date - f.start_date) / (24 * 60 * 60);
+                 view! {
+                     <p class="mb-2" class:text-danger = f.goes_negative()>
+                         {format!(
+                             "Lowest in the next {} days: {} on {}, {} at the end",
+                             days,
+                             data::amount_to_display(f.low_balance),
+                             crate::app::transactions::date_to_display(f.low_date),
+                             data::amount_to_display(f.ending_balance),
+                         )}
+                     </p>
+                 }.into_view()
+             }
+         }
+
+         <button class="btn btn-outline-secondary btn-sm" type="button"
+         data-bs-toggle="modal" data-bs-target={format!("#import_modal_{0}", account.account_id)}
          >
-             "Import Transactions CSV"
+             "Import Transactions"
          </button>
 
+         <button class="btn btn-outline-secondary btn-sm ms-1" type="button"
+         data-bs-toggle="modal" data-bs-target={format!("#reconcile_modal_{0}", account.account_id)}
+         >
+             "Reconcile"
+         </button>
+
+         <super::import_modal::ImportModal account_id=account.account_id/>
+         <super::reconcile_modal::ReconcileModal account_id=account.account_id/>
+
+         <button class="btn btn-outline-secondary btn-sm ms-1" type="button"
+         data-bs-toggle="modal" data-bs-target={format!("#{0}", settings_modal_id)}
+         >
+             "Account Settings"
+         </button>
+
+         <div class="modal fade" id={settings_modal_id.clone()} tabindex="-1" aria-labelledby={settings_modal_id.clone()} aria-hidden="true">
+            <div class="modal-dialog modal-dialog-centered">
+              <div class="modal-content">
+                <div class="modal-header">
+                  <h1 class="modal-title fs-5">"Account Settings"</h1>
+                  <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+                </div>
+
+                <div class="modal-body">
+                    <label class="col-form-label">"Name"</label>
+                    <div class="input-group mb-3">
+                        <input class="form-control" type="text" value={account.display_name} node_ref=rename_input/>
+                        <button class="btn btn-outline-secondary" type="button" data-bs-dismiss="modal"
+                            on:click = move |_| account_action("rename_account")
+                        >
+                            "Rename"
+                        </button>
+                    </div>
+
+                    <p class="text-body-secondary">
+                        "Only accounts with a zero balance can be closed. Only accounts without any transactions can be deleted."
+                    </p>
+                </div>
+
+                <div class="modal-footer">
+                  <button type="button" class="btn btn-outline-secondary" data-bs-dismiss="modal"
+                      on:click = move |_| account_action("close_account")
+                  >
+                      "Close Account"
+                  </button>
+                  <button type="button" class="btn btn-outline-danger" data-bs-dismiss="modal"
+                      on:click = move |_| account_action("delete_account")
+                  >
+                      "Delete Account"
+                  </button>
+                </div>
+              </div>
+            </div>
+          </div>
+
          </div>
          </div>
     }
@@ -0,0 +1,582 @@
+use leptos::html::*;
+use leptos::leptos_dom::ev::{MouseEvent, SubmitEvent};
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::error_modal;
+use data::import_batch::ImportBatch;
+use data::import_preview::*;
+use data::import_profile::*;
+use data::RytError;
+
+use crate::app::GlobalState;
+
+async fn get_import_profiles() -> Vec<ImportProfile> {
+    let res = tauri::invoke("get_import_profiles", &crate::app::NoArgs {}).await;
+    let ret: Result<Vec<ImportProfile>, RytError> = crate::app::convert_invoke(res);
+    // TODO handle error
+    return ret.unwrap();
+}
+
+async fn get_import_batches(account_id: i64) -> Vec<ImportBatch> {
+    #[derive(Serialize, Deserialize)]
+    struct Args {
+        acc: i64,
+    }
+
+    let res = tauri::invoke("get_import_batches", &Args { acc: account_id }).await;
+    let ret: Result<Vec<ImportBatch>, RytError> = crate::app::convert_invoke(res);
+    // TODO handle error
+    return ret.unwrap();
+}
+
+// Columns are entered starting at 1 and stored starting at 0. Empty means no column.
+fn parse_column(input: NodeRef<html::Input>) -> Option<i64> {
+    let value = input.get_untracked().unwrap().value();
+    match value.trim().parse::<i64>() {
+        Ok(v) if v > 0 => Some(v - 1),
+        _ => None,
+    }
+}
+
+#[component]
+pub fn ImportModal(account_id: i64) -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let profiles = create_signal::<Vec<ImportProfile>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let list = get_import_profiles().await;
+            profiles.1.set(list);
+        },
+    );
+
+    let batches = create_signal::<Vec<ImportBatch>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let list = get_import_batches(account_id).await;
+            batches.1.set(list);
+        },
+    );
+
+    let profile_select: NodeRef<html::Select> = create_node_ref();
+
+    // File read but not yet imported
+    let (preview, preview_set) = create_signal::<Option<ImportPreview>>(None);
+
+    let name_input: NodeRef<html::Input> = create_node_ref();
+    let delimiter_select: NodeRef<html::Select> = create_node_ref();
+    let skip_rows_input: NodeRef<html::Input> = create_node_ref();
+    let date_column_input: NodeRef<html::Input> = create_node_ref();
+    let date_format_input: NodeRef<html::Input> = create_node_ref();
+    let payee_column_input: NodeRef<html::Input> = create_node_ref();
+    let amount_format_select: NodeRef<html::Select> = create_node_ref();
+    let amount_column_input: NodeRef<html::Input> = create_node_ref();
+    let negate_input: NodeRef<html::Input> = create_node_ref();
+    let outflow_column_input: NodeRef<html::Input> = create_node_ref();
+    let inflow_column_input: NodeRef<html::Input> = create_node_ref();
+    let category_column_input: NodeRef<html::Input> = create_node_ref();
+    let notes_column_input: NodeRef<html::Input> = create_node_ref();
+
+    let selected_profile = move || -> Option<i64> {
+        return profile_select
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<i64>()
+            .ok();
+    };
+
+    let read_file = move |ev: MouseEvent| {
+        let profile_id = match selected_profile() {
+            Some(v) => v,
+            None => return,
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                acc: i64,
+                profile: i64,
+            }
+
+            let res = tauri::invoke(
+                "import_preview",
+                &Args {
+                    acc: account_id,
+                    profile: profile_id,
+                },
+            )
+            .await;
+            let ret: Result<ImportPreview, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(RytError::PickFileNone) => {}
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(v) => preview_set.set(Some(v)),
+            }
+        });
+    };
+
+    let read_ofx_file = move |ev: MouseEvent| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                acc: i64,
+            }
+
+            let res = tauri::invoke("import_ofx_preview", &Args { acc: account_id }).await;
+            let ret: Result<ImportPreview, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(RytError::PickFileNone) => {}
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(v) => preview_set.set(Some(v)),
+            }
+        });
+    };
+
+    let read_qif_file = move |ev: MouseEvent| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                acc: i64,
+            }
+
+            let res = tauri::invoke("import_qif", &Args { acc: account_id }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(RytError::PickFileNone) => {}
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(()) => {}
+            }
+
+            let list = get_import_batches(account_id).await;
+            batches.1.set(list);
+        });
+    };
+
+    let run_import = move |ev: MouseEvent| {
+        let to_commit = match preview.get_untracked() {
+            Some(v) => v,
+            None => return,
+        };
+        preview_set.set(None);
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                preview: ImportPreview,
+            }
+
+            let res = tauri::invoke("import_commit", &Args { preview: to_commit }).await;
+            let ret: Result<i64, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(_) => {}
+            }
+
+            let list = get_import_batches(account_id).await;
+            batches.1.set(list);
+        });
+    };
+
+    let undo_import = move |batch_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                batch: i64,
+            }
+
+            let res = tauri::invoke("import_rollback", &Args { batch: batch_id }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(()) => {}
+            }
+
+            let list = get_import_batches(account_id).await;
+            batches.1.set(list);
+        });
+    };
+
+    // Include or leave out one line of the preview
+    let set_skip = move |index: usize, skip: bool| {
+        preview_set.update(|p: &mut Option<ImportPreview>| {
+            if let Some(p) = p {
+                p.candidates[index].skip = skip;
+            }
+        });
+    };
+
+    let delete_profile = move |ev: MouseEvent| {
+        let profile_id = match selected_profile() {
+            Some(v) => v,
+            None => return,
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                pid: i64,
+            }
+
+            let res = tauri::invoke("delete_import_profile", &Args { pid: profile_id }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                }
+                Ok(()) => {}
+            }
+
+            let list = get_import_profiles().await;
+            profiles.1.set(list);
+        });
+    };
+
+    let create_profile = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        let display_name = name_input.get_untracked().unwrap().value();
+        if display_name.is_empty() {
+            return;
+        }
+
+        let amount_format = match amount_format_select.get_untracked().unwrap().value().as_str() {
+            "signed" => AmountFormat::Signed,
+            _ => AmountFormat::OutflowInflow,
+        };
+
+        let skip_rows: i64 = skip_rows_input
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<i64>()
+            .unwrap_or(0);
+
+        let profile = ImportProfile {
+            id: 0,
+            display_name,
+            delimiter: delimiter_select.get_untracked().unwrap().value(),
+            skip_rows,
+            date_column: parse_column(date_column_input).unwrap_or(0),
+            date_format: date_format_input.get_untracked().unwrap().value(),
+            payee_column: parse_column(payee_column_input).unwrap_or(0),
+            amount_format,
+            amount_column: parse_column(amount_column_input).unwrap_or(0),
+            negate_amounts: negate_input.get_untracked().unwrap().checked(),
+            outflow_column: parse_column(outflow_column_input).unwrap_or(0),
+            inflow_column: parse_column(inflow_column_input).unwrap_or(0),
+            category_column: parse_column(category_column_input),
+            notes_column: parse_column(notes_column_input),
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                profile: ImportProfile,
+            }
+
+            let res = tauri::invoke("create_import_profile", &Args { profile }).await;
+            let ret: Result<i64, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                Ok(_) => {}
+            }
+
+            let list = get_import_profiles().await;
+            profiles.1.set(list);
+        });
+    };
+
+    let modal_id = format!("import_modal_{0}", account_id);
+
+    view! {
+        <div class="modal fade" id={modal_id.clone()} tabindex="-1" aria-labelledby={modal_id.clone()} aria-hidden="true">
+           <div class="modal-dialog modal-dialog-centered modal-lg">
+             <div class="modal-content">
+               <div class="modal-header">
+                 <h1 class="modal-title fs-5">"Import Transactions"</h1>
+                 <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+               </div>
+
+               <div class="modal-body">
+
+                    <label class="col-form-label">"File Layout"</label>
+                    <div class="input-group mb-3">
+                        <select class="form-select" node_ref=profile_select>
+                        {
+                            move || {
+                                profiles.0.get().into_iter().map(
+                                |p| {
+                                    view! {
+                                        <option value={p.id}>{p.display_name}</option>
+                                    }
+                                }
+                                ).collect_view()
+                            }
+                        }
+                        </select>
+                        <button class="btn btn-outline-danger" type="button" on:click=delete_profile>
+                            "Delete Layout"
+                        </button>
+                        <button class="btn btn-outline-primary" type="button" on:click=read_file>
+                            "Choose CSV File"
+                        </button>
+                    </div>
+
+                    <div class="btn-group mb-3">
+                        <button class="btn btn-outline-primary" type="button" on:click=read_ofx_file>
+                            "Choose OFX / QFX File"
+                        </button>
+                        <button class="btn btn-outline-primary" type="button" on:click=read_qif_file>
+                            "Import QIF File"
+                        </button>
+                    </div>
+                    <p class="text-secondary">"QIF files are imported straight away, into the accounts they name."</p>
+
+                    {
+                        move || {
+                            let p = match preview.get() {
+                                Some(v) => v,
+                                None => return ().into_view(),
+                            };
+
+                            let error_rows = p.errors.into_iter().map(
+                            |e| {
+                                view! {
+                                    <tr>
+                                        <td>{e.line}</td>
+                                        <td class="text-danger">{e.error.to_string()}</td>
+                                        <td class="text-secondary">{e.text}</td>
+                                    </tr>
+                                }
+                            }
+                            ).collect_view();
+
+                            // Statement formats say what the balance should be
+                            let ledger = match (&p.ledger_balance, p.ledger_difference()) {
+                                (Some(ledger), Some(difference)) => {
+                                    let status = if difference == 0 {
+                                        view! { <span class="text-success">"Matches the account"</span> }
+                                    } else {
+                                        view! {
+                                            <span class="text-warning">
+                                                {format!("Account will be off by {}", data::amount_to_display(difference))}
+                                            </span>
+                                        }
+                                    };
+
+                                    view! {
+                                        <p>
+                                            {format!(
+                                                "Bank balance {} on {}. Account balance after import {}. ",
+                                                data::amount_to_display(ledger.amount),
+                                                crate::app::transactions::date_to_display(ledger.date),
+                                                data::amount_to_display(p.balance_after_import()),
+                                            )}
+                                            {status}
+                                        </p>
+                                    }.into_view()
+                                }
+                                _ => ().into_view(),
+                            };
+
+                            let candidate_count = p.candidates.iter().filter(|c| !c.skip).count();
+                            let candidate_rows = p.candidates.into_iter().enumerate().map(
+                            |(i, c)| {
+                                let row_class = if c.skip { "text-secondary" } else { "" };
+
+                                view! {
+                                    <tr class={row_class}>
+                                        <td>
+                                            <input type="checkbox" class="form-check-input" prop:checked={!c.skip}
+                                                on:change = move |ev| {
+                                                    set_skip(i, !event_target_checked(&ev));
+                                                }
+                                            />
+                                        </td>
+                                        <td>
+                                            {c.line}
+                                            {
+                                                if c.duplicate_of != 0 {
+                                                    view! { <span class="badge text-bg-warning ms-2">"Duplicate"</span> }.into_view()
+                                                } else {
+                                                    ().into_view()
+                                                }
+                                            }
+                                        </td>
+                                        <td>{crate::app::transactions::date_to_display(c.trans.date)}</td>
+                                        <td>{c.trans.payee}</td>
+                                        <td>{c.category}</td>
+                                        <td class="text-end">{data::amount_to_display(c.trans.amount)}</td>
+                                    </tr>
+                                }
+                            }
+                            ).collect_view();
+
+                            view! {
+                                <h6>{format!("{} transactions to import", candidate_count)}</h6>
+                                {ledger}
+
+                                <h6 class="text-danger">"Skipped Lines"</h6>
+                                <table class="table table-sm">
+                                    <tbody>
+                                        {error_rows}
+                                    </tbody>
+                                </table>
+
+                                <div style="max-height: 300px; overflow-y: auto;">
+                                <table class="table table-sm">
+                                    <thead>
+                                        <tr>
+                                            <th>"Import"</th>
+                                            <th>"Line"</th>
+                                            <th>"Date"</th>
+                                            <th>"Payee"</th>
+                                            <th>"Category"</th>
+                                            <th class="text-end">"Amount"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        {candidate_rows}
+                                    </tbody>
+                                </table>
+                                </div>
+                            }.into_view()
+                        }
+                    }
+
+                    <h6>"Past Imports"</h6>
+                    <table class="table table-sm">
+                        <tbody>
+                        {
+                            move || {
+                                batches.0.get().into_iter().map(
+                                |b| {
+                                    let batch_id = b.id;
+                                    view! {
+                                        <tr>
+                                            <td>{crate::app::transactions::date_to_display(b.date)}</td>
+                                            <td>{b.file_name}</td>
+                                            <td>{format!("{} transactions", b.transaction_count)}</td>
+                                            <td class="text-end">
+                                                <button class="btn btn-outline-danger btn-sm" type="button"
+                                                    on:click = move |_| { undo_import(batch_id); }
+                                                >
+                                                    "Undo"
+                                                </button>
+                                            </td>
+                                        </tr>
+                                    }
+                                }
+                                ).collect_view()
+                            }
+                        }
+                        </tbody>
+                    </table>
+
+                    <h6>"New Layout"</h6>
+                    <p class="text-secondary">"Columns are numbered from 1. Leave category or notes empty if the file doesn't have them."</p>
+
+                    <form on:submit=create_profile>
+                        <div class="row mb-2">
+                            <div class="col">
+                                <input type="text" class="form-control" placeholder="Layout Name" node_ref=name_input/>
+                            </div>
+                            <div class="col">
+                                <select class="form-select" node_ref=delimiter_select>
+                                    <option value=",">"Comma"</option>
+                                    <option value=";">"Semicolon"</option>
+                                    <option value="\t">"Tab"</option>
+                                    <option value="|">"Pipe"</option>
+                                </select>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Header Rows" value="1" node_ref=skip_rows_input/>
+                            </div>
+                        </div>
+
+                        <div class="row mb-2">
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Date Column" node_ref=date_column_input/>
+                            </div>
+                            <div class="col">
+                                <input type="text" class="form-control" placeholder="Date Format" value="%Y-%m-%d" node_ref=date_format_input/>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Payee Column" node_ref=payee_column_input/>
+                            </div>
+                        </div>
+
+                        <div class="row mb-2">
+                            <div class="col">
+                                <select class="form-select" node_ref=amount_format_select>
+                                    <option value="outflow_inflow">"Outflow and Inflow Columns"</option>
+                                    <option value="signed">"Single Signed Amount"</option>
+                                </select>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Amount Column" node_ref=amount_column_input/>
+                            </div>
+                            <div class="col form-check">
+                                <input type="checkbox" class="form-check-input" id={format!("negate_{0}", account_id)} node_ref=negate_input/>
+                                <label class="form-check-label" for={format!("negate_{0}", account_id)}>"Spending is positive"</label>
+                            </div>
+                        </div>
+
+                        <div class="row mb-2">
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Outflow Column" node_ref=outflow_column_input/>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Inflow Column" node_ref=inflow_column_input/>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Category Column" node_ref=category_column_input/>
+                            </div>
+                            <div class="col">
+                                <input type="number" class="form-control" placeholder="Notes Column" node_ref=notes_column_input/>
+                            </div>
+                        </div>
+
+                        <button type="submit" class="btn btn-outline-primary btn-sm">"Save Layout"</button>
+                    </form>
+
+               </div>
+
+               <div class="modal-footer">
+                 <button type="button" class="btn btn-secondary" data-bs-dismiss="modal"
+                    on:click = move |_| { preview_set.set(None); }
+                 >
+                    "Cancel"
+                 </button>
+                 <button type="button" class="btn btn-primary" data-bs-dismiss="modal" on:click=run_import
+                    disabled = move || preview.get().is_none()
+                 >
+                    "Import"
+                 </button>
+               </div>
+             </div>
+           </div>
+         </div>
+    }
+}
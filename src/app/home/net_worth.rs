@@ -0,0 +1,112 @@
+use leptos::html::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use gloo_timers::future::TimeoutFuture;
+
+use crate::app::categories::month_key_to_display;
+use crate::app::error_modal;
+use crate::app::GlobalState;
+use data::report::NetWorthSnapshot;
+use data::RytError;
+
+// Assets and liabilities as bars, net worth as a line over them
+fn net_worth_chart(history: &[NetWorthSnapshot]) -> String {
+    let option = serde_json::json!({
+        "tooltip": { "trigger": "axis" },
+        "legend": {},
+        "xAxis": {
+            "type": "category",
+            "data": history.iter().map(|s| month_key_to_display(s.month)).collect::<Vec<String>>(),
+        },
+        "yAxis": { "type": "value" },
+        "series": [
+            {
+                "name": "Assets",
+                "type": "bar",
+                "data": history.iter().map(|s| data::cents_to_dollars(s.assets)).collect::<Vec<f64>>(),
+            },
+            {
+                "name": "Liabilities",
+                "type": "bar",
+                "data": history.iter().map(|s| data::cents_to_dollars(s.liabilities)).collect::<Vec<f64>>(),
+            },
+            {
+                "name": "Net Worth",
+                "type": "line",
+                "data": history.iter().map(|s| data::cents_to_dollars(s.net_worth())).collect::<Vec<f64>>(),
+            },
+        ],
+    });
+    return option.to_string();
+}
+
+// Net worth across every account now, and at the end of each month before
+#[component]
+pub fn NetWorth() -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let history = create_signal::<Vec<NetWorthSnapshot>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                end: i64,
+            }
+
+            let end = (js_sys::Date::now() / 1000.0) as i64;
+            let res = tauri::invoke("get_net_worth_history", &Args { end }).await;
+            let ret: Result<Vec<NetWorthSnapshot>, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(v) => history.1.set(v),
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            }
+
+            // Give the chart element a moment to be in the page
+            TimeoutFuture::new(100).await;
+
+            crate::app::js::build_chart(
+                "net_worth_graph".to_string(),
+                net_worth_chart(&history.0.get_untracked()),
+            );
+        },
+    );
+
+    let current = move || history.0.with(|h| h.last().cloned());
+
+    view! {
+        <Show when=move || current().is_some()>
+            <div class="row mb-3">
+                <div class="col">
+                    <div class="rounded-3 p-3 px-4 bg-secondary-subtle">
+                        <h2>{move || data::amount_to_display(current().map(|s| s.net_worth()).unwrap_or(0))}</h2>
+                        <p class="text-secondary mb-0">"Net Worth"</p>
+                    </div>
+                </div>
+                <div class="col">
+                    <div class="rounded-3 p-3 px-4 bg-success-subtle">
+                        <h2>{move || data::amount_to_display(current().map(|s| s.assets).unwrap_or(0))}</h2>
+                        <p class="text-secondary mb-0">"Assets"</p>
+                    </div>
+                </div>
+                <div class="col">
+                    <div class="rounded-3 p-3 px-4 bg-danger-subtle">
+                        <h2>{move || data::amount_to_display(current().map(|s| s.liabilities).unwrap_or(0))}</h2>
+                        <p class="text-secondary mb-0">"Liabilities"</p>
+                    </div>
+                </div>
+            </div>
+        </Show>
+
+        <div id="net_worth_graph" style="width: 100%; height: 300px;"
+            class:d-none = move || current().is_none()
+        ></div>
+    }
+}
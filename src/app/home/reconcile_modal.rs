@@ -0,0 +1,135 @@
+use time::format_description::well_known::Iso8601;
+use time::PrimitiveDateTime;
+
+use leptos::html::*;
+use leptos::leptos_dom::ev::MouseEvent;
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::error_modal;
+use data::account::*;
+use data::RytError;
+
+use crate::app::GlobalState;
+
+// Check an account against a bank statement. Stays open so the difference can be read, and
+// fixed by clearing transactions, before trying again.
+#[component]
+pub fn ReconcileModal(account_id: i64) -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let summary = create_signal::<Option<ReconcileSummary>>(None);
+
+    let date_input: NodeRef<html::Input> = create_node_ref();
+    let balance_input: NodeRef<html::Input> = create_node_ref();
+
+    let run_reconcile = move |ev: MouseEvent| {
+        let mut date_val: String = date_input.get_untracked().unwrap().value();
+        date_val.push_str("T00:00:00");
+        let date = match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+            Ok(v) => v.assume_utc().unix_timestamp(),
+            Err(_) => {
+                error_modal::show_error("Error parsing statement date".to_string(), &global_state);
+                return;
+            }
+        };
+
+        let balance: f64 = match balance_input
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<f64>()
+        {
+            Ok(v) => v,
+            Err(_) => {
+                error_modal::show_error(
+                    "Error parsing statement balance".to_string(),
+                    &global_state,
+                );
+                return;
+            }
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                acc: i64,
+                date: i64,
+                balance: i64,
+            }
+
+            let res = tauri::invoke(
+                "reconcile_account",
+                &Args {
+                    acc: account_id,
+                    date,
+                    balance: data::dollars_to_cents(balance),
+                },
+            )
+            .await;
+            let ret: Result<ReconcileSummary, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(v) => summary.1.set(Some(v)),
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
+
+    let modal_id = format!("reconcile_modal_{0}", account_id);
+
+    view! {
+        <div class="modal fade" id={modal_id.clone()} tabindex="-1" aria-labelledby={modal_id.clone()} aria-hidden="true">
+           <div class="modal-dialog modal-dialog-centered">
+             <div class="modal-content">
+               <div class="modal-header">
+                 <h1 class="modal-title fs-5">"Reconcile"</h1>
+                 <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+               </div>
+
+               <div class="modal-body">
+                    <label class="col-form-label">"Statement Date"</label>
+                    <input class="form-control mb-3" type="date" node_ref=date_input/>
+
+                    <label class="col-form-label">"Statement Ending Balance"</label>
+                    <input class="form-control mb-3" type="number" node_ref=balance_input/>
+
+                    {
+                        move || {
+                            let s = match summary.0.get() {
+                                Some(v) => v,
+                                None => return ().into_view(),
+                            };
+
+                            if s.difference == 0 {
+                                return view! {
+                                    <div class="alert alert-success">
+                                        {format!("Balanced. {} transactions reconciled.", s.reconciled_count)}
+                                    </div>
+                                }.into_view();
+                            }
+
+                            view! {
+                                <div class="alert alert-warning">
+                                    <p>{format!("Cleared balance: {}", data::amount_to_display(s.cleared_balance))}</p>
+                                    <p class="mb-0">{format!("Difference: {}", data::amount_to_display(s.difference))}</p>
+                                </div>
+                            }.into_view()
+                        }
+                    }
+               </div>
+
+               <div class="modal-footer">
+                 <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">"Close"</button>
+                 <button type="button" class="btn btn-primary" on:click=run_reconcile>
+                    "Reconcile"
+                 </button>
+               </div>
+             </div>
+           </div>
+         </div>
+    }
+}
@@ -0,0 +1,275 @@
+use time::format_description::well_known::Iso8601;
+use time::PrimitiveDateTime;
+
+use leptos::html::*;
+use leptos::leptos_dom::ev::MouseEvent;
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::error_modal;
+use data::scheduled_transaction::*;
+use data::RytError;
+
+use crate::app::GlobalState;
+
+// How far ahead the list looks
+const UPCOMING_DAYS: i64 = 30;
+
+async fn get_upcoming(account_id: i64) -> Vec<UpcomingTransaction> {
+    #[derive(Serialize, Deserialize)]
+    struct Args {
+        acc: i64,
+        until: i64,
+    }
+
+    let now = (js_sys::Date::now() / 1000.0) as i64;
+    let until = now + UPCOMING_DAYS * 24 * 60 * 60;
+
+    let res = tauri::invoke(
+        "get_upcoming_transactions",
+        &Args {
+            acc: account_id,
+            until,
+        },
+    )
+    .await;
+    let ret: Result<Vec<UpcomingTransaction>, RytError> = crate::app::convert_invoke(res);
+    // TODO handle error
+    return ret.unwrap();
+}
+
+// Unix timestamp of a date input, None when it's empty
+fn parse_date_input(input: NodeRef<html::Input>) -> Option<i64> {
+    let mut date_val: String = input.get_untracked().unwrap().value();
+    if date_val.is_empty() {
+        return None;
+    }
+
+    date_val.push_str("T00:00:00");
+    return match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+        Ok(v) => Some(v.assume_utc().unix_timestamp()),
+        Err(_) => None,
+    };
+}
+
+// Recurring transactions of an account due in the next few weeks, and the form to add one
+#[component]
+pub fn Upcoming(account_id: i64) -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let upcoming = create_signal::<Vec<UpcomingTransaction>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            upcoming.1.set(get_upcoming(account_id).await);
+        },
+    );
+
+    let payee_input: NodeRef<html::Input> = create_node_ref();
+    let amount_input: NodeRef<html::Input> = create_node_ref();
+    let outflow_check: NodeRef<html::Input> = create_node_ref();
+    let category_input: NodeRef<html::Input> = create_node_ref();
+    let frequency_select: NodeRef<html::Select> = create_node_ref();
+    let start_date_input: NodeRef<html::Input> = create_node_ref();
+    let end_date_input: NodeRef<html::Input> = create_node_ref();
+
+    let create_scheduled = move |ev: MouseEvent| {
+        let payee = payee_input.get_untracked().unwrap().value();
+
+        let amount: f64 = match amount_input.get_untracked().unwrap().value().parse::<f64>() {
+            Ok(v) => v,
+            Err(_) => {
+                error_modal::show_error("Error parsing amount".to_string(), &global_state);
+                return;
+            }
+        };
+        let cents = data::dollars_to_cents(amount);
+        let cents = if outflow_check.get_untracked().unwrap().checked() {
+            -cents
+        } else {
+            cents
+        };
+
+        let start_date = match parse_date_input(start_date_input) {
+            Some(v) => v,
+            None => {
+                error_modal::show_error("Error parsing start date".to_string(), &global_state);
+                return;
+            }
+        };
+
+        let frequency = frequency_select
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<i64>()
+            .ok()
+            .and_then(Frequency::from_i64)
+            .unwrap_or(Frequency::Monthly);
+
+        let mut scheduled =
+            ScheduledTransaction::new(payee, cents, account_id, frequency, start_date);
+        scheduled.end_date = parse_date_input(end_date_input);
+
+        let category_name = category_input.get_untracked().unwrap().value();
+
+        spawn_local(async move {
+            scheduled.category_id =
+                match crate::app::transactions::get_or_create_category(category_name.trim()).await
+                {
+                    Ok(v) => v,
+                    Err(v) => {
+                        error_modal::show_error(v.to_string(), &global_state);
+                        return;
+                    }
+                };
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                scheduled: ScheduledTransaction,
+            }
+
+            let res = tauri::invoke("create_scheduled_transaction", &Args { scheduled }).await;
+            let ret: Result<i64, RytError> = crate::app::convert_invoke(res);
+            if let Err(v) = ret {
+                error_modal::show_error(v.to_string(), &global_state);
+                return;
+            }
+
+            upcoming.1.set(get_upcoming(account_id).await);
+        });
+    };
+
+    let delete_scheduled = move |scheduled_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                sid: i64,
+            }
+
+            let res = tauri::invoke("delete_scheduled_transaction", &Args { sid: scheduled_id }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            if let Err(v) = ret {
+                error_modal::show_error(v.to_string(), &global_state);
+                return;
+            }
+
+            upcoming.1.set(get_upcoming(account_id).await);
+        });
+    };
+
+    let modal_id = format!("schedule_modal_{0}", account_id);
+
+    view! {
+        <h6 class="mt-3">"Upcoming"</h6>
+        {
+            move || {
+                if upcoming.0.get().is_empty() {
+                    return view! { <p class="text-body-secondary">"Nothing scheduled in the next 30 days."</p> }.into_view();
+                }
+
+                view! {
+                    <table class="table table-sm">
+                        <tbody>
+                        {
+                            upcoming.0.get().into_iter().map(
+                            |u| {
+                                view! {
+                                    <tr>
+                                        <td>{crate::app::transactions::date_to_display(u.date)}</td>
+                                        <td>{u.payee}</td>
+                                        <td class="text-end">{data::amount_to_display(u.amount)}</td>
+                                        <td class="text-end">
+                                            <button class="btn btn-outline-danger btn-sm" type="button"
+                                                on:click=move |_| delete_scheduled(u.scheduled_id)
+                                            >
+                                                "Stop"
+                                            </button>
+                                        </td>
+                                    </tr>
+                                }
+                            }
+                            ).collect_view()
+                        }
+                        </tbody>
+                    </table>
+                }.into_view()
+            }
+        }
+
+        <button class="btn btn-outline-secondary btn-sm mb-2" type="button"
+            data-bs-toggle="modal" data-bs-target={format!("#{0}", modal_id)}
+        >
+            "Schedule Transaction"
+        </button>
+
+        <div class="modal fade" id={modal_id.clone()} tabindex="-1" aria-labelledby={modal_id.clone()} aria-hidden="true">
+           <div class="modal-dialog modal-dialog-centered">
+             <div class="modal-content">
+               <div class="modal-header">
+                 <h1 class="modal-title fs-5">"Schedule Transaction"</h1>
+                 <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+               </div>
+
+               <div class="modal-body">
+                    <label class="col-form-label">"Payee"</label>
+                    <input class="form-control mb-3" type="text" node_ref=payee_input/>
+
+                    <label class="col-form-label">"Amount"</label>
+                    <div class="input-group mb-3">
+                        <input class="form-control" type="number" node_ref=amount_input/>
+                        <div class="input-group-text">
+                            <input class="form-check-input mt-0 me-2" type="checkbox" checked node_ref=outflow_check/>
+                            "Outflow"
+                        </div>
+                    </div>
+
+                    <label class="col-form-label">"Category"</label>
+                    <input class="form-control mb-3" type="text" node_ref=category_input/>
+
+                    <label class="col-form-label">"Repeats"</label>
+                    <select class="form-select mb-3" node_ref=frequency_select>
+                    {
+                        [
+                            Frequency::Daily,
+                            Frequency::Weekly,
+                            Frequency::Biweekly,
+                            Frequency::Monthly,
+                            Frequency::Yearly,
+                        ].into_iter().map(
+                        |f| {
+                            view! {
+                                <option value={f.to_i64()} selected={f == Frequency::Monthly}>{f.display_name()}</option>
+                            }
+                        }
+                        ).collect_view()
+                    }
+                    </select>
+
+                    <div class="row">
+                        <div class="col">
+                            <label class="col-form-label">"First Date"</label>
+                            <input class="form-control" type="date" node_ref=start_date_input/>
+                        </div>
+                        <div class="col">
+                            <label class="col-form-label">"Last Date (optional)"</label>
+                            <input class="form-control" type="date" node_ref=end_date_input/>
+                        </div>
+                    </div>
+               </div>
+
+               <div class="modal-footer">
+                 <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">"Cancel"</button>
+                 <button type="button" class="btn btn-primary" data-bs-dismiss="modal" on:click=create_scheduled>
+                    "Schedule"
+                 </button>
+               </div>
+             </div>
+           </div>
+         </div>
+    }
+}
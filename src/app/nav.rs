@@ -28,6 +28,13 @@ pub fn Nav() -> impl IntoView {
 
             // TODO handle error
             db_info_set.set(ret.unwrap());
+
+            // The last file didn't open, so an empty one is open instead
+            let res = tauri::invoke("take_startup_error", &crate::app::NoArgs {}).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            if let Err(v) = ret {
+                super::error_modal::show_error(v.to_string(), &global_state);
+            }
         },
     );
 
@@ -36,9 +43,11 @@ pub fn Nav() -> impl IntoView {
         spawn_local(async move {
             let res = tauri::invoke("create_db", &crate::app::NoArgs {}).await;
             let ret: Result<(), RytError> = crate::app::convert_invoke(res);
-
-            // TODO handle error
-            super::js::reload_page();
+            match ret {
+                Ok(()) => super::js::reload_page(),
+                Err(RytError::PickFileNone) => {}
+                Err(v) => super::error_modal::show_error(v.to_string(), &global_state),
+            }
         });
     };
 
@@ -47,19 +56,48 @@ pub fn Nav() -> impl IntoView {
         spawn_local(async move {
             let res = tauri::invoke("open_db", &crate::app::NoArgs {}).await;
             let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(()) => super::js::reload_page(),
+                Err(RytError::PickFileNone) => {}
+                Err(v) => super::error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
 
-            // TODO handle error
-            super::js::reload_page();
+    let export_qif = move |ev: leptos::ev::MouseEvent| {
+        ev.prevent_default();
+        spawn_local(async move {
+            let res = tauri::invoke("export_to_qif", &crate::app::NoArgs {}).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(()) | Err(RytError::PickFileNone) => {}
+                Err(v) => super::error_modal::show_error(v.to_string(), &global_state),
+            }
         });
     };
 
-    let export_csv = move |ev: leptos::ev::MouseEvent| {
+    let backup_db = move |ev: leptos::ev::MouseEvent| {
         ev.prevent_default();
         spawn_local(async move {
-            let res = tauri::invoke("export_to_csv", &crate::app::NoArgs {}).await;
+            let res = tauri::invoke("backup_db", &crate::app::NoArgs {}).await;
             let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(()) | Err(RytError::PickFileNone) => {}
+                Err(v) => super::error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
 
-            // TODO handle error
+    let restore_db = move |ev: leptos::ev::MouseEvent| {
+        ev.prevent_default();
+        spawn_local(async move {
+            let res = tauri::invoke("restore_db", &crate::app::NoArgs {}).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(()) => super::js::reload_page(),
+                Err(RytError::PickFileNone) => {}
+                Err(v) => super::error_modal::show_error(v.to_string(), &global_state),
+            }
         });
     };
 
@@ -72,6 +110,8 @@ pub fn Nav() -> impl IntoView {
             <p class="side_nav_button"><a href="/">Overview</a></p>
             <p class="side_nav_button"><a href="/categories">Categories</a></p>
             <p class="side_nav_button"><a href="/transactions">Transactions</a></p>
+            <p class="side_nav_button"><a href="/rules">Rules</a></p>
+            <p class="side_nav_button"><a href="/reports">Reports</a></p>
 
 
             <div class="side_nav_align_bottom">
@@ -102,14 +142,30 @@ pub fn Nav() -> impl IntoView {
                     "Create New Database"
                     </button>
                     <button class="btn btn-outline-secondary btn-sm" type="button"
-                        on:click = export_csv
+                        data-bs-toggle="modal" data-bs-target="#export_csv_modal"
                     >
                     "Export Database to CSV"
                     </button>
+                    <button class="btn btn-outline-secondary btn-sm" type="button"
+                        on:click = export_qif
+                    >
+                    "Export Database to QIF"
+                    </button>
+                    <button class="btn btn-outline-secondary btn-sm" type="button"
+                        on:click = backup_db
+                    >
+                    "Backup Database"
+                    </button>
+                    <button class="btn btn-outline-secondary btn-sm" type="button"
+                        on:click = restore_db
+                    >
+                    "Restore From Backup"
+                    </button>
 
                 </div>
             </div>
 
+            <super::export_modal::ExportModal/>
         </div>
     }
 }
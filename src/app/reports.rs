@@ -0,0 +1,351 @@
+use leptos::html::*;
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use chrono::prelude::*;
+use gloo_timers::future::TimeoutFuture;
+
+use data::report::*;
+use data::RytError;
+
+use super::categories::month_key_to_display;
+
+// Payees listed in the top payees table
+const TOP_PAYEE_COUNT: i64 = 10;
+
+#[derive(Serialize, Deserialize)]
+struct RangeArgs {
+    start: i64,
+    end: i64,
+    limit: i64,
+}
+
+// Unix timestamp of the first second of a month, counting back months_back from this one
+fn month_start(months_back: u32) -> i64 {
+    let now = Utc::now();
+    let start = Utc
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .unwrap();
+    return start
+        .checked_sub_months(chrono::Months::new(months_back))
+        .unwrap()
+        .timestamp();
+}
+
+fn year_start() -> i64 {
+    return Utc
+        .with_ymd_and_hms(Utc::now().year(), 1, 1, 0, 0, 0)
+        .unwrap()
+        .timestamp();
+}
+
+// "2024-03-15" for a date input
+fn unix_to_input(unix_date: i64) -> String {
+    return match DateTime::from_timestamp(unix_date, 0) {
+        Some(v) => v.format("%Y-%m-%d").to_string(),
+        None => String::new(),
+    };
+}
+
+fn input_to_unix(value: &str) -> Option<i64> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    return Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp());
+}
+
+// Income and spending side by side for each month
+fn income_expense_chart(months: &[MonthlyIncomeExpense]) -> String {
+    let option = serde_json::json!({
+        "tooltip": { "trigger": "axis" },
+        "legend": {},
+        "xAxis": {
+            "type": "category",
+            "data": months.iter().map(|m| month_key_to_display(m.month)).collect::<Vec<String>>(),
+        },
+        "yAxis": { "type": "value" },
+        "series": [
+            {
+                "name": "Income",
+                "type": "bar",
+                "data": months.iter().map(|m| data::cents_to_dollars(m.income)).collect::<Vec<f64>>(),
+            },
+            {
+                "name": "Spending",
+                "type": "bar",
+                "data": months.iter().map(|m| data::cents_to_dollars(m.expense)).collect::<Vec<f64>>(),
+            },
+        ],
+    });
+    return option.to_string();
+}
+
+fn category_chart(categories: &[CategorySpending]) -> String {
+    let slices: Vec<serde_json::Value> = categories
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "name": category_name(&c.display_name),
+                "value": data::cents_to_dollars(c.amount),
+            })
+        })
+        .collect();
+
+    let option = serde_json::json!({
+        "tooltip": { "trigger": "item" },
+        "series": [
+            {
+                "type": "pie",
+                "radius": ["40%", "70%"],
+                "data": slices,
+            },
+        ],
+    });
+    return option.to_string();
+}
+
+// Uncategorized spending has no name
+fn category_name(display_name: &str) -> String {
+    if display_name.is_empty() {
+        return "Uncategorized".to_string();
+    }
+    return display_name.to_string();
+}
+
+#[component]
+pub fn Reports() -> impl IntoView {
+    let global_state = expect_context::<RwSignal<super::GlobalState>>();
+
+    // Last 12 months, including this one
+    let (range, range_set) = create_signal::<(i64, i64)>((month_start(11), Utc::now().timestamp()));
+
+    let months = create_signal::<Vec<MonthlyIncomeExpense>>(vec![]);
+    let categories = create_signal::<Vec<CategorySpending>>(vec![]);
+    let payees = create_signal::<Vec<PayeeSpending>>(vec![]);
+    let comparison = create_signal::<Vec<CategoryComparison>>(vec![]);
+
+    create_resource(
+        move || range.get(),
+        move |(start, end)| async move {
+            let args = RangeArgs {
+                start,
+                end,
+                limit: TOP_PAYEE_COUNT,
+            };
+
+            let res = tauri::invoke("get_income_expense_by_month", &args).await;
+            let ret: Result<Vec<MonthlyIncomeExpense>, RytError> = super::convert_invoke(res);
+            match ret {
+                Ok(v) => months.1.set(v),
+                Err(v) => {
+                    super::error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            }
+
+            let res = tauri::invoke("get_spending_by_category", &args).await;
+            let ret: Result<Vec<CategorySpending>, RytError> = super::convert_invoke(res);
+            // TODO handle error
+            categories.1.set(ret.unwrap());
+
+            let res = tauri::invoke("get_top_payees", &args).await;
+            let ret: Result<Vec<PayeeSpending>, RytError> = super::convert_invoke(res);
+            // TODO handle error
+            payees.1.set(ret.unwrap());
+
+            // The last month of the range against the one before
+            #[derive(Serialize, Deserialize)]
+            struct MonthArgs {
+                month: i64,
+            }
+
+            let end_date = DateTime::from_timestamp(end, 0).unwrap_or_default();
+            let month = data::month_key(end_date.year(), end_date.month());
+            let res = tauri::invoke("get_category_comparison", &MonthArgs { month }).await;
+            let ret: Result<Vec<CategoryComparison>, RytError> = super::convert_invoke(res);
+            // TODO handle error
+            comparison.1.set(ret.unwrap());
+
+            // Give the chart elements a moment to be in the page
+            TimeoutFuture::new(100).await;
+
+            super::js::build_chart(
+                "report_income_expense".to_string(),
+                income_expense_chart(&months.0.get_untracked()),
+            );
+            super::js::build_chart(
+                "report_categories".to_string(),
+                category_chart(&categories.0.get_untracked()),
+            );
+        },
+    );
+
+    let totals = move || -> (i64, i64) {
+        months.0.with(|m| {
+            (
+                m.iter().map(|v| v.income).sum(),
+                m.iter().map(|v| v.expense).sum(),
+            )
+        })
+    };
+
+    view! {
+        <h1>"Reports"</h1>
+
+        <div class="row g-2 mb-3 align-items-center">
+            <div class="col-auto">
+                <div class="btn-group btn-group-sm" role="group">
+                    <button type="button" class="btn btn-outline-secondary"
+                        on:click = move |_| range_set.set((month_start(0), Utc::now().timestamp()))
+                    >
+                        "This Month"
+                    </button>
+                    <button type="button" class="btn btn-outline-secondary"
+                        on:click = move |_| range_set.set((month_start(2), Utc::now().timestamp()))
+                    >
+                        "Last 3 Months"
+                    </button>
+                    <button type="button" class="btn btn-outline-secondary"
+                        on:click = move |_| range_set.set((year_start(), Utc::now().timestamp()))
+                    >
+                        "This Year"
+                    </button>
+                    <button type="button" class="btn btn-outline-secondary"
+                        on:click = move |_| range_set.set((month_start(11), Utc::now().timestamp()))
+                    >
+                        "Last 12 Months"
+                    </button>
+                </div>
+            </div>
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="date" title="From"
+                    prop:value = move || unix_to_input(range.get().0)
+                    on:change = move |ev| {
+                        if let Some(v) = input_to_unix(&event_target_value(&ev)) {
+                            range_set.update(|r| r.0 = v);
+                        }
+                    }
+                />
+            </div>
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="date" title="To"
+                    prop:value = move || unix_to_input(range.get().1)
+                    on:change = move |ev| {
+                        // The whole last day
+                        if let Some(v) = input_to_unix(&event_target_value(&ev)) {
+                            range_set.update(|r| r.1 = v + 24 * 60 * 60 - 1);
+                        }
+                    }
+                />
+            </div>
+        </div>
+
+        <div class="row mb-3">
+            <div class="col">
+                <div class="rounded-3 p-3 px-4 bg-success-subtle">
+                    <h2>{move || data::amount_to_display(totals().0)}</h2>
+                    <p class="text-secondary mb-0">"Income"</p>
+                </div>
+            </div>
+            <div class="col">
+                <div class="rounded-3 p-3 px-4 bg-danger-subtle">
+                    <h2>{move || data::amount_to_display(totals().1)}</h2>
+                    <p class="text-secondary mb-0">"Spending"</p>
+                </div>
+            </div>
+            <div class="col">
+                <div class="rounded-3 p-3 px-4 bg-secondary-subtle">
+                    <h2>{move || data::amount_to_display(totals().0 - totals().1)}</h2>
+                    <p class="text-secondary mb-0">"Net"</p>
+                </div>
+            </div>
+        </div>
+
+        <h5>"Income vs. Spending"</h5>
+        <div id="report_income_expense" style="width: 100%; height: 300px;"></div>
+
+        <div class="row mt-3">
+            <div class="col-6">
+                <h5>"Spending by Category"</h5>
+                <div id="report_categories" style="width: 100%; height: 300px;"></div>
+            </div>
+            <div class="col-6">
+                <h5>"Top Payees"</h5>
+                <table class="table table-sm">
+                    <thead>
+                        <tr>
+                            <th scope="col">"Payee"</th>
+                            <th scope="col">"Transactions"</th>
+                            <th scope="col" class="text-end">"Spent"</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                    {
+                        move || {
+                            payees.0.get().into_iter().map(
+                            |p| view! {
+                                <tr>
+                                    <td>{p.payee}</td>
+                                    <td>{p.transaction_count}</td>
+                                    <td class="text-end">{data::amount_to_display(p.amount)}</td>
+                                </tr>
+                            }
+                            ).collect_view()
+                        }
+                    }
+                    </tbody>
+                </table>
+            </div>
+        </div>
+
+        <h5 class="mt-3">
+        {
+            move || {
+                let end_date = DateTime::from_timestamp(range.get().1, 0).unwrap_or_default();
+                let month = data::month_key(end_date.year(), end_date.month());
+                format!("{} Compared to {}", month_key_to_display(month), month_key_to_display(previous_month(month)))
+            }
+        }
+        </h5>
+        <table class="table table-sm">
+            <thead>
+                <tr>
+                    <th scope="col">"Category"</th>
+                    <th scope="col" class="text-end">"Previous"</th>
+                    <th scope="col" class="text-end">"Current"</th>
+                    <th scope="col" class="text-end">"Change"</th>
+                </tr>
+            </thead>
+            <tbody>
+            {
+                move || {
+                    comparison.0.get().into_iter().map(
+                    |c| {
+                        let change = match c.change_percent() {
+                            Some(percent) => format!("{} ({:+}%)", data::amount_to_display(c.change()), percent),
+                            None => data::amount_to_display(c.change()),
+                        };
+
+                        view! {
+                            <tr>
+                                <td>{category_name(&c.display_name)}</td>
+                                <td class="text-end">{data::amount_to_display(c.previous)}</td>
+                                <td class="text-end">{data::amount_to_display(c.current)}</td>
+                                <td class="text-end"
+                                    class:text-danger = c.change() > 0
+                                    class:text-success = c.change() < 0
+                                >
+                                    {change}
+                                </td>
+                            </tr>
+                        }
+                    }
+                    ).collect_view()
+                }
+            }
+            </tbody>
+        </table>
+    }
+}
@@ -0,0 +1,327 @@
+use leptos::html::*;
+use leptos::leptos_dom::ev::MouseEvent;
+use leptos::logging::*;
+use leptos::*;
+
+use tauri_sys::tauri;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::error_modal;
+use data::account::AccountDisplay;
+use data::category::Category;
+use data::payee_rule::*;
+use data::RytError;
+
+use crate::app::GlobalState;
+
+async fn get_rules() -> Vec<PayeeRule> {
+    let res = tauri::invoke("get_payee_rules", &crate::app::NoArgs {}).await;
+    let ret: Result<Vec<PayeeRule>, RytError> = crate::app::convert_invoke(res);
+    // TODO handle error
+    return ret.unwrap();
+}
+
+// Empty input is no limit
+fn parse_amount_input(input: NodeRef<html::Input>) -> Option<i64> {
+    return input
+        .get_untracked()
+        .unwrap()
+        .value()
+        .parse::<f64>()
+        .ok()
+        .map(data::dollars_to_cents);
+}
+
+// Short description of what a rule matches
+fn rule_condition(rule: &PayeeRule, accounts: &[AccountDisplay]) -> String {
+    let mut ret = format!(
+        "Payee {} \"{}\"",
+        rule.match_kind.display_name().to_lowercase(),
+        rule.pattern
+    );
+
+    if let Some(a) = accounts.iter().find(|a| a.account_id == rule.account_id) {
+        ret.push_str(&format!(", in {}", a.display_name));
+    }
+    if let Some(min) = rule.min_amount {
+        ret.push_str(&format!(", at least {}", data::amount_to_display(min)));
+    }
+    if let Some(max) = rule.max_amount {
+        ret.push_str(&format!(", at most {}", data::amount_to_display(max)));
+    }
+    return ret;
+}
+
+// Short description of what a rule does
+fn rule_action(rule: &PayeeRule, categories: &[Category]) -> String {
+    let mut parts: Vec<String> = vec![];
+
+    if let Some(c) = categories.iter().find(|c| c.id == rule.category_id) {
+        parts.push(format!("Category {}", c.display_name));
+    }
+    if !rule.rename_payee.is_empty() {
+        parts.push(format!("Rename to \"{}\"", rule.rename_payee));
+    }
+    if !rule.notes.is_empty() {
+        parts.push(format!("Notes \"{}\"", rule.notes));
+    }
+    return parts.join(", ");
+}
+
+#[component]
+pub fn Rules() -> impl IntoView {
+    let global_state = expect_context::<RwSignal<GlobalState>>();
+
+    let rules = create_signal::<Vec<PayeeRule>>(vec![]);
+    let accounts = create_signal::<Vec<AccountDisplay>>(vec![]);
+    let categories = create_signal::<Vec<Category>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let res = tauri::invoke("get_account_display_list", &crate::app::NoArgs {}).await;
+            let ret: Result<Vec<AccountDisplay>, RytError> = crate::app::convert_invoke(res);
+            // TODO handle error
+            accounts.1.set(ret.unwrap());
+
+            let res = tauri::invoke("get_categories", &crate::app::NoArgs {}).await;
+            let ret: Result<Vec<Category>, RytError> = crate::app::convert_invoke(res);
+            // TODO handle error
+            categories.1.set(ret.unwrap());
+
+            rules.1.set(get_rules().await);
+        },
+    );
+
+    // Message after running the rules over existing transactions
+    let (applied, applied_set) = create_signal::<String>(String::new());
+
+    let match_select: NodeRef<html::Select> = create_node_ref();
+    let pattern_input: NodeRef<html::Input> = create_node_ref();
+    let account_select: NodeRef<html::Select> = create_node_ref();
+    let min_amount_input: NodeRef<html::Input> = create_node_ref();
+    let max_amount_input: NodeRef<html::Input> = create_node_ref();
+    let category_input: NodeRef<html::Input> = create_node_ref();
+    let rename_input: NodeRef<html::Input> = create_node_ref();
+    let notes_input: NodeRef<html::Input> = create_node_ref();
+
+    let create_rule = move |ev: MouseEvent| {
+        let match_kind = match_select
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<i64>()
+            .ok()
+            .and_then(PayeeMatch::from_i64)
+            .unwrap_or(PayeeMatch::Contains);
+
+        let mut rule = PayeeRule::new(match_kind, pattern_input.get_untracked().unwrap().value());
+        rule.account_id = account_select
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<i64>()
+            .unwrap_or(0);
+        rule.min_amount = parse_amount_input(min_amount_input);
+        rule.max_amount = parse_amount_input(max_amount_input);
+        rule.rename_payee = rename_input
+            .get_untracked()
+            .unwrap()
+            .value()
+            .trim()
+            .to_string();
+        rule.notes = notes_input.get_untracked().unwrap().value();
+
+        let category_name = category_input.get_untracked().unwrap().value();
+
+        spawn_local(async move {
+            rule.category_id = match crate::app::transactions::get_or_create_category(
+                category_name.trim(),
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            };
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                rule: PayeeRule,
+            }
+
+            let res = tauri::invoke("create_payee_rule", &Args { rule }).await;
+            let ret: Result<i64, RytError> = crate::app::convert_invoke(res);
+            if let Err(v) = ret {
+                error_modal::show_error(v.to_string(), &global_state);
+                return;
+            }
+
+            rules.1.set(get_rules().await);
+        });
+    };
+
+    let delete_rule = move |rule_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                rid: i64,
+            }
+
+            let res = tauri::invoke("delete_payee_rule", &Args { rid: rule_id }).await;
+            let ret: Result<(), RytError> = crate::app::convert_invoke(res);
+            if let Err(v) = ret {
+                error_modal::show_error(v.to_string(), &global_state);
+                return;
+            }
+
+            rules.1.set(get_rules().await);
+        });
+    };
+
+    let apply_rules = move |ev: MouseEvent| {
+        spawn_local(async move {
+            let res = tauri::invoke("apply_payee_rules", &crate::app::NoArgs {}).await;
+            let ret: Result<i64, RytError> = crate::app::convert_invoke(res);
+            match ret {
+                Ok(count) => applied_set.set(format!("{} transactions updated.", count)),
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+            }
+        });
+    };
+
+    view! {
+        <div class="container-fluid">
+          <div class="row">
+            <div class="col">
+                <h1>"Payee Rules"</h1>
+            </div>
+            <div class="col text-end">
+                <button type="button" class="btn btn-secondary me-2" on:click=apply_rules>
+                    "Apply To Uncategorized"
+                </button>
+                <button type="button" class="btn btn-secondary" data-bs-toggle="modal" data-bs-target="#rule_create">
+                    "Add Rule"
+                </button>
+            </div>
+          </div>
+
+          <p class="text-body-secondary">
+            "Imported transactions are filled in by the first rule they match. A category from the file is kept."
+          </p>
+          <p>{move || applied.get()}</p>
+
+          <table class="table table-sm">
+            <thead>
+                <tr>
+                    <th scope="col">"When"</th>
+                    <th scope="col">"Then"</th>
+                    <th scope="col"></th>
+                </tr>
+            </thead>
+            <tbody>
+            {
+                move || {
+                    let account_list = accounts.0.get();
+                    let category_list = categories.0.get();
+                    rules.0.get().into_iter().map(
+                    |r| {
+                        let rule_id = r.id;
+                        view! {
+                            <tr>
+                                <td>{rule_condition(&r, &account_list)}</td>
+                                <td>{rule_action(&r, &category_list)}</td>
+                                <td class="text-end">
+                                    <button class="btn btn-outline-danger btn-sm" type="button"
+                                        on:click=move |_| delete_rule(rule_id)
+                                    >
+                                        "Delete"
+                                    </button>
+                                </td>
+                            </tr>
+                        }
+                    }
+                    ).collect_view()
+                }
+            }
+            </tbody>
+          </table>
+        </div>
+
+        <div class="modal fade" id="rule_create" tabindex="-1" aria-labelledby="rule_create" aria-hidden="true">
+           <div class="modal-dialog modal-dialog-centered">
+             <div class="modal-content">
+               <div class="modal-header">
+                 <h1 class="modal-title fs-5">"Add Rule"</h1>
+                 <button type="button" class="btn-close" data-bs-dismiss="modal" aria-label="Close"></button>
+               </div>
+
+               <div class="modal-body">
+                    <h6>"When"</h6>
+                    <div class="input-group mb-3">
+                        <span class="input-group-text">"Payee"</span>
+                        <select class="form-select" node_ref=match_select>
+                        {
+                            [PayeeMatch::Contains, PayeeMatch::Equals, PayeeMatch::Regex].into_iter().map(
+                            |m| {
+                                view! {
+                                    <option value={m.to_i64()}>{m.display_name()}</option>
+                                }
+                            }
+                            ).collect_view()
+                        }
+                        </select>
+                        <input class="form-control" type="text" node_ref=pattern_input/>
+                    </div>
+
+                    <label class="col-form-label">"Account"</label>
+                    <select class="form-select mb-3" node_ref=account_select>
+                        <option value="0">"Any Account"</option>
+                        {
+                            move || {
+                                accounts.0.get().into_iter().map(
+                                |a| {
+                                    view! {
+                                        <option value={a.account_id}>{a.display_name}</option>
+                                    }
+                                }
+                                ).collect_view()
+                            }
+                        }
+                    </select>
+
+                    <div class="row mb-3">
+                        <div class="col">
+                            <label class="col-form-label">"Amount At Least"</label>
+                            <input class="form-control" type="number" placeholder="-100.00" node_ref=min_amount_input/>
+                        </div>
+                        <div class="col">
+                            <label class="col-form-label">"Amount At Most"</label>
+                            <input class="form-control" type="number" placeholder="0.00" node_ref=max_amount_input/>
+                        </div>
+                    </div>
+
+                    <h6>"Then"</h6>
+                    <label class="col-form-label">"Category"</label>
+                    <input class="form-control mb-3" type="text" node_ref=category_input/>
+
+                    <label class="col-form-label">"Rename Payee"</label>
+                    <input class="form-control mb-3" type="text" node_ref=rename_input/>
+
+                    <label class="col-form-label">"Notes"</label>
+                    <input class="form-control mb-3" type="text" node_ref=notes_input/>
+               </div>
+
+               <div class="modal-footer">
+                 <button type="button" class="btn btn-secondary" data-bs-dismiss="modal">"Cancel"</button>
+                 <button type="button" class="btn btn-primary" data-bs-dismiss="modal" on:click=create_rule>
+                    "Add"
+                 </button>
+               </div>
+             </div>
+           </div>
+         </div>
+    }
+}
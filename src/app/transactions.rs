@@ -14,30 +14,425 @@ use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::{from_value, to_value};
 
 use crate::app::error_modal;
+use data::account::*;
+use data::category::Category;
 use data::transaction::*;
+use data::transaction_query::*;
+use data::transaction_split::*;
 use data::RytError;
 
-async fn get_transactions_list() -> TransactionDisplayList {
-    let res = tauri::invoke("get_all_transactions_display", &super::NoArgs {}).await;
-    let ret: Result<TransactionDisplayList, RytError> = super::convert_invoke(res);
+async fn get_transactions_list(query: TransactionQuery) -> TransactionPage {
+    #[derive(Serialize, Deserialize)]
+    struct Args {
+        query: TransactionQuery,
+    }
+
+    let res = tauri::invoke("query_transactions", &Args { query }).await;
+    let ret: Result<TransactionPage, RytError> = super::convert_invoke(res);
 
     // TODO handle error
     return ret.unwrap();
 }
 
+async fn get_account_list() -> Vec<AccountDisplay> {
+    let res = tauri::invoke("get_account_display_list", &super::NoArgs {}).await;
+    let ret: Result<Vec<AccountDisplay>, RytError> = super::convert_invoke(res);
+
+    // TODO handle error
+    return ret.unwrap();
+}
+
+// Empty name means no category. Creates the category if it doesn't exist yet.
+pub async fn get_or_create_category(name: &str) -> Result<i64, RytError> {
+    if name.is_empty() {
+        return Ok(0);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Args<'a> {
+        name: &'a str,
+    }
+
+    let res = tauri::invoke("get_category_id", &Args { name: name }).await;
+    let ret: Result<i64, RytError> = super::convert_invoke(res);
+    if let Ok(v) = ret {
+        return Ok(v);
+    }
+
+    let res = tauri::invoke("create_category", &Args { name: name }).await;
+    return super::convert_invoke(res);
+}
+
+// Unix timestamp of a "2024-03-15" date input, None when it's empty or invalid
+fn date_input_to_unix(value: &str) -> Option<i64> {
+    if value.is_empty() {
+        return None;
+    }
+
+    let date_val = format!("{value}T00:00:00");
+    return match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+        Ok(v) => Some(v.assume_utc().unix_timestamp()),
+        Err(_) => None,
+    };
+}
+
+// Cents of a dollar amount input, None when it's empty or invalid
+fn dollars_input_to_cents(value: &str) -> Option<i64> {
+    return value.trim().parse::<f64>().ok().map(data::dollars_to_cents);
+}
+
+pub fn date_to_display(unix_date: i64) -> String {
+    match time::OffsetDateTime::from_unix_timestamp(unix_date) {
+        Ok(v) => {
+            // Unwrap safe here, these formats are always known. Incorrect
+            // format is programmer error.
+            let format_desc = time::format_description::parse("[year]-[month]-[day]").unwrap();
+            return v.format(&format_desc).unwrap();
+        }
+        _ => return String::new(),
+    }
+}
+
+// One line of the split editor, as typed. Amounts are dollars in the direction of the transaction.
+#[derive(Clone, Debug, Default)]
+struct SplitLine {
+    category: String,
+    notes: String,
+    amount: String,
+}
+
+impl SplitLine {
+    fn from_display(split: &TransactionSplitDisplay) -> SplitLine {
+        SplitLine {
+            category: split.category_display.clone(),
+            notes: split.split_raw.notes.clone(),
+            amount: format!(
+                "{:.2}",
+                data::cents_to_dollars(split.split_raw.amount.abs())
+            ),
+        }
+    }
+}
+
+// Lines with an amount become splits, outflows when the transaction is an outflow.
+// Categories are created if they don't exist yet.
+async fn lines_to_splits(
+    lines: Vec<SplitLine>,
+    outflow: bool,
+) -> Result<Vec<TransactionSplit>, RytError> {
+    let mut ret: Vec<TransactionSplit> = vec![];
+    for line in lines {
+        let dollars: f64 = line.amount.trim().parse::<f64>().unwrap_or(0.0);
+        if dollars == 0.0 {
+            continue;
+        }
+
+        let cents = data::dollars_to_cents(dollars.abs());
+        let category_id = get_or_create_category(line.category.trim()).await?;
+
+        let mut split = TransactionSplit::new(category_id, if outflow { -cents } else { cents });
+        split.notes = line.notes;
+        ret.push(split);
+    }
+
+    return Ok(ret);
+}
+
+#[component]
+fn SplitEditor(lines: RwSignal<Vec<SplitLine>>) -> impl IntoView {
+    // Only redraw when lines are added or removed, so typing doesn't lose focus
+    let line_count = create_memo(move |_| lines.with(|l| l.len()));
+
+    let total = move || -> i64 {
+        lines.with(|l| {
+            l.iter()
+                .map(|v| data::dollars_to_cents(v.amount.trim().parse::<f64>().unwrap_or(0.0)))
+                .sum()
+        })
+    };
+
+    view! {
+        <div class="p-2">
+        {
+            move || {
+                (0..line_count.get()).map(
+                |i| {
+                    let line = lines.with_untracked(|l| l[i].clone());
+                    view! {
+                        <div class="row g-1 mb-1">
+                            <div class="col">
+                                <input class="form-control form-control-sm" placeholder="Category" value={line.category}
+                                    on:input = move |ev| {
+                                        let v = event_target_value(&ev);
+                                        lines.update(|l| l[i].category = v);
+                                    }
+                                />
+                            </div>
+                            <div class="col">
+                                <input class="form-control form-control-sm" placeholder="Notes" value={line.notes}
+                                    on:input = move |ev| {
+                                        let v = event_target_value(&ev);
+                                        lines.update(|l| l[i].notes = v);
+                                    }
+                                />
+                            </div>
+                            <div class="col-2">
+                                <input class="form-control form-control-sm" placeholder="Amount" type="number" value={line.amount}
+                                    on:input = move |ev| {
+                                        let v = event_target_value(&ev);
+                                        lines.update(|l| l[i].amount = v);
+                                    }
+                                />
+                            </div>
+                            <div class="col-auto">
+                                <button type="button" class="btn btn-outline-danger btn-sm"
+                                    on:click = move |_| { lines.update(|l| { l.remove(i); }); }
+                                >
+                                    "Remove"
+                                </button>
+                            </div>
+                        </div>
+                    }
+                }
+                ).collect_view()
+            }
+        }
+            <button type="button" class="btn btn-outline-secondary btn-sm"
+                on:click = move |_| { lines.update(|l| l.push(SplitLine::default())); }
+            >
+                "Add Line"
+            </button>
+            <span class="ms-2 text-secondary">
+                {move || format!("Lines total ${:.2}", data::cents_to_dollars(total()))}
+            </span>
+        </div>
+    }
+}
+
 #[component]
 pub fn Transactions() -> impl IntoView {
     let global_state = expect_context::<RwSignal<super::GlobalState>>();
 
-    let transactions = create_signal::<TransactionDisplayList>(TransactionDisplayList::new());
+    // Filter, sort and page shown. Changing it loads the rows again.
+    let (query, query_set) = create_signal(TransactionQuery::new());
+
+    let transactions = create_signal::<TransactionPage>(TransactionPage::new());
+    create_resource(
+        move || query.get(),
+        move |q| async move {
+            let lst = get_transactions_list(q).await;
+            transactions.1.set(lst);
+        },
+    );
+
+    let categories = create_signal::<Vec<Category>>(vec![]);
     create_resource(
         || (),
         move |_| async move {
-            let lst = get_transactions_list().await;
-            transactions.1.set(lst);
+            let res = tauri::invoke("get_categories", &super::NoArgs {}).await;
+            let ret: Result<Vec<Category>, RytError> = super::convert_invoke(res);
+            // TODO handle error
+            categories.1.set(ret.unwrap());
         },
     );
 
+    // Change the filter and go back to the first page
+    let set_filter = move |f: Box<dyn FnOnce(&mut TransactionFilter)>| {
+        query_set.update(|q| {
+            f(&mut q.filter);
+            q.offset = 0;
+        });
+    };
+
+    let accounts = create_signal::<Vec<AccountDisplay>>(vec![]);
+    create_resource(
+        || (),
+        move |_| async move {
+            let lst = get_account_list().await;
+            accounts.1.set(lst);
+        },
+    );
+
+    // Row currently being edited inline, 0 for none
+    let (editing_id, editing_id_set) = create_signal::<i64>(0);
+
+    let handle = window_event_listener(ev::keydown, move |ev| {
+        let code = ev.key_code();
+        if code == 27 {
+            editing_id_set.set(0);
+        }
+    });
+
+    let edit_payee_nr: NodeRef<html::Input> = create_node_ref();
+    let edit_date_nr: NodeRef<html::Input> = create_node_ref();
+    let edit_account_nr: NodeRef<html::Select> = create_node_ref();
+    let edit_category_nr: NodeRef<html::Input> = create_node_ref();
+    let edit_notes_nr: NodeRef<html::Input> = create_node_ref();
+    let edit_outflow_nr: NodeRef<html::Input> = create_node_ref();
+    let edit_inflow_nr: NodeRef<html::Input> = create_node_ref();
+
+    // Split lines of the row being edited, empty when it isn't split
+    let edit_lines = create_rw_signal::<Vec<SplitLine>>(vec![]);
+    let edit_split = create_memo(move |_| edit_lines.with(|l| !l.is_empty()));
+
+    let save_transaction = move |trans_id: i64| {
+        spawn_local(async move {
+            // parse amounts, entered in dollars
+            let outflow: f64 = match edit_outflow_nr
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<f64>()
+            {
+                Ok(v) => v,
+                Err(v) => 0.0,
+            };
+            let inflow: f64 = match edit_inflow_nr
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<f64>()
+            {
+                Ok(v) => v,
+                Err(v) => 0.0,
+            };
+
+            // convert date to unix timestamp
+            let mut date_val: String = edit_date_nr.get_untracked().unwrap().value();
+            date_val.push_str("T00:00:00");
+            let date = match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error("Error parsing date".to_string(), &global_state);
+                    return;
+                }
+            };
+            let unix_date = date.assume_utc().unix_timestamp();
+
+            let account_id: i64 = match edit_account_nr
+                .get_untracked()
+                .unwrap()
+                .value()
+                .parse::<i64>()
+            {
+                Ok(v) => v,
+                Err(v) => 0,
+            };
+
+            let mut trans = match Transaction::new(
+                edit_payee_nr.get_untracked().unwrap().value(),
+                data::dollars_to_cents(inflow),
+                data::dollars_to_cents(outflow),
+                unix_date,
+                account_id,
+            ) {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            };
+            trans.id = trans_id;
+            trans.notes = edit_notes_nr.get_untracked().unwrap().value();
+
+            let category_name: String = edit_category_nr.get_untracked().unwrap().value();
+            trans.category_id = match get_or_create_category(category_name.trim()).await {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            };
+
+            // No lines saves it as an ordinary transaction
+            let splits = match lines_to_splits(edit_lines.get_untracked(), trans.amount < 0).await {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            };
+
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                trans: Transaction,
+                splits: Vec<TransactionSplit>,
+            }
+
+            let ret = tauri::invoke("update_split_transaction", &Args { trans, splits }).await;
+            let res: Result<(), RytError> = super::convert_invoke(ret);
+            match res {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            editing_id_set.set(0);
+
+            let lst = get_transactions_list(query.get_untracked()).await;
+            transactions.1.set(lst);
+        });
+    };
+
+    let delete_transaction = move |trans_id: i64| {
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                tid: i64,
+            }
+
+            let ret = tauri::invoke("delete_transaction", &Args { tid: trans_id }).await;
+            let res: Result<(), RytError> = super::convert_invoke(ret);
+            match res {
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+                _ => {}
+            }
+
+            editing_id_set.set(0);
+
+            let lst = get_transactions_list(query.get_untracked()).await;
+            transactions.1.set(lst);
+        });
+    };
+
+    // Flip between cleared and uncleared. Reconciled transactions aren't toggled here.
+    let toggle_cleared = move |trans: Transaction| {
+        let cleared = match trans.cleared {
+            ClearedStatus::Uncleared => ClearedStatus::Cleared,
+            _ => ClearedStatus::Uncleared,
+        };
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                tid: i64,
+                cleared: ClearedStatus,
+            }
+
+            let ret = tauri::invoke(
+                "set_transaction_cleared",
+                &Args {
+                    tid: trans.id,
+                    cleared,
+                },
+            )
+            .await;
+            let res: Result<(), RytError> = super::convert_invoke(ret);
+            if let Err(v) = res {
+                error_modal::show_error(v.to_string(), &global_state);
+                return;
+            }
+
+            let lst = get_transactions_list(query.get_untracked()).await;
+            transactions.1.set(lst);
+        });
+    };
+
     let create_transaction_payee_nr: NodeRef<html::Input> = create_node_ref();
     let create_transaction_date_nr: NodeRef<html::Input> = create_node_ref();
     let create_transaction_category_nr: NodeRef<html::Input> = create_node_ref();
@@ -45,6 +440,9 @@ pub fn Transactions() -> impl IntoView {
     let (outflow_get, outflow_set) = create_signal("".to_string());
     let (inflow_get, inflow_set) = create_signal("".to_string());
 
+    let create_lines = create_rw_signal::<Vec<SplitLine>>(vec![]);
+    let create_split = create_memo(move |_| create_lines.with(|l| !l.is_empty()));
+
     let create_transaction = move |ev: SubmitEvent| {
         ev.prevent_default();
         spawn_local(async move {
@@ -88,8 +486,16 @@ pub fn Transactions() -> impl IntoView {
                 }
             };
 
-            // get category id from name
-            {
+            let splits = match lines_to_splits(create_lines.get_untracked(), outflow != 0).await {
+                Ok(v) => v,
+                Err(v) => {
+                    error_modal::show_error(v.to_string(), &global_state);
+                    return;
+                }
+            };
+
+            // get category id from name, split transactions use their lines instead
+            if splits.is_empty() {
                 #[derive(Serialize, Deserialize)]
                 struct Args<'a> {
                     name: &'a str,
@@ -116,24 +522,235 @@ pub fn Transactions() -> impl IntoView {
             #[derive(Serialize, Deserialize)]
             struct Args {
                 trans: Transaction,
+                splits: Vec<TransactionSplit>,
             }
 
-            let ret = tauri::invoke("create_transaction", &Args { trans: trans }).await;
+            let ret = tauri::invoke("create_split_transaction", &Args { trans, splits }).await;
             let res: Result<i64, RytError> = super::convert_invoke(ret);
+            match res {
+                Err(v) => error_modal::show_error(v.to_string(), &global_state),
+                _ => create_lines.set(vec![]),
+            }
+
+            let lst = get_transactions_list(query.get_untracked()).await;
+            transactions.1.set(lst);
+        });
+    };
+
+    let transfer_from_nr: NodeRef<html::Select> = create_node_ref();
+    let transfer_to_nr: NodeRef<html::Select> = create_node_ref();
+    let transfer_amount_nr: NodeRef<html::Input> = create_node_ref();
+    let transfer_date_nr: NodeRef<html::Input> = create_node_ref();
+    let transfer_notes_nr: NodeRef<html::Input> = create_node_ref();
+
+    let create_transfer = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        let select_id = |nr: NodeRef<html::Select>| -> i64 {
+            nr.get_untracked()
+                .unwrap()
+                .value()
+                .parse::<i64>()
+                .unwrap_or(0)
+        };
+        let from = select_id(transfer_from_nr);
+        let to = select_id(transfer_to_nr);
+
+        let amount: f64 = transfer_amount_nr
+            .get_untracked()
+            .unwrap()
+            .value()
+            .parse::<f64>()
+            .unwrap_or(0.0);
+
+        let mut date_val: String = transfer_date_nr.get_untracked().unwrap().value();
+        date_val.push_str("T00:00:00");
+        let date = match PrimitiveDateTime::parse(&date_val, &Iso8601::DEFAULT) {
+            Ok(v) => v.assume_utc().unix_timestamp(),
+            Err(v) => {
+                error_modal::show_error("Error parsing date".to_string(), &global_state);
+                return;
+            }
+        };
+
+        let notes = transfer_notes_nr.get_untracked().unwrap().value();
+
+        spawn_local(async move {
+            #[derive(Serialize, Deserialize)]
+            struct Args {
+                from: i64,
+                to: i64,
+                amount: i64,
+                date: i64,
+                notes: String,
+            }
+
+            let ret = tauri::invoke(
+                "create_account_transfer",
+                &Args {
+                    from,
+                    to,
+                    amount: data::dollars_to_cents(amount),
+                    date,
+                    notes,
+                },
+            )
+            .await;
+            let res: Result<(i64, i64), RytError> = super::convert_invoke(ret);
             match res {
                 Err(v) => error_modal::show_error(v.to_string(), &global_state),
                 _ => {}
             }
 
-            let lst = get_transactions_list().await;
+            let lst = get_transactions_list(query.get_untracked()).await;
             transactions.1.set(lst);
         });
     };
 
+    let account_options = move || {
+        accounts
+            .0
+            .get()
+            .into_iter()
+            .map(|acc| {
+                view! {
+                    <option value={acc.account_id}>{acc.display_name}</option>
+                }
+            })
+            .collect_view()
+    };
+
     view! {
         <h1>
             Transactions
         </h1>
+        <div class="row g-2 mb-2">
+            <div class="col-3">
+                <input class="form-control form-control-sm" placeholder="Search payees"
+                    on:change = move |ev| {
+                        let v = event_target_value(&ev);
+                        set_filter(Box::new(move |f| f.payee = v));
+                    }
+                />
+            </div>
+            <div class="col">
+                <select class="form-select form-select-sm"
+                    on:change = move |ev| {
+                        let v = event_target_value(&ev).parse::<i64>().ok();
+                        set_filter(Box::new(move |f| f.account_id = v));
+                    }
+                >
+                    <option value="">"All Accounts"</option>
+                    {account_options}
+                </select>
+            </div>
+            <div class="col">
+                <select class="form-select form-select-sm"
+                    on:change = move |ev| {
+                        let v = event_target_value(&ev).parse::<i64>().ok();
+                        set_filter(Box::new(move |f| f.category_id = v));
+                    }
+                >
+                    <option value="">"All Categories"</option>
+                    {
+                        move || {
+                            categories.0.get().into_iter().map(
+                            |c| view! {
+                                <option value={c.id}>{c.display_name}</option>
+                            }
+                            ).collect_view()
+                        }
+                    }
+                </select>
+            </div>
+            <div class="col">
+                <select class="form-select form-select-sm"
+                    on:change = move |ev| {
+                        let v = event_target_value(&ev).parse::<i64>().ok().and_then(ClearedStatus::from_i64);
+                        set_filter(Box::new(move |f| f.cleared = v));
+                    }
+                >
+                    <option value="">"Any State"</option>
+                    {
+                        [ClearedStatus::Uncleared, ClearedStatus::Cleared, ClearedStatus::Reconciled].into_iter().map(
+                        |c| view! {
+                            <option value={c.to_i64()}>{c.display_name()}</option>
+                        }
+                        ).collect_view()
+                    }
+                </select>
+            </div>
+            <div class="col">
+                <select class="form-select form-select-sm"
+                    on:change = move |ev| {
+                        // Options are numbered in the order of TransactionSort::all
+                        let index = event_target_value(&ev).parse::<usize>().unwrap_or(0);
+                        if let Some(sort) = TransactionSort::all().get(index).copied() {
+                            query_set.update(|q| {
+                                q.sort = sort;
+                                q.offset = 0;
+                            });
+                        }
+                    }
+                >
+                    {
+                        TransactionSort::all().into_iter().enumerate().map(
+                        |(i, s)| view! {
+                            <option value={i}>{s.display_name()}</option>
+                        }
+                        ).collect_view()
+                    }
+                </select>
+            </div>
+        </div>
+        <div class="row g-2 mb-3 align-items-center">
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="date" title="From"
+                    on:change = move |ev| {
+                        let v = date_input_to_unix(&event_target_value(&ev));
+                        set_filter(Box::new(move |f| f.start_date = v));
+                    }
+                />
+            </div>
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="date" title="To"
+                    on:change = move |ev| {
+                        // The whole last day
+                        let v = date_input_to_unix(&event_target_value(&ev)).map(|d| d + 24 * 60 * 60 - 1);
+                        set_filter(Box::new(move |f| f.end_date = v));
+                    }
+                />
+            </div>
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="number" placeholder="Min amount"
+                    title="Outflows are negative"
+                    on:change = move |ev| {
+                        let v = dollars_input_to_cents(&event_target_value(&ev));
+                        set_filter(Box::new(move |f| f.min_amount = v));
+                    }
+                />
+            </div>
+            <div class="col-auto">
+                <input class="form-control form-control-sm" type="number" placeholder="Max amount"
+                    title="Outflows are negative"
+                    on:change = move |ev| {
+                        let v = dollars_input_to_cents(&event_target_value(&ev));
+                        set_filter(Box::new(move |f| f.max_amount = v));
+                    }
+                />
+            </div>
+            <div class="col-auto">
+                <div class="form-check">
+                    <input type="checkbox" class="form-check-input" id="filter_uncategorized"
+                        on:change = move |ev| {
+                            let v = event_target_checked(&ev);
+                            set_filter(Box::new(move |f| f.uncategorized_only = v));
+                        }
+                    />
+                    <label class="form-check-label" for="filter_uncategorized">"Uncategorized only"</label>
+                </div>
+            </div>
+        </div>
         <table class="table table-sm">
             <thead>
                 <tr>
@@ -141,15 +758,102 @@ pub fn Transactions() -> impl IntoView {
                     <th scope="col">Date</th>
                     <th scope="col">Account</th>
                     <th scope="col">Category</th>
+                    <th scope="col">Notes</th>
                     <th scope="col">Outflow</th>
                     <th scope="col">Inflow</th>
+                    <th scope="col" title="Cleared">"C"</th>
+                    <th scope="col"></th>
                 </tr>
             </thead>
             <tbody>
             {
                 move || {
+                    let account_list = accounts.0.get();
+
                     transactions.0.get().transactions.into_iter().map(
                     |val| {
+                        let trans_id = val.trans_raw.id;
+                        let date = date_to_display(val.trans_raw.date);
+
+                        // Transfers have no category, the other side follows any edit
+                        let is_transfer = val.trans_raw.is_transfer();
+
+                        if editing_id.get() == trans_id {
+                            let mut outflow = String::new();
+                            let mut inflow = String::new();
+                            if val.trans_raw.amount > 0 {
+                                inflow = format!("{:.2}", data::cents_to_dollars(val.trans_raw.amount));
+                            } else {
+                                outflow = format!("{:.2}", data::cents_to_dollars(-val.trans_raw.amount));
+                            }
+
+                            let account_options = account_list.iter().map(
+                            |acc| {
+                                view!{
+                                    <option value={acc.account_id} selected={acc.account_id == val.trans_raw.account_id}>
+                                        {acc.display_name.clone()}
+                                    </option>
+                                }
+                            }
+                            ).collect_view();
+
+                            return view!{
+                                <tr>
+                                    <td><input class="form-control form-control-sm" value={val.trans_raw.payee} node_ref=edit_payee_nr/></td>
+                                    <td><input class="form-control form-control-sm" type="date" value={date} node_ref=edit_date_nr/></td>
+                                    <td>
+                                        <select class="form-select form-select-sm" node_ref=edit_account_nr>
+                                            {account_options}
+                                        </select>
+                                    </td>
+                                    <td>
+                                        <input class="form-control form-control-sm" value={val.category_display} node_ref=edit_category_nr
+                                            disabled = move || is_transfer || edit_split.get()
+                                        />
+                                    </td>
+                                    <td><input class="form-control form-control-sm" value={val.trans_raw.notes} node_ref=edit_notes_nr/></td>
+                                    <td><input class="form-control form-control-sm" type="number" value={outflow} node_ref=edit_outflow_nr/></td>
+                                    <td><input class="form-control form-control-sm" type="number" value={inflow} node_ref=edit_inflow_nr/></td>
+                                    <td></td>
+                                    <td class="text-nowrap">
+                                        <button class="btn btn-primary btn-sm"
+                                            on:click = move |_| { save_transaction(trans_id); }
+                                        >
+                                            "Save"
+                                        </button>
+                                        <button class="btn btn-outline-secondary btn-sm ms-1"
+                                            disabled = move || is_transfer || edit_split.get()
+                                            on:click = move |_| {
+                                                edit_lines.set(vec![SplitLine::default(), SplitLine::default()]);
+                                            }
+                                        >
+                                            "Split"
+                                        </button>
+                                        <button class="btn btn-outline-danger btn-sm ms-1"
+                                            on:click = move |_| { delete_transaction(trans_id); }
+                                        >
+                                            "Delete"
+                                        </button>
+                                    </td>
+                                </tr>
+                                {
+                                    move || {
+                                        if !edit_split.get() {
+                                            return ().into_view();
+                                        }
+
+                                        view! {
+                                            <tr>
+                                                <td colspan="9">
+                                                    <SplitEditor lines=edit_lines/>
+                                                </td>
+                                            </tr>
+                                        }.into_view()
+                                    }
+                                }
+                            }.into_view();
+                        }
+
                         let mut outflow = String::new();
                         let mut inflow = String::new();
                         if val.trans_raw.amount > 0 {
@@ -158,34 +862,90 @@ pub fn Transactions() -> impl IntoView {
                             outflow = data::amount_to_display(-val.trans_raw.amount);
                         }
 
-                        let mut date = String::new();
-                        match  time::OffsetDateTime::from_unix_timestamp(val.trans_raw.date) {
-                            Ok(v) => {
-                                // Unwrap safe here, these formats are always known. Incorrect
-                                // format is programmer error.
-                                let format_desc = time::format_description::parse("[year]-[month]-[day]").unwrap();
-                                date = v.format(&format_desc).unwrap();
-                                //date = v.to_string(),
-                            }
-                            _ => {}
-                        }
+                        let category = if val.trans_raw.is_transfer() {
+                            "Transfer".to_string()
+                        } else if val.splits.is_empty() {
+                            val.category_display.clone()
+                        } else {
+                            let names: Vec<String> = val.splits.iter().map(|s| s.category_display.clone()).collect();
+                            format!("Split: {}", names.join(", "))
+                        };
+
+                        let edit_start_lines: Vec<SplitLine> = val.splits.iter().map(SplitLine::from_display).collect();
+
+                        let trans_raw = val.trans_raw.clone();
+                        let is_reconciled = val.trans_raw.is_reconciled();
+                        let is_cleared = val.trans_raw.cleared != ClearedStatus::Uncleared;
+                        let cleared_label = match val.trans_raw.cleared {
+                            ClearedStatus::Uncleared => "c",
+                            ClearedStatus::Cleared => "C",
+                            ClearedStatus::Reconciled => "R",
+                        };
 
                         view!{
-                            <tr>
-                                <td style="width:50%">{val.trans_raw.payee}</td>
+                            <tr on:click = move |_| {
+                                // Reconciled transactions are locked
+                                if is_reconciled {
+                                    return;
+                                }
+                                edit_lines.set(edit_start_lines.clone());
+                                editing_id_set.set(trans_id);
+                            }>
+                                <td style="width:40%">{val.trans_raw.payee}</td>
                                 <td>{date}</td>
                                 <td>{val.account_display}</td>
-                                <td>{val.category_display}</td>
+                                <td>{category}</td>
+                                <td>{val.trans_raw.notes}</td>
                                 <td style="width:5%">{outflow}</td>
                                 <td style="width:5%">{inflow}</td>
+                                <td>
+                                    <button class="btn btn-sm" type="button"
+                                        class:btn-success = is_cleared
+                                        class:btn-outline-secondary = !is_cleared
+                                        disabled = is_reconciled
+                                        on:click = move |ev| {
+                                            ev.stop_propagation();
+                                            toggle_cleared(trans_raw.clone());
+                                        }
+                                    >
+                                        {cleared_label}
+                                    </button>
+                                </td>
+                                <td></td>
                             </tr>
-                        }
+                        }.into_view()
                     }
                     ).collect_view()
                 }
             }
             </tbody>
         </table>
+        <div class="d-flex align-items-center gap-2 mb-3">
+            <button type="button" class="btn btn-outline-secondary btn-sm"
+                disabled = move || !transactions.0.get().has_previous()
+                on:click = move |_| {
+                    query_set.update(|q| q.offset = (q.offset - q.limit).max(0));
+                }
+            >
+                "Previous"
+            </button>
+            <span class="text-secondary">
+            {
+                move || {
+                    let page = transactions.0.get();
+                    format!("Page {} of {}, {} transactions", page.page_number(), page.page_count(), page.total_count)
+                }
+            }
+            </span>
+            <button type="button" class="btn btn-outline-secondary btn-sm"
+                disabled = move || !transactions.0.get().has_next()
+                on:click = move |_| {
+                    query_set.update(|q| q.offset += q.limit);
+                }
+            >
+                "Next"
+            </button>
+        </div>
         <form class="row row-cols-lg-auto" on:submit=create_transaction>
             <div class="col-12">
                 <input class="form-control" placeholder="Payee" node_ref=create_transaction_payee_nr/>
@@ -216,13 +976,66 @@ pub fn Transactions() -> impl IntoView {
             </div>
 
             <div class="col-12">
-                <input class="form-control" placeholder="Category" node_ref=create_transaction_category_nr/>
+                <input class="form-control" placeholder="Category" node_ref=create_transaction_category_nr
+                    disabled = move || create_split.get()
+                />
+            </div>
+
+            <div class="col-12">
+            <button class="btn btn-outline-secondary" type="button"
+                disabled = move || create_split.get()
+                on:click = move |_| {
+                    create_lines.set(vec![SplitLine::default(), SplitLine::default()]);
+                }
+            >
+                "Split"
+            </button>
             </div>
 
             <div class="col-12">
             <button class="btn btn-primary" type="submit">"Add Transaction"</button>
             </div>
         </form>
+        {
+            move || {
+                if !create_split.get() {
+                    return ().into_view();
+                }
+
+                view! { <SplitEditor lines=create_lines/> }.into_view()
+            }
+        }
+
+        <h5 class="mt-4">"Transfer Between Accounts"</h5>
+        <form class="row row-cols-lg-auto" on:submit=create_transfer>
+            <div class="col-12">
+                <select class="form-select" node_ref=transfer_from_nr>
+                    {account_options}
+                </select>
+            </div>
+
+            <div class="col-12">
+                <select class="form-select" node_ref=transfer_to_nr>
+                    {account_options}
+                </select>
+            </div>
+
+            <div class="col-12">
+                <input class="form-control" placeholder="Amount" type="number" node_ref=transfer_amount_nr/>
+            </div>
+
+            <div class="col-12">
+                <input class="form-control" placeholder="Date" type="date" node_ref=transfer_date_nr/>
+            </div>
+
+            <div class="col-12">
+                <input class="form-control" placeholder="Notes" node_ref=transfer_notes_nr/>
+            </div>
+
+            <div class="col-12">
+            <button class="btn btn-primary" type="submit">"Transfer"</button>
+            </div>
+        </form>
 
     }
 }